@@ -0,0 +1,124 @@
+use crate::cache::cache_op_executors::matching_keys;
+use crate::cache::cache_operations::{Invalidate, InvalidationPattern};
+use crate::cache::compression::unpack;
+use crate::cache::inner_cache::CacheEntry;
+use crate::cache::{namespaced, Cache, CACHE_REQS_PREFIX, CACHE_RESP_PREFIX, CACHE_TAG_PREFIX};
+use crate::models::service::admin::{CacheFamilyStats, CacheKey, CachedEntry, FlushReport};
+use crate::providers::info::TOKENS_KEY;
+use crate::utils::errors::ApiResult;
+
+// Keys of a family whose memory usage is looked up, the usage of the others is extrapolated
+const FAMILY_MEMORY_SAMPLE_SIZE: usize = 1000;
+
+/// Same keys that [flush] reports for the pattern
+pub async fn list_keys(
+    cache: &impl Cache,
+    pattern: &InvalidationPattern,
+    chain_id: u64,
+    limit: usize,
+) -> Vec<CacheKey> {
+    let mut keys = matching_keys(cache, pattern, chain_id).await;
+    keys.sort();
+    keys.truncate(limit);
+    let ttls = cache.times_to_live(&keys).await;
+    let sizes = cache.memory_usage(&keys).await;
+    keys.into_iter()
+        .zip(ttls)
+        .zip(sizes)
+        .map(|((key, ttl), size)| CacheKey { key, ttl, size })
+        .collect()
+}
+
+/// `key` is expected without the namespace, `field` is required for hashes like the token cache
+/// (`dip_ti_<chain_id>`)
+pub async fn get_entry(
+    cache: &impl Cache,
+    key: &str,
+    field: Option<&str>,
+) -> ApiResult<CachedEntry> {
+    let namespaced_key = namespaced(key);
+    let cached = match field {
+        Some(field) => cache.get_from_hash(&namespaced_key, field).await,
+        None => cache.fetch(&namespaced_key).await,
+    };
+    let cached = cached.ok_or(client_error!(404, "Cache entry not found"))?;
+    let ttl = cache.time_to_live(&namespaced_key).await;
+
+    if key.starts_with(CACHE_REQS_PREFIX) {
+        let entry = CacheEntry::parse(&cached).ok_or(client_error!(422, "Corrupt cache entry"))?;
+        Ok(CachedEntry {
+            key: namespaced_key,
+            ttl,
+            version: Some(entry.version),
+            status: Some(entry.status),
+            created_at: entry.created_at,
+            url: entry.url,
+            content_hash: entry.content_hash,
+            body: entry.body,
+        })
+    } else {
+        Ok(CachedEntry {
+            key: namespaced_key,
+            ttl,
+            version: None,
+            status: None,
+            created_at: None,
+            url: None,
+            content_hash: None,
+            body: unpack(cached).ok_or(client_error!(422, "Corrupt cache entry"))?,
+        })
+    }
+}
+
+/// The memory of families with more than `FAMILY_MEMORY_SAMPLE_SIZE` keys is estimated from the
+/// usage of that many of them
+pub async fn family_stats(cache: &impl Cache) -> Vec<CacheFamilyStats> {
+    let families = vec![
+        (CACHE_RESP_PREFIX, format!("{}_*", CACHE_RESP_PREFIX)),
+        (CACHE_REQS_PREFIX, format!("{}_*", CACHE_REQS_PREFIX)),
+        (TOKENS_KEY, format!("{}_*", TOKENS_KEY)),
+        (CACHE_TAG_PREFIX, format!("{}_*", CACHE_TAG_PREFIX)),
+    ];
+    let mut stats = Vec::with_capacity(families.len());
+    for (family, pattern) in families {
+        let keys = cache.keys(&namespaced(&pattern)).await;
+        let sample = &keys[..keys.len().min(FAMILY_MEMORY_SAMPLE_SIZE)];
+        let sizes: Vec<usize> = cache
+            .memory_usage(sample)
+            .await
+            .into_iter()
+            .flatten()
+            .collect();
+        let estimated_memory = if sizes.is_empty() {
+            0
+        } else {
+            sizes.iter().sum::<usize>() * keys.len() / sizes.len()
+        };
+        stats.push(CacheFamilyStats {
+            family: family.to_string(),
+            keys: keys.len(),
+            sampled_keys: sizes.len(),
+            estimated_memory,
+        });
+    }
+    stats
+}
+
+/// The report lists the keys the deletion targets, patterns for an address or hash are resolved
+/// through its tag of `chain_id` in both cases
+pub async fn flush(
+    cache: &impl Cache,
+    pattern: InvalidationPattern,
+    chain_id: u64,
+    dry_run: bool,
+) -> ApiResult<FlushReport> {
+    let mut keys = matching_keys(cache, &pattern, chain_id).await;
+    keys.sort();
+    if !dry_run {
+        Invalidate::new(pattern)
+            .chain_id(chain_id)
+            .execute(cache)
+            .await?;
+    }
+    Ok(FlushReport { dry_run, keys })
+}
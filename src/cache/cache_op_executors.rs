@@ -1,86 +1,337 @@
 use crate::cache::cache_operations::{CacheResponse, InvalidationPattern, RequestCached};
-use crate::cache::inner_cache::CachedWithCode;
-use crate::cache::{Cache, CACHE_REQS_PREFIX, CACHE_RESP_PREFIX};
+use crate::cache::compression::{pack, unpack, CompressionSettings};
+use crate::cache::inner_cache::CacheEntry;
+use crate::cache::single_flight::coalesce;
+use crate::cache::tags::extract_tags;
+use crate::cache::{namespaced, response_key, Cache, CACHE_REQS_PREFIX};
+use crate::monitoring::cache::{
+    report_generator_duration, report_request_cache, report_response_cache,
+    report_upstream_duration,
+};
+use crate::monitoring::metrics::endpoint_template;
+use crate::monitoring::tracing::{in_span, set_span_attribute, upstream_headers};
 use crate::utils::errors::{ApiError, ApiResult};
+use lazy_static::lazy_static;
+use opentelemetry::trace::FutureExt;
+use opentelemetry::KeyValue;
 use rocket::response::content;
+use rocket::tokio::task::JoinHandle;
 use serde::Serialize;
-use std::time::Duration;
+use std::collections::HashSet;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
-pub(super) fn invalidate(cache: &impl Cache, pattern: &InvalidationPattern) {
-    cache.invalidate_pattern(pattern.to_pattern_string().as_str());
+lazy_static! {
+    // Keys with a revalidation in flight, so that each stale entry is regenerated only once
+    static ref REVALIDATING: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
 }
 
-pub(super) async fn cache_response<S>(
+/// Keys that [invalidate] deletes, looked up the same way
+pub(super) async fn matching_keys(
+    cache: &impl Cache,
+    pattern: &InvalidationPattern,
+    chain_id: u64,
+) -> Vec<String> {
+    let pattern_string = pattern.to_pattern_string();
+    match pattern.to_tag(chain_id) {
+        Some(tag) => cache.tagged_keys(&tag, &pattern_string).await,
+        None => cache.keys(&pattern_string).await,
+    }
+}
+
+pub(super) async fn invalidate(
     cache: &impl Cache,
+    pattern: &InvalidationPattern,
+    chain_id: u64,
+) -> ApiResult<()> {
+    let pattern_string = pattern.to_pattern_string();
+    let deleted = match pattern.to_tag(chain_id) {
+        Some(tag) => cache.invalidate_tag(&tag, &pattern_string).await,
+        None => cache.invalidate_pattern(&pattern_string).await,
+    };
+    if deleted {
+        Ok(())
+    } else {
+        Err(ApiError::new_from_message_with_code(
+            503,
+            format!("Could not invalidate {}", pattern_string),
+        ))
+    }
+}
+
+pub(super) async fn cache_response<S>(
+    cache: &(impl Cache + Clone + 'static),
     cache_response: &CacheResponse<'_, S>,
 ) -> ApiResult<content::Json<String>>
 where
     S: Serialize,
 {
-    let cache_key = format!("{}_{}", CACHE_RESP_PREFIX, cache_response.key);
-    let cached = cache.fetch(&cache_key);
+    let cache_key = response_key(&cache_response.key);
+    let cached = cache.fetch(&cache_key).await.and_then(unpack);
     match cached {
-        Some(value) => Ok(content::Json(value)),
+        Some(value) => {
+            report_response_cache(&cache_response.key, "hit");
+            if let Some(revalidator) = &cache_response.revalidator {
+                if is_stale(
+                    cache,
+                    &cache_key,
+                    cache_response.duration,
+                    cache_response.stale_duration,
+                )
+                .await
+                {
+                    let cache = cache.clone();
+                    let revalidator = revalidator.clone();
+                    let duration = cache_response.duration;
+                    revalidate(cache_key.to_string(), async move {
+                        match revalidator().await {
+                            Ok(resp_string) => {
+                                create_tagged(
+                                    &cache,
+                                    &cache_key,
+                                    &pack(&resp_string, CompressionSettings::configured()),
+                                    duration,
+                                )
+                                .await
+                            }
+                            Err(error) => {
+                                log::warn!("Revalidation of {} failed: {}", cache_key, error)
+                            }
+                        }
+                    });
+                }
+            }
+            Ok(content::Json(value))
+        }
         None => {
-            let resp_string = serde_json::to_string(&cache_response.generate().await?)?;
-            cache.create(&cache_key, &resp_string, cache_response.duration);
-            Ok(content::Json(resp_string))
+            report_response_cache(&cache_response.key, "miss");
+            coalesce(
+                cache,
+                &cache_key,
+                || async {
+                    cache
+                        .fetch(&cache_key)
+                        .await
+                        .and_then(unpack)
+                        .map(|value| Ok(content::Json(value)))
+                },
+                || generate_and_cache(cache, &cache_key, cache_response),
+            )
+            .await
         }
     }
 }
 
-pub(super) async fn request_cached(
+async fn generate_and_cache<S>(
     cache: &impl Cache,
+    cache_key: &str,
+    cache_response: &CacheResponse<'_, S>,
+) -> ApiResult<content::Json<String>>
+where
+    S: Serialize,
+{
+    let started_at = Instant::now();
+    let generated = in_span(
+        &format!("generate {}", endpoint_template(&cache_response.key)),
+        cache_response.generate(),
+    )
+    .await;
+    report_generator_duration(&cache_response.key, started_at.elapsed());
+    let resp_string = serde_json::to_string(&generated?)?;
+    create_tagged(
+        cache,
+        cache_key,
+        &pack(&resp_string, CompressionSettings::configured()),
+        cache_response.duration,
+    )
+    .await;
+    Ok(content::Json(resp_string))
+}
+
+pub(super) async fn request_cached(
+    cache: &(impl Cache + Clone + 'static),
     client: &reqwest::Client,
     operation: &RequestCached,
 ) -> ApiResult<String> {
-    let cache_key = format!("{}_{}", CACHE_REQS_PREFIX, &operation.url);
-    match cache.fetch(&cache_key) {
-        Some(cached) => CachedWithCode::split(&cached).to_result(),
-        None => {
-            let request = client
-                .get(&operation.url)
-                .timeout(Duration::from_millis(operation.request_timeout));
-
-            let response = (request.send().await).map_err(|err| {
-                if operation.cache_all_errors {
-                    cache.create(
-                        &cache_key,
-                        &CachedWithCode::join(500, &format!("{:?}", &err)),
-                        operation.error_cache_duration,
-                    );
-                }
-                err
-            })?;
-            let status_code = response.status().as_u16();
-
-            // Early return and no caching if the error is a 500 or greater
-            let is_server_error = response.status().is_server_error();
-            if !operation.cache_all_errors && is_server_error {
-                return Err(ApiError::from_backend_error(
-                    status_code,
-                    &format!("Got server error for {}", response.text().await?),
-                ));
+    let url_template = endpoint_template(&operation.url);
+    in_span(&format!("GET {}", url_template), async {
+        set_span_attribute(KeyValue::new("http.url_template", url_template.to_string()));
+        request_cached_in_span(cache, client, operation).await
+    })
+    .await
+}
+
+async fn request_cached_in_span(
+    cache: &(impl Cache + Clone + 'static),
+    client: &reqwest::Client,
+    operation: &RequestCached,
+) -> ApiResult<String> {
+    let cache_key = namespaced(&format!("{}_{}", CACHE_REQS_PREFIX, &operation.url));
+    let cached = cache.fetch(&cache_key).await;
+    match cached.and_then(|cached| CacheEntry::parse(&cached)) {
+        Some(entry) => {
+            let outcome = if entry.is_error() { "error_hit" } else { "hit" };
+            report_request_cache(&operation.url, outcome);
+            set_span_attribute(KeyValue::new("cache.hit", true));
+            if !entry.is_error()
+                && is_stale(
+                    cache,
+                    &cache_key,
+                    operation.cache_duration,
+                    operation.stale_duration,
+                )
+                .await
+            {
+                let cache = cache.clone();
+                let client = client.clone();
+                let operation = operation.clone();
+                revalidate(cache_key, async move {
+                    // Errors are cached (or not) the same way as for any other request
+                    let _ = request_and_cache(&cache, &client, &operation).await;
+                });
             }
+            entry.to_result()
+        }
+        None => {
+            report_request_cache(&operation.url, "miss");
+            set_span_attribute(KeyValue::new("cache.hit", false));
+            coalesce(
+                cache,
+                &cache_key,
+                || async {
+                    cache
+                        .fetch(&cache_key)
+                        .await
+                        .and_then(|cached| CacheEntry::parse(&cached))
+                        .map(|entry| entry.to_result())
+                },
+                || request_and_cache(cache, client, operation),
+            )
+            .await
+        }
+    }
+}
 
-            let is_client_error = response.status().is_client_error();
-            let raw_data = response.text().await?;
+async fn request_and_cache(
+    cache: &impl Cache,
+    client: &reqwest::Client,
+    operation: &RequestCached,
+) -> ApiResult<String> {
+    let cache_key = namespaced(&format!("{}_{}", CACHE_REQS_PREFIX, &operation.url));
+    let request = client
+        .get(&operation.url)
+        .headers(upstream_headers(&operation.url))
+        .timeout(Duration::from_millis(operation.request_timeout));
 
-            if is_client_error || is_server_error {
-                cache.create(
+    let started_at = Instant::now();
+    let response = request.send().await;
+    let status = response.as_ref().map_or(String::from("error"), |response| {
+        response.status().as_u16().to_string()
+    });
+    report_upstream_duration(&operation.url, &status, started_at.elapsed());
+    set_span_attribute(KeyValue::new("http.status_code", status));
+    let response = match response {
+        Ok(response) => response,
+        Err(err) => {
+            if operation.cache_all_errors {
+                create_tagged(
+                    cache,
                     &cache_key,
-                    &CachedWithCode::join(status_code, &raw_data),
+                    &CacheEntry::new(500, &format!("{:?}", &err), &operation.url)
+                        .to_cache_string(CompressionSettings::configured()),
                     operation.error_cache_duration,
-                );
-                Err(ApiError::from_backend_error(status_code, &raw_data))
-            } else {
-                cache.create(
-                    &cache_key,
-                    &CachedWithCode::join(status_code, &raw_data),
-                    operation.cache_duration,
-                );
-                Ok(raw_data.to_string())
+                )
+                .await;
             }
+            return Err(err.into());
         }
+    };
+    let status_code = response.status().as_u16();
+
+    // Early return and no caching if the error is a 500 or greater
+    let is_server_error = response.status().is_server_error();
+    if !operation.cache_all_errors && is_server_error {
+        return Err(ApiError::from_backend_error(
+            status_code,
+            &format!("Got server error for {}", response.text().await?),
+        ));
+    }
+
+    let is_client_error = response.status().is_client_error();
+    let raw_data = response.text().await?;
+
+    if is_client_error || is_server_error {
+        create_tagged(
+            cache,
+            &cache_key,
+            &CacheEntry::new(status_code, &raw_data, &operation.url)
+                .to_cache_string(CompressionSettings::configured()),
+            operation.error_cache_duration,
+        )
+        .await;
+        Err(ApiError::from_backend_error(status_code, &raw_data))
+    } else {
+        create_tagged(
+            cache,
+            &cache_key,
+            &CacheEntry::new(status_code, &raw_data, &operation.url)
+                .to_cache_string(CompressionSettings::configured()),
+            operation.cache_duration,
+        )
+        .await;
+        Ok(raw_data.to_string())
     }
 }
+
+async fn create_tagged(cache: &impl Cache, key: &str, value: &str, timeout: usize) {
+    cache.create(key, value, timeout).await;
+    let tags = extract_tags(key);
+    if !tags.is_empty() {
+        cache.tag(key, &tags, timeout).await;
+    }
+}
+
+// The age of an entry is derived from its remaining time to live
+async fn is_stale(
+    cache: &impl Cache,
+    cache_key: &str,
+    duration: usize,
+    stale_duration: Option<usize>,
+) -> bool {
+    match stale_duration {
+        Some(stale_duration) => cache
+            .time_to_live(cache_key)
+            .await
+            .map_or(false, |ttl| duration.saturating_sub(ttl) >= stale_duration),
+        None => false,
+    }
+}
+
+// Releases the key once its revalidation is over, also if it panicked or was cancelled
+struct Revalidating(String);
+
+impl Drop for Revalidating {
+    fn drop(&mut self) {
+        REVALIDATING.lock().unwrap().remove(&self.0);
+    }
+}
+
+/// `None` if a revalidation of `cache_key` is already in flight
+pub(super) fn revalidate(
+    cache_key: String,
+    revalidation: impl Future<Output = ()> + Send + 'static,
+) -> Option<JoinHandle<()>> {
+    if !REVALIDATING.lock().unwrap().insert(cache_key.to_string()) {
+        return None;
+    }
+    let revalidating = Revalidating(cache_key);
+    // The revalidation is traced as part of the request that triggered it
+    Some(rocket::tokio::spawn(
+        async move {
+            let _revalidating = revalidating;
+            revalidation.await;
+        }
+        .with_current_context(),
+    ))
+}
@@ -1,5 +1,9 @@
 use crate::cache::cache_op_executors::{cache_response, invalidate, request_cached};
-use crate::cache::{Cache, CACHE_REQS_PREFIX, CACHE_REQS_RESP_PREFIX, CACHE_RESP_PREFIX};
+use crate::cache::tags::tag_for;
+use crate::cache::{
+    namespaced, Cache, CACHE_REQS_PREFIX, CACHE_REQS_RESP_PREFIX, CACHE_RESP_PREFIX,
+};
+use crate::config::chains::default_chain;
 use crate::config::{
     default_request_timeout, request_cache_duration, request_error_cache_duration,
 };
@@ -11,7 +15,9 @@ use rocket::response::content;
 use serde::Deserialize;
 use serde::Serialize;
 use std::future::Future;
+use std::sync::Arc;
 
+#[derive(Clone)]
 pub enum Database {
     Info = 1,
     Default = 2,
@@ -19,6 +25,7 @@ pub enum Database {
 
 pub struct Invalidate {
     pattern: InvalidationPattern,
+    chain_id: u64,
     database: Database,
 }
 
@@ -34,6 +41,8 @@ pub enum InvalidationScope {
 pub enum InvalidationPattern {
     Any(InvalidationScope, String),
     Transactions(InvalidationScope, String),
+    QueuedTransactions(InvalidationScope, String),
+    SafeInfo(InvalidationScope, String),
     Balances(InvalidationScope, String),
     Collectibles(InvalidationScope, String),
     Transfers(InvalidationScope, String),
@@ -43,6 +52,10 @@ pub enum InvalidationPattern {
 
 impl InvalidationPattern {
     pub(super) fn to_pattern_string(&self) -> String {
+        namespaced(&self.to_unscoped_pattern_string())
+    }
+
+    fn to_unscoped_pattern_string(&self) -> String {
         match &self {
             InvalidationPattern::Any(scope, value) => {
                 format!("{}*{}*", scope.invalidation_scope_string(), &value)
@@ -71,8 +84,45 @@ impl InvalidationPattern {
                     value
                 )
             }
+            // `transactions/queued` responses and the `multisig-transactions/?` requests they are
+            // built from, the `all-transactions/?` requests of the history match as well
+            InvalidationPattern::QueuedTransactions(scope, value) => {
+                format!(
+                    "{}*/{}/*transactions/[q?]*",
+                    scope.invalidation_scope_string(),
+                    value
+                )
+            }
+            // Upstream requests end with a `/` and responses don't, a trailing `*` would match
+            // every other endpoint of the Safe as well
+            InvalidationPattern::SafeInfo(scope, value) => match scope {
+                InvalidationScope::Requests => {
+                    format!("{}*/safes/{}/", scope.invalidation_scope_string(), value)
+                }
+                InvalidationScope::Responses => {
+                    format!("{}*/safes/{}", scope.invalidation_scope_string(), value)
+                }
+                InvalidationScope::Both => {
+                    format!("{}*/safes/{}*", scope.invalidation_scope_string(), value)
+                }
+            },
             InvalidationPattern::Contracts => String::from("*contract*"),
-            InvalidationPattern::Tokens => String::from(TOKENS_KEY),
+            InvalidationPattern::Tokens => format!("{}_*", TOKENS_KEY),
+        }
+    }
+
+    /// Patterns for a single address or hash are resolved through its tag of `chain_id` instead
+    /// of a SCAN
+    pub(super) fn to_tag(&self, chain_id: u64) -> Option<String> {
+        match &self {
+            InvalidationPattern::Any(_, value)
+            | InvalidationPattern::Balances(_, value)
+            | InvalidationPattern::Collectibles(_, value)
+            | InvalidationPattern::Transfers(_, value)
+            | InvalidationPattern::Transactions(_, value)
+            | InvalidationPattern::QueuedTransactions(_, value)
+            | InvalidationPattern::SafeInfo(_, value) => tag_for(chain_id, value),
+            InvalidationPattern::Contracts | InvalidationPattern::Tokens => None,
         }
     }
 }
@@ -91,17 +141,25 @@ impl Invalidate {
     pub fn new(pattern: InvalidationPattern) -> Self {
         Invalidate {
             pattern,
+            chain_id: default_chain().chain_id,
             database: Database::Default,
         }
     }
 
+    /// Patterns that aren't resolved through a tag match the keys of every chain
+    pub fn chain_id(&mut self, chain_id: u64) -> &mut Self {
+        self.chain_id = chain_id;
+        self
+    }
+
     fn database(&mut self, database: Database) -> &mut Self {
         self.database = database;
         self
     }
 
-    pub fn execute(&self, cache: &impl Cache) {
-        invalidate(cache, &self.pattern)
+    /// Fails if the cache backend could not delete the matching keys
+    pub async fn execute(&self, cache: &impl Cache) -> ApiResult<()> {
+        invalidate(cache, &self.pattern, self.chain_id).await
     }
 }
 
@@ -112,8 +170,11 @@ where
     database: Database,
     pub key: String,
     pub duration: usize,
+    pub stale_duration: Option<usize>,
     // "dyn" allows setting the type of the BoxFuture to different times in runtime
-    pub resp_generator: Option<Box<dyn Fn() -> BoxFuture<'a, ApiResult<R>> + Send + Sync + 'a>>,
+    pub resp_generator: Option<Arc<dyn Fn() -> BoxFuture<'a, ApiResult<R>> + Send + Sync + 'a>>,
+    pub(super) revalidator:
+        Option<Arc<dyn Fn() -> BoxFuture<'static, ApiResult<String>> + Send + Sync>>,
 }
 
 impl<'a, R> CacheResponse<'a, R>
@@ -125,7 +186,9 @@ where
             key,
             database: Database::Default,
             duration: request_cache_duration(),
+            stale_duration: None,
             resp_generator: None,
+            revalidator: None,
         }
     }
 
@@ -144,7 +207,7 @@ where
         F: Fn() -> Fut + Send + Sync + 'a,
         Fut: Future<Output = ApiResult<R>> + Send + 'a,
     {
-        self.resp_generator = Some(Box::new(move || resp_generator().boxed()));
+        self.resp_generator = Some(Arc::new(move || resp_generator().boxed()));
         self
     }
 
@@ -152,16 +215,41 @@ where
         (self.resp_generator.as_ref().unwrap())().await
     }
 
-    pub async fn execute(&self, cache: &impl Cache) -> ApiResult<content::Json<String>> {
+    pub async fn execute(
+        &self,
+        cache: &(impl Cache + Clone + 'static),
+    ) -> ApiResult<content::Json<String>> {
         cache_response(cache, &self).await
     }
 }
 
+impl<R> CacheResponse<'static, R>
+where
+    R: Serialize + 'static,
+{
+    /// Entries older than `stale_duration` are served while a background task regenerates them.
+    /// The generator has to own its data to outlive the request, so it must be set beforehand.
+    pub fn stale_while_revalidate(&mut self, stale_duration: usize) -> &mut Self {
+        let resp_generator = self
+            .resp_generator
+            .clone()
+            .expect("resp_generator must be set before stale_while_revalidate");
+        self.stale_duration = Some(stale_duration);
+        self.revalidator = Some(Arc::new(move || {
+            let resp_generator = resp_generator.clone();
+            async move { Ok(serde_json::to_string(&resp_generator().await?)?) }.boxed()
+        }));
+        self
+    }
+}
+
+#[derive(Clone)]
 pub struct RequestCached {
     database: Database,
     pub url: String,
     pub request_timeout: u64,
     pub cache_duration: usize,
+    pub stale_duration: Option<usize>,
     pub error_cache_duration: usize,
     pub cache_all_errors: bool,
 }
@@ -173,6 +261,7 @@ impl RequestCached {
             url,
             request_timeout: default_request_timeout(),
             cache_duration: request_cache_duration(),
+            stale_duration: None,
             error_cache_duration: request_error_cache_duration(),
             cache_all_errors: false,
         }
@@ -193,6 +282,13 @@ impl RequestCached {
         self
     }
 
+    /// Successful responses older than `stale_duration` are served while they are requested
+    /// again in the background
+    pub fn stale_duration(&mut self, stale_duration: usize) -> &mut Self {
+        self.stale_duration = Some(stale_duration);
+        self
+    }
+
     pub fn error_cache_duration(&mut self, error_cache_duration: usize) -> &mut Self {
         self.error_cache_duration = error_cache_duration;
         self
@@ -203,7 +299,11 @@ impl RequestCached {
         self
     }
 
-    pub async fn execute(&self, client: &reqwest::Client, cache: &impl Cache) -> ApiResult<String> {
+    pub async fn execute(
+        &self,
+        client: &reqwest::Client,
+        cache: &(impl Cache + Clone + 'static),
+    ) -> ApiResult<String> {
         assert!(self.request_timeout > 0);
         request_cached(cache, &client, self).await
     }
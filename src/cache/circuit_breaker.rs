@@ -0,0 +1,68 @@
+use crate::monitoring::cache::report_cache_bypass;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Default)]
+struct State {
+    opened_at: Option<Instant>,
+    // Set while a request probes the backend
+    probe_started_at: Option<Instant>,
+}
+
+/// Keeps track of the availability of the cache backend.
+///
+/// After a failure the circuit opens and the cache is bypassed. Once `cooldown` has elapsed a
+/// single request is let through to probe the backend, closing the circuit again on success. A
+/// probe that doesn't report back within `probe_timeout`, e.g. because its request was dropped,
+/// is given up and the next request probes instead.
+pub struct CircuitBreaker {
+    state: Mutex<State>,
+    cooldown: Duration,
+    probe_timeout: Duration,
+}
+
+impl CircuitBreaker {
+    pub fn new(cooldown: Duration, probe_timeout: Duration) -> Self {
+        CircuitBreaker {
+            state: Mutex::new(State::default()),
+            cooldown,
+            probe_timeout,
+        }
+    }
+
+    pub fn allows_request(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let opened_at = match state.opened_at {
+            None => return true,
+            Some(opened_at) => opened_at,
+        };
+        let probe_pending = state.probe_started_at.map_or(false, |started_at| {
+            started_at.elapsed() < self.probe_timeout
+        });
+        if opened_at.elapsed() < self.cooldown || probe_pending {
+            return false;
+        }
+        state.probe_started_at = Some(Instant::now());
+        true
+    }
+
+    pub fn record_success(&self) {
+        let mut state = self.state.lock().unwrap();
+        if state.opened_at.take().is_some() {
+            report_cache_bypass(false);
+        }
+        state.probe_started_at = None;
+    }
+
+    pub fn record_failure(&self) {
+        let mut state = self.state.lock().unwrap();
+        if state.opened_at.replace(Instant::now()).is_none() {
+            report_cache_bypass(true);
+        }
+        state.probe_started_at = None;
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.state.lock().unwrap().opened_at.is_some()
+    }
+}
@@ -0,0 +1,83 @@
+use crate::config::{
+    cache_compression_enabled, cache_compression_hash_field_threshold, cache_compression_threshold,
+};
+use crate::monitoring::cache::report_compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::io::{Read, Write};
+
+// JSON values never start with this prefix, so plain and compressed values can live side by side
+const COMPRESSED_PREFIX: &'static str = "gz:";
+
+/// Which values are compressed before they are stored
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CompressionSettings {
+    pub enabled: bool,
+    /// Size in bytes from which values are compressed
+    pub threshold: usize,
+}
+
+impl CompressionSettings {
+    /// `CACHE_COMPRESSION_ENABLED` and `CACHE_COMPRESSION_THRESHOLD`
+    pub fn configured() -> Self {
+        CompressionSettings {
+            enabled: cache_compression_enabled(),
+            threshold: cache_compression_threshold(),
+        }
+    }
+
+    /// `CACHE_COMPRESSION_HASH_FIELD_THRESHOLD` instead, for the fields of hashes
+    pub fn configured_for_hash_fields() -> Self {
+        CompressionSettings {
+            threshold: cache_compression_hash_field_threshold(),
+            ..CompressionSettings::configured()
+        }
+    }
+}
+
+/// Gzips and base64 encodes `value` if compression is enabled and `value` is at least
+/// `threshold` bytes long. Values that wouldn't get any smaller are left as they are.
+pub(super) fn compress(value: &str, settings: CompressionSettings) -> Option<String> {
+    if !settings.enabled || value.len() < settings.threshold {
+        return None;
+    }
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    let compressed = encoder
+        .write_all(value.as_bytes())
+        .and_then(|_| encoder.finish())
+        .map_err(|error| log::error!("Could not compress cache value: {:?}", error))
+        .ok()?;
+    let encoded = base64::encode(compressed);
+    report_compression(value.len(), encoded.len());
+    if encoded.len() >= value.len() {
+        return None;
+    }
+    Some(encoded)
+}
+
+pub(super) fn decompress(value: &str) -> Option<String> {
+    let compressed = base64::decode(value).ok()?;
+    let mut decompressed = String::new();
+    GzDecoder::new(compressed.as_slice())
+        .read_to_string(&mut decompressed)
+        .map_err(|error| log::error!("Could not decompress cache value: {:?}", error))
+        .ok()?;
+    Some(decompressed)
+}
+
+/// Compresses `value` when it's worth it, marking it so that [unpack] can tell the formats apart
+pub fn pack(value: &str, settings: CompressionSettings) -> String {
+    match compress(value, settings) {
+        Some(compressed) => format!("{}{}", COMPRESSED_PREFIX, compressed),
+        None => value.to_string(),
+    }
+}
+
+/// Values that can't be decompressed are returned as `None`, same as a cache miss
+pub fn unpack(value: String) -> Option<String> {
+    match value.strip_prefix(COMPRESSED_PREFIX) {
+        Some(compressed) => decompress(compressed),
+        None => Some(value),
+    }
+}
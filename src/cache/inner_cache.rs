@@ -1,39 +1,105 @@
+use crate::cache::compression::{compress, decompress, CompressionSettings};
 use crate::utils::errors::ApiError;
+use chrono::Utc;
+use ethcontract_common::hash::keccak256;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, PartialEq)]
-pub(super) struct CachedWithCode {
-    pub(super) code: u16,
-    pub(super) data: String,
+/// Envelope for the responses of [RequestCached](crate::cache::cache_operations::RequestCached)
+///
+/// Entries written before the envelope existed are stored as `"<status>;<body>"`, these are
+/// still read as version 0 entries without any metadata.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct CacheEntry {
+    pub(super) version: u8,
+    pub(super) status: u16,
+    pub(super) body: String,
+    pub(super) created_at: Option<i64>,
+    pub(super) url: Option<String>,
+    pub(super) content_hash: Option<String>,
+    #[serde(default)]
+    pub(super) compressed: bool,
 }
 
-impl CachedWithCode {
-    const SEPARATOR: &'static str = ";";
-
-    pub(super) fn split(cached: &str) -> Self {
-        let cached_with_code: Vec<&str> = cached.splitn(2, CachedWithCode::SEPARATOR).collect();
-        CachedWithCode {
-            code: cached_with_code
-                .get(0)
-                .expect("Must have a status code")
-                .parse()
-                .expect("Not a valid Http code"),
-            data: cached_with_code.get(1).expect("Must have data").to_string(),
+impl CacheEntry {
+    pub(super) const VERSION: u8 = 1;
+    const LEGACY_SEPARATOR: &'static str = ";";
+
+    pub(super) fn new(status: u16, body: &str, url: &str) -> Self {
+        CacheEntry {
+            version: CacheEntry::VERSION,
+            status,
+            body: body.to_string(),
+            created_at: Some(Utc::now().timestamp()),
+            url: Some(url.to_string()),
+            content_hash: Some(to_hex_string!(keccak256(body.as_bytes().to_vec()))),
+            compressed: false,
+        }
+    }
+
+    /// Corrupt entries are logged and returned as `None`, so that they are handled as a miss
+    pub(super) fn parse(cached: &str) -> Option<Self> {
+        let entry = if cached.starts_with('{') {
+            serde_json::from_str::<CacheEntry>(cached)
+                .ok()
+                .and_then(CacheEntry::decompressed)
+        } else {
+            CacheEntry::parse_legacy(cached)
+        };
+        if entry.is_none() {
+            log::warn!("Ignoring corrupt cache entry: {:.100}", cached);
+        }
+        entry
+    }
+
+    fn parse_legacy(cached: &str) -> Option<Self> {
+        let mut parts = cached.splitn(2, CacheEntry::LEGACY_SEPARATOR);
+        let status = parts.next()?.parse().ok()?;
+        let body = parts.next()?;
+        Some(CacheEntry {
+            version: 0,
+            status,
+            body: body.to_string(),
+            created_at: None,
+            url: None,
+            content_hash: None,
+            compressed: false,
+        })
+    }
+
+    // The flag only describes how the body is stored, parsed entries always hold the plain body
+    fn decompressed(self) -> Option<Self> {
+        if !self.compressed {
+            return Some(self);
         }
+        Some(CacheEntry {
+            body: decompress(&self.body)?,
+            compressed: false,
+            ..self
+        })
     }
 
-    pub(super) fn join(code: u16, data: &str) -> String {
-        format!("{}{}{}", code, CachedWithCode::SEPARATOR, data)
+    pub(super) fn to_cache_string(&self, compression: CompressionSettings) -> String {
+        let stored = match compress(&self.body, compression) {
+            Some(body) => CacheEntry {
+                body,
+                compressed: true,
+                ..self.clone()
+            },
+            None => self.clone(),
+        };
+        serde_json::to_string(&stored).expect("Cache entries are always serializable")
     }
 
     pub(super) fn is_error(&self) -> bool {
-        200 > self.code || self.code >= 400
+        200 > self.status || self.status >= 400
     }
 
     pub(super) fn to_result(&self) -> Result<String, ApiError> {
         if self.is_error() {
-            Err(ApiError::from_backend_error(self.code, &self.data))
+            Err(ApiError::from_backend_error(self.status, &self.body))
         } else {
-            Ok(String::from(&self.data))
+            Ok(String::from(&self.body))
         }
     }
 }
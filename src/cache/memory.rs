@@ -0,0 +1,495 @@
+use crate::cache::Cache;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+enum Value {
+    Plain(String),
+    Hash(HashMap<String, String>),
+}
+
+struct Entry {
+    value: Value,
+    expires_at: Option<Instant>,
+    // `None` for entries that are never evicted
+    last_used: Option<u64>,
+}
+
+impl Entry {
+    fn is_expired(&self, now: Instant) -> bool {
+        self.expires_at
+            .map_or(false, |expires_at| expires_at <= now)
+    }
+}
+
+#[derive(Default)]
+struct Store {
+    entries: HashMap<String, Entry>,
+    // Access tick -> key of the evictable entries, the first one is the least recently used
+    usage: BTreeMap<u64, String>,
+    tick: u64,
+}
+
+impl Store {
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn live_entry(&mut self, id: &str, now: Instant) -> Option<&mut Entry> {
+        if self.entries.get(id)?.is_expired(now) {
+            self.remove(id);
+            return None;
+        }
+        let tick = self.next_tick();
+        let entry = self.entries.get_mut(id)?;
+        if let Some(last_used) = entry.last_used {
+            self.usage.remove(&last_used);
+            self.usage.insert(tick, id.to_string());
+            entry.last_used = Some(tick);
+        }
+        Some(entry)
+    }
+
+    fn insert(&mut self, id: &str, value: Value, expires_at: Option<Instant>, max_entries: usize) {
+        let tick = self.next_tick();
+        self.put(id, value, expires_at, Some(tick));
+        self.evict(max_entries);
+    }
+
+    /// Kept until it expires or is removed, and not counted towards `max_entries`. For entries
+    /// that other entries depend on, such as tags.
+    fn insert_unevictable(&mut self, id: &str, value: Value, expires_at: Option<Instant>) {
+        self.put(id, value, expires_at, None);
+    }
+
+    fn put(&mut self, id: &str, value: Value, expires_at: Option<Instant>, last_used: Option<u64>) {
+        self.remove(id);
+        if let Some(tick) = last_used {
+            self.usage.insert(tick, id.to_string());
+        }
+        self.entries.insert(
+            id.to_string(),
+            Entry {
+                value,
+                expires_at,
+                last_used,
+            },
+        );
+    }
+
+    fn remove_matching(&mut self, is_match: impl Fn(&str) -> bool) {
+        let matching: Vec<String> = self
+            .entries
+            .keys()
+            .filter(|key| is_match(key))
+            .cloned()
+            .collect();
+        for key in matching {
+            self.remove(&key);
+        }
+    }
+
+    fn remove(&mut self, id: &str) {
+        if let Some(Entry {
+            last_used: Some(last_used),
+            ..
+        }) = self.entries.remove(id)
+        {
+            self.usage.remove(&last_used);
+        }
+    }
+
+    // Same case insensitive matching as Redis tags
+    fn tag_members_matching(&self, tag: &str, pattern: &str) -> Vec<String> {
+        let pattern = pattern.to_lowercase();
+        match self.entries.get(tag) {
+            Some(Entry {
+                value: Value::Hash(members),
+                ..
+            }) => members
+                .keys()
+                .filter(|member| glob_match(&pattern, &member.to_lowercase()))
+                .cloned()
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    fn evict(&mut self, max_entries: usize) {
+        if self.usage.len() > max_entries {
+            let now = Instant::now();
+            let expired: Vec<String> = self
+                .entries
+                .iter()
+                .filter(|(_, entry)| entry.is_expired(now))
+                .map(|(id, _)| id.to_string())
+                .collect();
+            for id in expired {
+                self.remove(&id);
+            }
+        }
+        while self.usage.len() > max_entries {
+            let oldest = match self.usage.iter().next() {
+                Some((_, id)) => id.to_string(),
+                None => break,
+            };
+            self.remove(&oldest);
+        }
+    }
+}
+
+/// Process-local cache backend, for deployments without a Redis instance.
+///
+/// Entries honour their timeouts and the least recently used keys are evicted once
+/// `max_entries` is reached. Tags are never evicted, as their keys couldn't be invalidated
+/// anymore, and expire with the last of their keys. Hashes hold state like the runtime settings
+/// and aren't evicted either. Patterns follow the glob semantics of Redis
+/// `SCAN MATCH`.
+pub struct InMemoryCache {
+    store: Mutex<Store>,
+    max_entries: usize,
+    max_timeout: Option<usize>,
+}
+
+impl InMemoryCache {
+    pub fn new(max_entries: usize) -> Self {
+        InMemoryCache {
+            store: Mutex::new(Store::default()),
+            max_entries,
+            max_timeout: None,
+        }
+    }
+
+    /// Caps the timeout of every entry, including hashes which otherwise never expire
+    pub fn with_max_timeout(mut self, max_timeout: usize) -> Self {
+        self.max_timeout = Some(max_timeout);
+        self
+    }
+
+    fn store(&self) -> MutexGuard<Store> {
+        // A panic while holding the lock can't leave the store in an unusable state
+        self.store
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn expiry(&self, timeout: usize) -> Option<Instant> {
+        let timeout = self.max_timeout.map_or(timeout, |max| timeout.min(max));
+        Some(Instant::now() + Duration::from_secs(timeout as u64))
+    }
+
+    /// Removes the keys matching `pattern` in any case, the same keys a tag lookup would match
+    pub fn invalidate_pattern_ignoring_case(&self, pattern: &str) {
+        let pattern = pattern.to_lowercase();
+        self.store()
+            .remove_matching(|key| glob_match(&pattern, &key.to_lowercase()));
+    }
+}
+
+#[rocket::async_trait]
+impl Cache for InMemoryCache {
+    async fn fetch(&self, id: &str) -> Option<String> {
+        match self.store().live_entry(id, Instant::now()) {
+            Some(Entry {
+                value: Value::Plain(value),
+                ..
+            }) => Some(value.to_string()),
+            _ => None,
+        }
+    }
+
+    async fn create(&self, id: &str, dest: &str, timeout: usize) {
+        self.store().insert(
+            id,
+            Value::Plain(dest.to_string()),
+            self.expiry(timeout),
+            self.max_entries,
+        );
+    }
+
+    async fn insert_in_hash(&self, hash: &str, id: &str, dest: &str) -> bool {
+        let mut store = self.store();
+        if let Some(Entry {
+            value: Value::Hash(fields),
+            ..
+        }) = store.live_entry(hash, Instant::now())
+        {
+            fields.insert(id.to_string(), dest.to_string());
+            return true;
+        }
+        let mut fields = HashMap::new();
+        fields.insert(id.to_string(), dest.to_string());
+        let expires_at = self.max_timeout.and_then(|max| self.expiry(max));
+        store.insert_unevictable(hash, Value::Hash(fields), expires_at);
+        true
+    }
+
+    async fn get_from_hash(&self, hash: &str, id: &str) -> Option<String> {
+        match self.store().live_entry(hash, Instant::now()) {
+            Some(Entry {
+                value: Value::Hash(fields),
+                ..
+            }) => fields.get(id).cloned(),
+            _ => None,
+        }
+    }
+
+    async fn has_key(&self, id: &str) -> bool {
+        self.store().live_entry(id, Instant::now()).is_some()
+    }
+
+    async fn acquire_lock(&self, id: &str, token: &str, timeout_millis: usize) -> bool {
+        self.create_if_absent(id, token, timeout_millis)
+            .await
+            .unwrap_or(true)
+    }
+
+    async fn create_if_absent(&self, id: &str, dest: &str, timeout_millis: usize) -> Option<bool> {
+        let now = Instant::now();
+        let mut store = self.store();
+        if store.live_entry(id, now).is_some() {
+            return Some(false);
+        }
+        let expires_at = now + Duration::from_millis(timeout_millis as u64);
+        store.insert(
+            id,
+            Value::Plain(dest.to_string()),
+            Some(expires_at),
+            self.max_entries,
+        );
+        Some(true)
+    }
+
+    async fn release_lock(&self, id: &str, token: &str) {
+        let mut store = self.store();
+        let is_holder = match store.live_entry(id, Instant::now()) {
+            Some(Entry {
+                value: Value::Plain(value),
+                ..
+            }) => *value == token,
+            _ => false,
+        };
+        if is_holder {
+            store.remove(id);
+        }
+    }
+
+    async fn time_to_live(&self, id: &str) -> Option<usize> {
+        let now = Instant::now();
+        let expires_at = self.store().live_entry(id, now)?.expires_at?;
+        Some(expires_at.saturating_duration_since(now).as_secs() as usize)
+    }
+
+    async fn expire_entity(&self, id: &str, timeout: usize) {
+        let mut store = self.store();
+        // Same as Redis, a non positive timeout deletes the key
+        if timeout == 0 {
+            store.remove(id);
+        } else if let Some(entry) = store.live_entry(id, Instant::now()) {
+            entry.expires_at = self.expiry(timeout);
+        }
+    }
+
+    async fn invalidate_pattern(&self, pattern: &str) -> bool {
+        self.store().remove_matching(|key| glob_match(pattern, key));
+        true
+    }
+
+    async fn invalidate(&self, id: &str) {
+        self.store().remove(id);
+    }
+
+    async fn tag(&self, id: &str, tags: &[String], timeout: usize) {
+        let mut store = self.store();
+        let now = Instant::now();
+        let expires_at = self.expiry(timeout);
+        for tag in tags {
+            match store.live_entry(tag, now) {
+                Some(Entry {
+                    value: Value::Hash(members),
+                    expires_at: tag_expires_at,
+                    ..
+                }) => {
+                    members.insert(id.to_string(), String::new());
+                    // Tags have to outlive every one of their entries
+                    *tag_expires_at = (*tag_expires_at).max(expires_at);
+                }
+                _ => {
+                    let mut members = HashMap::new();
+                    members.insert(id.to_string(), String::new());
+                    store.insert_unevictable(tag, Value::Hash(members), expires_at);
+                }
+            }
+        }
+    }
+
+    async fn invalidate_tag(&self, tag: &str, pattern: &str) -> bool {
+        let mut store = self.store();
+        for member in store.tag_members_matching(tag, pattern) {
+            store.remove(&member);
+            if let Some(Entry {
+                value: Value::Hash(members),
+                ..
+            }) = store.entries.get_mut(tag)
+            {
+                members.remove(&member);
+            }
+        }
+        true
+    }
+
+    async fn tagged_keys(&self, tag: &str, pattern: &str) -> Vec<String> {
+        self.store().tag_members_matching(tag, pattern)
+    }
+
+    async fn keys(&self, pattern: &str) -> Vec<String> {
+        let now = Instant::now();
+        self.store()
+            .entries
+            .iter()
+            .filter(|(key, entry)| !entry.is_expired(now) && glob_match(pattern, key))
+            .map(|(key, _)| key.to_string())
+            .collect()
+    }
+
+    async fn times_to_live(&self, ids: &[String]) -> Vec<Option<usize>> {
+        let store = self.store();
+        let now = Instant::now();
+        ids.iter()
+            .map(|id| {
+                let entry = store
+                    .entries
+                    .get(id)
+                    .filter(|entry| !entry.is_expired(now))?;
+                let expires_at = entry.expires_at?;
+                Some(expires_at.saturating_duration_since(now).as_secs() as usize)
+            })
+            .collect()
+    }
+
+    // Approximated by the length of the key and its contents
+    async fn memory_usage(&self, ids: &[String]) -> Vec<Option<usize>> {
+        let store = self.store();
+        ids.iter()
+            .map(|id| {
+                let entry = store.entries.get(id)?;
+                let size = match &entry.value {
+                    Value::Plain(value) => value.len(),
+                    Value::Hash(fields) => fields
+                        .iter()
+                        .map(|(field, value)| field.len() + value.len())
+                        .sum(),
+                };
+                Some(id.len() + size)
+            })
+            .collect()
+    }
+
+    async fn info(&self) -> Option<String> {
+        let store = self.store();
+        Some(format!(
+            "# Memory\r\nbackend:in_memory\r\nkeys:{}\r\nmax_keys:{}\r\n",
+            store.entries.len(),
+            self.max_entries
+        ))
+    }
+
+    async fn is_available(&self) -> bool {
+        true
+    }
+}
+
+/// Byte-wise glob matching with the same semantics as Redis `stringmatchlen`
+/// (`*`, `?`, `[...]`, `[^...]`, ranges and `\` escapes)
+pub(super) fn glob_match(pattern: &str, key: &str) -> bool {
+    matches(pattern.as_bytes(), key.as_bytes())
+}
+
+// Iterative, a `*` is only ever retried from the position of the last one, so that patterns with
+// many of them don't backtrack exponentially
+fn matches(pattern: &[u8], string: &[u8]) -> bool {
+    let mut p = 0;
+    let mut s = 0;
+    // Pattern position after the last `*`, and the string position it has matched up to
+    let mut last_star: Option<(usize, usize)> = None;
+    while s < string.len() {
+        if p < pattern.len() {
+            if pattern[p] == b'*' {
+                p += 1;
+                last_star = Some((p, s));
+                continue;
+            }
+            if let Some(consumed) = match_single(pattern, p, string[s]) {
+                p += consumed;
+                s += 1;
+                continue;
+            }
+        }
+        match last_star {
+            Some((star_p, star_s)) => {
+                p = star_p;
+                s = star_s + 1;
+                last_star = Some((star_p, s));
+            }
+            None => return false,
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+// Pattern bytes consumed if the token at `p` matches `c`
+fn match_single(pattern: &[u8], p: usize, c: u8) -> Option<usize> {
+    match pattern[p] {
+        b'?' => Some(1),
+        b'[' => {
+            let (matched, closing) = match_class(&pattern[p + 1..], c);
+            let consumed = (2 + closing).min(pattern.len() - p);
+            if matched {
+                Some(consumed)
+            } else {
+                None
+            }
+        }
+        b'\\' if p + 1 < pattern.len() => {
+            if pattern[p + 1] == c {
+                Some(2)
+            } else {
+                None
+            }
+        }
+        literal => {
+            if literal == c {
+                Some(1)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+// Returns whether `c` is part of the class and the index of its closing `]`.
+// Unterminated classes extend to the end of the pattern, as in Redis.
+fn match_class(class: &[u8], c: u8) -> (bool, usize) {
+    let negate = class.first() == Some(&b'^');
+    let mut i = if negate { 1 } else { 0 };
+    let mut matched = false;
+    while i < class.len() && class[i] != b']' {
+        if class[i] == b'\\' && i + 1 < class.len() {
+            i += 1;
+            matched |= class[i] == c;
+        } else if i + 2 < class.len() && class[i + 1] == b'-' {
+            let (start, end) = if class[i] <= class[i + 2] {
+                (class[i], class[i + 2])
+            } else {
+                (class[i + 2], class[i])
+            };
+            matched |= start <= c && c <= end;
+            i += 2;
+        } else {
+            matched |= class[i] == c;
+        }
+        i += 1;
+    }
+    (matched != negate, i)
+}
@@ -1,26 +1,202 @@
+pub mod admin;
 mod cache_op_executors;
 pub mod cache_operations;
+mod circuit_breaker;
+pub mod compression;
 mod inner_cache;
+pub mod memory;
 pub mod redis;
+pub mod single_flight;
+mod tags;
+pub mod tiered;
 
 #[cfg(test)]
 mod tests;
 
+use crate::cache::memory::InMemoryCache;
+use crate::cache::redis::{create_pool, RedisCache};
+use crate::cache::tiered::TieredCache;
+use crate::config::{
+    cache_backend, cache_key_namespace, cache_schema_version, chain_id, l1_cache_duration,
+    l1_cache_enabled, memory_cache_max_entries,
+};
+use lazy_static::lazy_static;
 use mockall::automock;
+use rocket::request::{self, FromRequest, Request};
+use rocket::State;
+use std::sync::Arc;
 
 const CACHE_REQS_PREFIX: &'static str = "c_reqs";
 const CACHE_RESP_PREFIX: &'static str = "c_resp";
 const CACHE_REQS_RESP_PREFIX: &'static str = "c_re";
+const CACHE_LOCK_PREFIX: &'static str = "c_lock";
+const CACHE_TAG_PREFIX: &'static str = "c_tag";
+
+lazy_static! {
+    // Computed once, so that keys don't change while the service is running
+    static ref CACHE_NAMESPACE: String = format!(
+        "{}:{}:v{}",
+        cache_key_namespace(),
+        chain_id(),
+        cache_schema_version()
+    );
+}
+
+/// Gateways for different chains, or with different response formats, can share a Redis
+/// instance as long as every key goes through this function. The chains of the registry that
+/// share a gateway are told apart by their urls and chain ids within the same namespace.
+pub fn namespaced(key: &str) -> String {
+    format!("{}:{}", *CACHE_NAMESPACE, key)
+}
+
+/// Key of the response that [CacheResponse](cache_operations::CacheResponse) stores for `uri`
+pub fn response_key(uri: &str) -> String {
+    namespaced(&format!("{}_{}", CACHE_RESP_PREFIX, uri))
+}
 
 #[automock]
+#[rocket::async_trait]
 pub trait Cache: Send + Sync {
-    fn fetch(&self, id: &str) -> Option<String>;
-    fn create(&self, id: &str, dest: &str, timeout: usize);
-    fn insert_in_hash(&self, hash: &str, id: &str, dest: &str);
-    fn get_from_hash(&self, hash: &str, id: &str) -> Option<String>;
-    fn has_key(&self, id: &str) -> bool;
-    fn expire_entity(&self, id: &str, timeout: usize);
-    fn invalidate_pattern(&self, pattern: &str);
-    fn invalidate(&self, id: &str);
-    fn info(&self) -> Option<String>;
+    async fn fetch(&self, id: &str) -> Option<String>;
+    async fn create(&self, id: &str, dest: &str, timeout: usize);
+    /// `false` if the value could not be stored
+    async fn insert_in_hash(&self, hash: &str, id: &str, dest: &str) -> bool;
+    async fn get_from_hash(&self, hash: &str, id: &str) -> Option<String>;
+    async fn has_key(&self, id: &str) -> bool;
+    /// `token` identifies the holder, only it can release the lock before it expires
+    async fn acquire_lock(&self, id: &str, token: &str, timeout_millis: usize) -> bool;
+    async fn release_lock(&self, id: &str, token: &str);
+    /// `Some(false)` if the key already exists, `None` if the backend couldn't tell
+    async fn create_if_absent(&self, id: &str, dest: &str, timeout_millis: usize) -> Option<bool>;
+    async fn time_to_live(&self, id: &str) -> Option<usize>;
+    async fn expire_entity(&self, id: &str, timeout: usize);
+    /// `false` if the matching keys could not be deleted
+    async fn invalidate_pattern(&self, pattern: &str) -> bool;
+    async fn invalidate(&self, id: &str);
+    async fn tag(&self, id: &str, tags: &[String], timeout: usize);
+    /// `false` if the matching keys could not be deleted
+    async fn invalidate_tag(&self, tag: &str, pattern: &str) -> bool;
+    /// Keys of `tag` matching `pattern`, the ones [Cache::invalidate_tag] deletes
+    async fn tagged_keys(&self, tag: &str, pattern: &str) -> Vec<String>;
+    async fn keys(&self, pattern: &str) -> Vec<String>;
+    async fn times_to_live(&self, ids: &[String]) -> Vec<Option<usize>>;
+    async fn memory_usage(&self, ids: &[String]) -> Vec<Option<usize>>;
+    async fn info(&self) -> Option<String>;
+    async fn is_available(&self) -> bool;
+}
+
+pub fn create_cache() -> Arc<dyn Cache> {
+    match cache_backend().as_str() {
+        "redis" if l1_cache_enabled() => Arc::new(TieredCache::new(
+            RedisCache::new(create_pool()),
+            memory_cache_max_entries(),
+            l1_cache_duration(),
+        )),
+        "redis" => Arc::new(RedisCache::new(create_pool())),
+        "memory" => Arc::new(InMemoryCache::new(memory_cache_max_entries())),
+        backend => panic!("Unsupported cache backend: {}", backend),
+    }
+}
+
+/// Request guard for the cache backend selected with `CACHE_BACKEND`
+#[derive(Clone)]
+pub struct ServiceCache(Arc<dyn Cache>);
+
+impl ServiceCache {
+    pub fn new(cache: Arc<dyn Cache>) -> Self {
+        ServiceCache(cache)
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ServiceCache {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        let cache = try_outcome!(request.guard::<State<Arc<dyn Cache>>>().await);
+        request::Outcome::Success(ServiceCache(cache.inner().clone()))
+    }
+}
+
+#[rocket::async_trait]
+impl Cache for ServiceCache {
+    async fn fetch(&self, id: &str) -> Option<String> {
+        self.0.fetch(id).await
+    }
+
+    async fn create(&self, id: &str, dest: &str, timeout: usize) {
+        self.0.create(id, dest, timeout).await
+    }
+
+    async fn insert_in_hash(&self, hash: &str, id: &str, dest: &str) -> bool {
+        self.0.insert_in_hash(hash, id, dest).await
+    }
+
+    async fn get_from_hash(&self, hash: &str, id: &str) -> Option<String> {
+        self.0.get_from_hash(hash, id).await
+    }
+
+    async fn has_key(&self, id: &str) -> bool {
+        self.0.has_key(id).await
+    }
+
+    async fn acquire_lock(&self, id: &str, token: &str, timeout_millis: usize) -> bool {
+        self.0.acquire_lock(id, token, timeout_millis).await
+    }
+
+    async fn release_lock(&self, id: &str, token: &str) {
+        self.0.release_lock(id, token).await
+    }
+
+    async fn create_if_absent(&self, id: &str, dest: &str, timeout_millis: usize) -> Option<bool> {
+        self.0.create_if_absent(id, dest, timeout_millis).await
+    }
+
+    async fn time_to_live(&self, id: &str) -> Option<usize> {
+        self.0.time_to_live(id).await
+    }
+
+    async fn expire_entity(&self, id: &str, timeout: usize) {
+        self.0.expire_entity(id, timeout).await
+    }
+
+    async fn invalidate_pattern(&self, pattern: &str) -> bool {
+        self.0.invalidate_pattern(pattern).await
+    }
+
+    async fn invalidate(&self, id: &str) {
+        self.0.invalidate(id).await
+    }
+
+    async fn tag(&self, id: &str, tags: &[String], timeout: usize) {
+        self.0.tag(id, tags, timeout).await
+    }
+
+    async fn invalidate_tag(&self, tag: &str, pattern: &str) -> bool {
+        self.0.invalidate_tag(tag, pattern).await
+    }
+
+    async fn tagged_keys(&self, tag: &str, pattern: &str) -> Vec<String> {
+        self.0.tagged_keys(tag, pattern).await
+    }
+
+    async fn keys(&self, pattern: &str) -> Vec<String> {
+        self.0.keys(pattern).await
+    }
+
+    async fn times_to_live(&self, ids: &[String]) -> Vec<Option<usize>> {
+        self.0.times_to_live(ids).await
+    }
+
+    async fn memory_usage(&self, ids: &[String]) -> Vec<Option<usize>> {
+        self.0.memory_usage(ids).await
+    }
+
+    async fn info(&self) -> Option<String> {
+        self.0.info().await
+    }
+
+    async fn is_available(&self) -> bool {
+        self.0.is_available().await
+    }
 }
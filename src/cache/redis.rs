@@ -1,103 +1,390 @@
+use crate::cache::circuit_breaker::CircuitBreaker;
+use crate::cache::memory::glob_match;
 use crate::cache::Cache;
-use crate::config::{redis_scan_count, redis_url};
-use r2d2::{Pool, PooledConnection};
-use redis::{self, pipe, Commands, FromRedisValue, Iter, ToRedisArgs};
-use rocket::request::{self, FromRequest, Request};
-use rocket::State;
+use crate::config::{
+    redis_circuit_breaker_cooldown, redis_connection_timeout, redis_pool_size, redis_scan_count,
+    redis_url,
+};
+use crate::monitoring::cache::{report_cache_bypassed_operation, report_redis_pool};
+use bb8::{Pool, PooledConnection};
+use bb8_redis::RedisConnectionManager;
+use lazy_static::lazy_static;
+use redis::{self, pipe, AsyncCommands, RedisResult, Script, ToRedisArgs};
+use std::time::Duration;
 
-type RedisPool = Pool<redis::Client>;
-type RedisConnection = PooledConnection<redis::Client>;
+type RedisPool = Pool<RedisConnectionManager>;
+type RedisConnection<'a> = PooledConnection<'a, RedisConnectionManager>;
 
-pub struct ServiceCache<'r>(State<'r, RedisPool>);
+lazy_static! {
+    // Adds the key in ARGV[1] to every tag, tags only ever get their timeout extended
+    static ref TAG_SCRIPT: Script = Script::new(
+        r"
+        for _, tag in ipairs(KEYS) do
+            redis.call('SADD', tag, ARGV[1])
+            if redis.call('TTL', tag) < tonumber(ARGV[2]) then
+                redis.call('EXPIRE', tag, ARGV[2])
+            end
+        end
+        "
+    );
+
+    // Deletes the lock in KEYS[1] only if it is still held with the token in ARGV[1], it may
+    // have expired and been acquired by someone else in the meantime
+    static ref RELEASE_LOCK_SCRIPT: Script = Script::new(
+        r"
+        if redis.call('GET', KEYS[1]) == ARGV[1] then
+            return redis.call('DEL', KEYS[1])
+        end
+        return 0
+        "
+    );
+}
+
+pub struct RedisCache {
+    pool: RedisPool,
+    circuit_breaker: CircuitBreaker,
+}
 
 pub fn create_pool() -> RedisPool {
-    let client = redis::Client::open(redis_url()).unwrap();
-    Pool::builder().max_size(15).build(client).unwrap()
+    let manager = RedisConnectionManager::new(redis_url()).unwrap();
+    Pool::builder()
+        .max_size(redis_pool_size())
+        .connection_timeout(Duration::from_millis(redis_connection_timeout()))
+        .build_unchecked(manager)
 }
 
-#[rocket::async_trait]
-impl<'r> FromRequest<'r> for ServiceCache<'r> {
-    type Error = ();
+impl RedisCache {
+    pub fn new(pool: RedisPool) -> Self {
+        RedisCache {
+            pool,
+            // Getting a connection takes at most the connection timeout, a probe that takes
+            // longer was dropped
+            circuit_breaker: CircuitBreaker::new(
+                Duration::from_secs(redis_circuit_breaker_cooldown()),
+                Duration::from_millis(redis_connection_timeout()),
+            ),
+        }
+    }
+
+    // While the circuit is open no connection is handed out, so callers bypass the cache
+    async fn conn(&self) -> Option<RedisConnection<'_>> {
+        if !self.circuit_breaker.allows_request() {
+            report_cache_bypassed_operation();
+            return None;
+        }
+        let connection = self.pool.get().await;
+        let state = self.pool.state();
+        report_redis_pool(state.connections, state.idle_connections, redis_pool_size());
+        match connection {
+            Ok(connection) => {
+                self.circuit_breaker.record_success();
+                Some(connection)
+            }
+            Err(error) => {
+                log::error!("Could not get a redis connection: {:?}", error);
+                self.circuit_breaker.record_failure();
+                None
+            }
+        }
+    }
 
-    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
-        let pool = try_outcome!(request.guard::<State<RedisPool>>().await);
-        return request::Outcome::Success(ServiceCache(pool));
+    // Redis failures are logged and degrade to a cache miss instead of failing the request
+    fn check<T>(&self, result: RedisResult<T>) -> Option<T> {
+        result
+            .map_err(|error| {
+                log::error!("Redis error: {:?}", error);
+                if error.is_io_error() || error.is_timeout() {
+                    self.circuit_breaker.record_failure();
+                }
+            })
+            .ok()
     }
-}
 
-impl ServiceCache<'_> {
-    fn conn(&self) -> RedisConnection {
-        self.0.inner().get().unwrap()
+    pub async fn publish(&self, channel: &str, message: &str) {
+        if let Some(mut con) = self.conn().await {
+            let result: RedisResult<usize> = con.publish(channel, message).await;
+            self.check(result);
+        }
     }
 }
 
-impl Cache for ServiceCache<'_> {
-    fn fetch(&self, id: &str) -> Option<String> {
-        match self.conn().get(id) {
-            Ok(value) => Some(value),
-            _ => None,
+#[rocket::async_trait]
+impl Cache for RedisCache {
+    async fn fetch(&self, id: &str) -> Option<String> {
+        let mut con = self.conn().await?;
+        self.check(con.get(id).await)?
+    }
+
+    async fn create(&self, id: &str, dest: &str, timeout: usize) {
+        if let Some(mut con) = self.conn().await {
+            let result: RedisResult<()> = con.set_ex(id, dest, timeout).await;
+            self.check(result);
+        }
+    }
+
+    async fn insert_in_hash(&self, hash: &str, id: &str, dest: &str) -> bool {
+        match self.conn().await {
+            Some(mut con) => {
+                let result: RedisResult<()> = con.hset(hash, id, dest).await;
+                self.check(result).is_some()
+            }
+            None => false,
         }
     }
 
-    fn create(&self, id: &str, dest: &str, timeout: usize) {
-        let _: () = self.conn().set_ex(id, dest, timeout).unwrap();
+    async fn get_from_hash(&self, hash: &str, id: &str) -> Option<String> {
+        let mut con = self.conn().await?;
+        self.check(con.hget(hash, id).await)?
+    }
+
+    async fn has_key(&self, id: &str) -> bool {
+        match self.conn().await {
+            Some(mut con) => {
+                let result: Option<usize> = self.check(con.exists(id).await);
+                result.map(|it| it != 0).unwrap_or(false)
+            }
+            None => false,
+        }
+    }
+
+    async fn acquire_lock(&self, id: &str, token: &str, timeout_millis: usize) -> bool {
+        // Without Redis there is nothing to coordinate with, so the caller can go ahead
+        let mut con = match self.conn().await {
+            Some(con) => con,
+            None => return true,
+        };
+        let result: RedisResult<Option<String>> = redis::cmd("SET")
+            .arg(id)
+            .arg(token)
+            .arg("NX")
+            .arg("PX")
+            .arg(timeout_millis)
+            .query_async(&mut *con)
+            .await;
+        self.check(result).map_or(true, |reply| reply.is_some())
+    }
+
+    async fn create_if_absent(&self, id: &str, dest: &str, timeout_millis: usize) -> Option<bool> {
+        let mut con = self.conn().await?;
+        let result: RedisResult<Option<String>> = redis::cmd("SET")
+            .arg(id)
+            .arg(dest)
+            .arg("NX")
+            .arg("PX")
+            .arg(timeout_millis)
+            .query_async(&mut *con)
+            .await;
+        self.check(result).map(|reply| reply.is_some())
+    }
+
+    async fn release_lock(&self, id: &str, token: &str) {
+        if let Some(mut con) = self.conn().await {
+            let result: RedisResult<usize> = RELEASE_LOCK_SCRIPT
+                .key(id)
+                .arg(token)
+                .invoke_async(&mut *con)
+                .await;
+            self.check(result);
+        }
     }
 
-    fn insert_in_hash(&self, hash: &str, id: &str, dest: &str) {
-        let _: () = self.conn().hset(hash, id, dest).unwrap();
+    async fn time_to_live(&self, id: &str) -> Option<usize> {
+        let mut con = self.conn().await?;
+        // Negative values are returned for missing keys and keys without a timeout
+        let ttl: isize = self.check(con.ttl(id).await)?;
+        if ttl < 0 {
+            None
+        } else {
+            Some(ttl as usize)
+        }
     }
 
-    fn get_from_hash(&self, hash: &str, id: &str) -> Option<String> {
-        self.conn().hget(hash, id).ok()
+    async fn expire_entity(&self, id: &str, timeout: usize) {
+        if let Some(mut con) = self.conn().await {
+            let result: RedisResult<()> = con.expire(id, timeout).await;
+            self.check(result);
+        }
     }
 
-    fn has_key(&self, id: &str) -> bool {
-        let result: Option<usize> = self.conn().exists(id).ok();
-        result.map(|it| it != 0).unwrap_or(false)
+    async fn invalidate_pattern(&self, pattern: &str) -> bool {
+        let mut con = match self.conn().await {
+            Some(con) => con,
+            None => return false,
+        };
+        match self.check(scan_match_count(&mut con, pattern, redis_scan_count()).await) {
+            Some(keys) => self.check(pipeline_delete(&mut con, keys).await).is_some(),
+            None => false,
+        }
     }
 
-    fn expire_entity(&self, id: &str, timeout: usize) {
-        let _: () = self.conn().expire(id, timeout).unwrap();
+    async fn invalidate(&self, id: &str) {
+        if let Some(mut con) = self.conn().await {
+            let result: RedisResult<()> = con.del(id).await;
+            self.check(result);
+        }
     }
 
-    fn invalidate_pattern(&self, pattern: &str) {
-        pipeline_delete(
-            &mut self.conn(),
-            scan_match_count(&mut self.conn(), pattern, redis_scan_count()),
-        );
+    async fn tag(&self, id: &str, tags: &[String], timeout: usize) {
+        if let Some(mut con) = self.conn().await {
+            let mut invocation = TAG_SCRIPT.prepare_invoke();
+            for tag in tags {
+                invocation.key(tag);
+            }
+            let result: RedisResult<()> = invocation
+                .arg(id)
+                .arg(timeout)
+                .invoke_async(&mut *con)
+                .await;
+            self.check(result);
+        }
     }
 
-    fn invalidate(&self, id: &str) {
-        let _: () = self.conn().del(id).unwrap();
+    async fn invalidate_tag(&self, tag: &str, pattern: &str) -> bool {
+        let mut con = match self.conn().await {
+            Some(con) => con,
+            None => return false,
+        };
+        let keys = match self.check(tag_members_matching(&mut con, tag, pattern).await) {
+            Some(keys) => keys,
+            None => return false,
+        };
+        self.check(pipeline_untag(&mut con, tag, keys).await)
+            .is_some()
     }
 
-    fn info(&self) -> Option<String> {
-        info(&mut self.conn())
+    async fn tagged_keys(&self, tag: &str, pattern: &str) -> Vec<String> {
+        match self.conn().await {
+            Some(mut con) => self
+                .check(tag_members_matching(&mut con, tag, pattern).await)
+                .unwrap_or_default(),
+            None => vec![],
+        }
+    }
+
+    async fn keys(&self, pattern: &str) -> Vec<String> {
+        match self.conn().await {
+            Some(mut con) => self
+                .check(scan_match_count(&mut con, pattern, redis_scan_count()).await)
+                .unwrap_or_default(),
+            None => vec![],
+        }
+    }
+
+    async fn times_to_live(&self, ids: &[String]) -> Vec<Option<usize>> {
+        let ttls = match self.conn().await {
+            Some(mut con) => self.check(pipeline_ttl(&mut con, ids).await),
+            None => None,
+        };
+        ttls.unwrap_or_else(|| vec![None; ids.len()])
+    }
+
+    async fn memory_usage(&self, ids: &[String]) -> Vec<Option<usize>> {
+        let usage = match self.conn().await {
+            Some(mut con) => self.check(pipeline_memory_usage(&mut con, ids).await),
+            None => None,
+        };
+        usage.unwrap_or_else(|| vec![None; ids.len()])
+    }
+
+    async fn info(&self) -> Option<String> {
+        let mut con = self.conn().await?;
+        self.check(redis::cmd("INFO").query_async(&mut *con).await)
+    }
+
+    async fn is_available(&self) -> bool {
+        !self.circuit_breaker.is_open()
     }
 }
 
-fn pipeline_delete(con: &mut redis::Connection, keys: Iter<String>) {
+async fn pipeline_delete(con: &mut redis::aio::Connection, keys: Vec<String>) -> RedisResult<()> {
+    if keys.is_empty() {
+        return Ok(());
+    }
+    let pipeline = &mut pipe();
+    for key in keys {
+        pipeline.del(key);
+    }
+    pipeline.query_async(con).await
+}
+
+async fn pipeline_untag(
+    con: &mut redis::aio::Connection,
+    tag: &str,
+    keys: Vec<String>,
+) -> RedisResult<()> {
+    if keys.is_empty() {
+        return Ok(());
+    }
     let pipeline = &mut pipe();
+    pipeline.srem(tag, &keys);
     for key in keys {
         pipeline.del(key);
     }
-    pipeline.execute(con);
+    pipeline.query_async(con).await
 }
 
-fn scan_match_count<'r, P: ToRedisArgs, C: ToRedisArgs, RV: FromRedisValue>(
-    con: &'r mut redis::Connection,
+// Patterns are matched case insensitively, as addresses show up checksummed and lowercased
+async fn tag_members_matching(
+    con: &mut redis::aio::Connection,
+    tag: &str,
+    pattern: &str,
+) -> RedisResult<Vec<String>> {
+    let members: Vec<String> = con.smembers(tag).await?;
+    let pattern = pattern.to_lowercase();
+    Ok(members
+        .into_iter()
+        .filter(|member| glob_match(&pattern, &member.to_lowercase()))
+        .collect())
+}
+
+async fn pipeline_ttl(
+    con: &mut redis::aio::Connection,
+    keys: &[String],
+) -> RedisResult<Vec<Option<usize>>> {
+    if keys.is_empty() {
+        return Ok(vec![]);
+    }
+    let pipeline = &mut pipe();
+    for key in keys {
+        pipeline.ttl(key);
+    }
+    let ttls: Vec<isize> = pipeline.query_async(con).await?;
+    // Negative values are returned for missing keys and keys without a timeout
+    Ok(ttls
+        .into_iter()
+        .map(|ttl| if ttl < 0 { None } else { Some(ttl as usize) })
+        .collect())
+}
+
+async fn pipeline_memory_usage(
+    con: &mut redis::aio::Connection,
+    keys: &[String],
+) -> RedisResult<Vec<Option<usize>>> {
+    if keys.is_empty() {
+        return Ok(vec![]);
+    }
+    let pipeline = &mut pipe();
+    for key in keys {
+        pipeline.cmd("MEMORY").arg("USAGE").arg(key);
+    }
+    pipeline.query_async(con).await
+}
+
+async fn scan_match_count<P: ToRedisArgs, C: ToRedisArgs>(
+    con: &mut redis::aio::Connection,
     pattern: P,
     count: C,
-) -> redis::Iter<'r, RV> {
+) -> RedisResult<Vec<String>> {
     let mut cmd = redis::cmd("SCAN");
     cmd.cursor_arg(0)
         .arg("MATCH")
         .arg(pattern)
         .arg("COUNT")
         .arg(count);
-    cmd.iter(con).unwrap()
-}
-
-fn info(con: &mut redis::Connection) -> Option<String> {
-    redis::cmd("INFO").query(con).ok()
+    let mut iter = cmd.iter_async::<String>(con).await?;
+    let mut keys = vec![];
+    while let Some(key) = iter.next_item().await {
+        keys.push(key);
+    }
+    Ok(keys)
 }
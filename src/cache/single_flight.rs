@@ -0,0 +1,143 @@
+use crate::cache::{Cache, CACHE_LOCK_PREFIX};
+use crate::config::{single_flight_lock_timeout, single_flight_poll_interval};
+use crate::utils::errors::{ApiError, ApiResult};
+use lazy_static::lazy_static;
+use rocket::tokio::sync::watch;
+use rocket::tokio::time::sleep;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+// `None` until the generator is done, waiters look successful results up in the cache
+type Outcome = Option<Result<(), ApiError>>;
+
+lazy_static! {
+    static ref IN_FLIGHT: Mutex<HashMap<String, watch::Receiver<Outcome>>> =
+        Mutex::new(HashMap::new());
+}
+
+enum Flight {
+    Leading(watch::Sender<Outcome>, InFlight),
+    Following(watch::Receiver<Outcome>),
+}
+
+/// Runs `generator` at most once per `key` across concurrent requests.
+///
+/// Requests in this instance wait for the one that runs the generator, other instances are kept
+/// out by a lock in the cache that expires after `SINGLE_FLIGHT_LOCK_TIMEOUT` milliseconds.
+/// Waiters get the result through `lookup`, so the generator is expected to store it in the
+/// cache. Errors aren't necessarily cached, so waiters in this instance get the error of the
+/// request they waited for, requests arriving afterwards run the generator again. If the request
+/// running the generator is cancelled its waiters start over.
+pub async fn coalesce<T, L, LFut, G, GFut>(
+    cache: &impl Cache,
+    key: &str,
+    lookup: L,
+    generator: G,
+) -> ApiResult<T>
+where
+    L: Fn() -> LFut,
+    LFut: Future<Output = Option<ApiResult<T>>>,
+    G: FnOnce() -> GFut,
+    GFut: Future<Output = ApiResult<T>>,
+{
+    // Results can't be shared without a cache, waiting would only serialize the requests
+    if !cache.is_available().await {
+        return generator().await;
+    }
+    loop {
+        match join_flight(key) {
+            Flight::Leading(sender, in_flight) => {
+                let result = match lookup().await {
+                    Some(result) => result,
+                    None => run_exclusively(cache, key, &lookup, generator).await,
+                };
+                // Requests arriving from now on don't get this result
+                drop(in_flight);
+                let _ = sender.send(Some(result.as_ref().map(|_| ()).map_err(Clone::clone)));
+                return result;
+            }
+            Flight::Following(receiver) => match outcome(receiver).await {
+                Some(Err(error)) => return Err(error),
+                Some(Ok(())) => {
+                    if let Some(result) = lookup().await {
+                        return result;
+                    }
+                }
+                None => {}
+            },
+        }
+    }
+}
+
+async fn run_exclusively<T, L, LFut, G, GFut>(
+    cache: &impl Cache,
+    key: &str,
+    lookup: &L,
+    generator: G,
+) -> ApiResult<T>
+where
+    L: Fn() -> LFut,
+    LFut: Future<Output = Option<ApiResult<T>>>,
+    G: FnOnce() -> GFut,
+    GFut: Future<Output = ApiResult<T>>,
+{
+    let lock_key = format!("{}_{}", CACHE_LOCK_PREFIX, key);
+    let token = Uuid::new_v4().to_string();
+    let lock_timeout = single_flight_lock_timeout();
+    let deadline = Instant::now() + Duration::from_millis(lock_timeout as u64);
+    // Once the lock expired its holder is assumed to be gone, and we generate the value ourselves
+    let mut acquired = false;
+    while Instant::now() < deadline {
+        if cache.acquire_lock(&lock_key, &token, lock_timeout).await {
+            acquired = true;
+            break;
+        }
+        sleep(Duration::from_millis(single_flight_poll_interval())).await;
+        if let Some(result) = lookup().await {
+            return result;
+        }
+    }
+
+    let result = generator().await;
+    if acquired {
+        cache.release_lock(&lock_key, &token).await;
+    }
+    result
+}
+
+fn join_flight(key: &str) -> Flight {
+    let mut flights = IN_FLIGHT.lock().unwrap();
+    match flights.get(key) {
+        Some(receiver) => Flight::Following(receiver.clone()),
+        None => {
+            let (sender, receiver) = watch::channel(None);
+            flights.insert(key.to_string(), receiver);
+            Flight::Leading(sender, InFlight(key.to_string()))
+        }
+    }
+}
+
+// `None` if the request running the generator was cancelled
+async fn outcome(mut receiver: watch::Receiver<Outcome>) -> Outcome {
+    loop {
+        let outcome = receiver.borrow().clone();
+        if outcome.is_some() {
+            return outcome;
+        }
+        if receiver.changed().await.is_err() {
+            return receiver.borrow().clone();
+        }
+    }
+}
+
+// Removes the flight of the key, also if its generator panicked or the request was cancelled
+struct InFlight(String);
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        IN_FLIGHT.lock().unwrap().remove(&self.0);
+    }
+}
@@ -0,0 +1,63 @@
+use crate::cache::{namespaced, CACHE_REQS_PREFIX, CACHE_RESP_PREFIX, CACHE_TAG_PREFIX};
+use crate::config::chains::{chains, default_chain};
+use crate::utils::chains::split_chain_scope;
+use lazy_static::lazy_static;
+use regex::Regex;
+
+lazy_static! {
+    // Safe, token and contract addresses, as well as safe_tx_hashes and tx_hashes
+    static ref IDENTIFIER: Regex = Regex::new(r"0x(?:[0-9a-fA-F]{64}|[0-9a-fA-F]{40})").unwrap();
+}
+
+/// Tags of a cache key, one for every address or hash that is part of it. The same address can
+/// be a Safe on several chains, so tags are scoped to the chain the key was cached for.
+pub(super) fn extract_tags(key: &str) -> Vec<String> {
+    let chain_id = chain_of_key(key);
+    let mut tags: Vec<String> = IDENTIFIER
+        .find_iter(key)
+        .map(|identifier| tag_key(chain_id, identifier.as_str()))
+        .collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+/// Only values that are an address or hash as a whole are tracked in a tag
+pub(super) fn tag_for(chain_id: u64, value: &str) -> Option<String> {
+    IDENTIFIER
+        .find(value)
+        .filter(|identifier| identifier.start() == 0 && identifier.end() == value.len())
+        .map(|identifier| tag_key(chain_id, identifier.as_str()))
+}
+
+// Responses of the default chain are cached under their unscoped uri and requests under the url
+// of the transaction service of their chain. Keys of other upstreams belong to the default chain.
+fn chain_of_key(key: &str) -> u64 {
+    let namespace = namespaced("");
+    let key = key.strip_prefix(namespace.as_str()).unwrap_or(key);
+    let default_chain_id = default_chain().chain_id;
+    if let Some(uri) = strip_family(key, CACHE_RESP_PREFIX) {
+        split_chain_scope(uri).map_or(default_chain_id, |(chain_id, _)| chain_id)
+    } else if let Some(url) = strip_family(key, CACHE_REQS_PREFIX) {
+        chains()
+            .iter()
+            .find(|chain| url.starts_with(&chain.base_transaction_service_url()))
+            .map_or(default_chain_id, |chain| chain.chain_id)
+    } else {
+        default_chain_id
+    }
+}
+
+fn strip_family<'a>(key: &'a str, family: &str) -> Option<&'a str> {
+    key.strip_prefix(family)?.strip_prefix('_')
+}
+
+// Addresses show up both checksummed and lowercased in urls
+fn tag_key(chain_id: u64, identifier: &str) -> String {
+    namespaced(&format!(
+        "{}_{}_{}",
+        CACHE_TAG_PREFIX,
+        chain_id,
+        identifier.to_lowercase()
+    ))
+}
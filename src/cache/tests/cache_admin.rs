@@ -0,0 +1,169 @@
+use crate::cache::admin::{family_stats, flush, get_entry, list_keys};
+use crate::cache::cache_operations::{InvalidationPattern, InvalidationScope};
+use crate::cache::compression::CompressionSettings;
+use crate::cache::inner_cache::CacheEntry;
+use crate::cache::memory::InMemoryCache;
+use crate::cache::{namespaced, Cache};
+use crate::config::chains::default_chain;
+
+fn chain_id() -> u64 {
+    default_chain().chain_id
+}
+
+async fn setup_cache() -> InMemoryCache {
+    let cache = InMemoryCache::new(10);
+    cache
+        .create(&namespaced("c_resp_/v1/safes/0x1"), "\"safe\"", 60)
+        .await;
+    cache
+        .create(&namespaced("c_resp_/v1/safes/0x2"), "\"other\"", 60)
+        .await;
+    cache
+        .create(
+            &namespaced("c_reqs_https://example.com/api/v1/safes/0x1"),
+            &CacheEntry::new(200, "{}", "https://example.com/api/v1/safes/0x1")
+                .to_cache_string(CompressionSettings::configured()),
+            60,
+        )
+        .await;
+    cache
+}
+
+#[rocket::async_test]
+async fn list_keys_sorted_and_limited() {
+    let cache = setup_cache().await;
+    let pattern = InvalidationPattern::Any(InvalidationScope::Responses, String::from("/v1/safes"));
+
+    let actual = list_keys(&cache, &pattern, chain_id(), 1).await;
+
+    assert_eq!(actual.len(), 1);
+    assert_eq!(actual[0].key, namespaced("c_resp_/v1/safes/0x1"));
+    assert!(actual[0].ttl.unwrap() <= 60);
+    assert!(actual[0].size.is_some());
+}
+
+#[rocket::async_test]
+async fn get_entry_decodes_request_envelope() {
+    let cache = setup_cache().await;
+
+    let actual = get_entry(&cache, "c_reqs_https://example.com/api/v1/safes/0x1", None)
+        .await
+        .unwrap();
+
+    assert_eq!(actual.version, Some(CacheEntry::VERSION));
+    assert_eq!(actual.status, Some(200));
+    assert_eq!(
+        actual.url.as_deref(),
+        Some("https://example.com/api/v1/safes/0x1")
+    );
+    assert_eq!(actual.body, "{}");
+}
+
+#[rocket::async_test]
+async fn get_entry_returns_plain_response() {
+    let cache = setup_cache().await;
+
+    let actual = get_entry(&cache, "c_resp_/v1/safes/0x1", None)
+        .await
+        .unwrap();
+
+    assert_eq!(actual.key, namespaced("c_resp_/v1/safes/0x1"));
+    assert_eq!(actual.status, None);
+    assert_eq!(actual.body, "\"safe\"");
+}
+
+#[rocket::async_test]
+async fn get_entry_missing_key() {
+    let cache = setup_cache().await;
+
+    let actual = get_entry(&cache, "c_resp_/v1/unknown", None)
+        .await
+        .expect_err("Missing entries are not found");
+
+    assert_eq!(actual.status, 404);
+}
+
+#[rocket::async_test]
+async fn family_stats_counts_keys() {
+    let cache = setup_cache().await;
+
+    let actual = family_stats(&cache).await;
+
+    let responses = actual
+        .iter()
+        .find(|stats| stats.family == "c_resp")
+        .unwrap();
+    let requests = actual
+        .iter()
+        .find(|stats| stats.family == "c_reqs")
+        .unwrap();
+    assert_eq!(responses.keys, 2);
+    assert_eq!(responses.sampled_keys, 2);
+    assert!(responses.estimated_memory > 0);
+    assert_eq!(requests.keys, 1);
+}
+
+#[rocket::async_test]
+async fn flush_dry_run_keeps_keys() {
+    let cache = setup_cache().await;
+    let pattern = InvalidationPattern::Any(InvalidationScope::Both, String::from("/v1/safes/0x1"));
+
+    let actual = flush(&cache, pattern, chain_id(), true).await.unwrap();
+
+    assert!(actual.dry_run);
+    assert_eq!(actual.keys.len(), 2);
+    assert!(cache.has_key(&namespaced("c_resp_/v1/safes/0x1")).await);
+}
+
+#[rocket::async_test]
+async fn flush_deletes_matching_keys() {
+    let cache = setup_cache().await;
+    let pattern = InvalidationPattern::Any(InvalidationScope::Both, String::from("/v1/safes/0x1"));
+
+    let actual = flush(&cache, pattern, chain_id(), false).await.unwrap();
+
+    assert!(!actual.dry_run);
+    assert_eq!(actual.keys.len(), 2);
+    assert!(!cache.has_key(&namespaced("c_resp_/v1/safes/0x1")).await);
+    assert!(cache.has_key(&namespaced("c_resp_/v1/safes/0x2")).await);
+}
+
+#[rocket::async_test]
+async fn flush_dry_run_reports_tagged_keys() {
+    let address = "0x1230B3d59858296A31053C1b8562Ecf89A2f888b";
+    let cache = InMemoryCache::new(10);
+    let tagged = namespaced(&format!("c_resp_/v1/safes/{}/balances/USD", address));
+    cache.create(&tagged, "{}", 60).await;
+    let tag = namespaced(&format!("c_tag_{}_{}", chain_id(), address.to_lowercase()));
+    cache.tag(&tagged, &[tag], 60).await;
+    // Only keys stored through the cache operations are tagged and deleted
+    let untagged = namespaced(&format!("c_resp_/v1/safes/{}/collectibles", address));
+    cache.create(&untagged, "{}", 60).await;
+    let pattern = || InvalidationPattern::Any(InvalidationScope::Both, address.to_string());
+
+    let report = flush(&cache, pattern(), chain_id(), true).await.unwrap();
+    let deleted = flush(&cache, pattern(), chain_id(), false).await.unwrap();
+
+    assert_eq!(report.keys, vec![tagged.to_string()]);
+    assert_eq!(deleted.keys, report.keys);
+    assert!(!cache.has_key(&tagged).await);
+    assert!(cache.has_key(&untagged).await);
+}
+
+#[rocket::async_test]
+async fn list_keys_resolves_address_through_tag() {
+    let address = "0x1230B3d59858296A31053C1b8562Ecf89A2f888b";
+    let cache = InMemoryCache::new(10);
+    let tagged = namespaced(&format!("c_resp_/v1/safes/{}/balances/USD", address));
+    cache.create(&tagged, "{}", 60).await;
+    let tag = namespaced(&format!("c_tag_{}_{}", chain_id(), address.to_lowercase()));
+    cache.tag(&tagged, &[tag], 60).await;
+    let untagged = namespaced(&format!("c_resp_/v1/safes/{}/collectibles", address));
+    cache.create(&untagged, "{}", 60).await;
+    let pattern = InvalidationPattern::Any(InvalidationScope::Both, address.to_string());
+
+    let actual = list_keys(&cache, &pattern, chain_id(), 100).await;
+
+    let keys: Vec<String> = actual.into_iter().map(|key| key.key).collect();
+    assert_eq!(keys, vec![tagged]);
+}
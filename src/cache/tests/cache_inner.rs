@@ -1,97 +1,125 @@
-use crate::cache::inner_cache::CachedWithCode;
+use crate::cache::compression::CompressionSettings;
+use crate::cache::inner_cache::CacheEntry;
 use crate::utils::errors::{ApiError, ErrorDetails};
 
+fn legacy_entry(status: u16, body: &str) -> CacheEntry {
+    CacheEntry {
+        version: 0,
+        status,
+        body: String::from(body),
+        created_at: None,
+        url: None,
+        content_hash: None,
+        compressed: false,
+    }
+}
+
 #[test]
-fn cache_with_code_split_success() {
+fn cache_entry_parse_legacy_success() {
     let input = "400;123";
 
-    let cached_with_code = CachedWithCode::split(input);
-    let expected = CachedWithCode {
-        code: 400,
-        data: String::from("123"),
-    };
+    let actual = CacheEntry::parse(input);
 
-    assert_eq!(cached_with_code, expected);
+    assert_eq!(actual, Some(legacy_entry(400, "123")));
 }
 
 #[test]
-#[should_panic]
-fn cache_with_code_split_failure_parse() {
-    CachedWithCode::split("400A;123");
+fn cache_entry_parse_legacy_failure_parse() {
+    assert_eq!(CacheEntry::parse("400A;123"), None);
 }
 
 #[test]
-#[should_panic]
-fn cache_with_code_split_failure_not_enough_parts() {
-    CachedWithCode::split("400MissingSeparatorForSomeReason");
+fn cache_entry_parse_legacy_failure_not_enough_parts() {
+    assert_eq!(CacheEntry::parse("400MissingSeparatorForSomeReason"), None);
 }
 
 #[test]
-fn cache_with_code_split_multiple_separators() {
-    let expected = CachedWithCode {
-        code: 404,
-        data: String::from("foo;bar"),
-    };
-    let actual = CachedWithCode::split("404;foo;bar");
+fn cache_entry_parse_legacy_multiple_separators() {
+    let actual = CacheEntry::parse("404;foo;bar");
 
-    assert_eq!(actual, expected);
+    assert_eq!(actual, Some(legacy_entry(404, "foo;bar")));
 }
 
 #[test]
-fn cache_with_code_split_data_is_only_separators() {
-    let expected = CachedWithCode {
-        code: 404,
-        data: String::from(";;;;;"),
-    };
-    let actual = CachedWithCode::split("404;;;;;;");
+fn cache_entry_parse_legacy_data_is_only_separators() {
+    let actual = CacheEntry::parse("404;;;;;;");
 
-    assert_eq!(actual, expected);
+    assert_eq!(actual, Some(legacy_entry(404, ";;;;;")));
 }
 
 #[test]
-fn cache_with_code_join() {
-    let actual = CachedWithCode::join(400, "data");
-    let expected = String::from("400;data");
+fn cache_entry_new() {
+    let actual = CacheEntry::new(200, "", "https://example.com/api/v1/safes/");
+
+    assert_eq!(actual.version, CacheEntry::VERSION);
+    assert_eq!(actual.status, 200);
+    assert_eq!(actual.body, "");
+    assert!(actual.created_at.is_some());
+    assert_eq!(
+        actual.url.as_deref(),
+        Some("https://example.com/api/v1/safes/")
+    );
+    assert_eq!(
+        actual.content_hash.as_deref(),
+        Some("0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470")
+    );
+    assert!(!actual.compressed);
+}
+
+#[test]
+fn cache_entry_round_trip() {
+    let entry = CacheEntry::new(404, "{\"detail\":\"Not found.\"}", "https://example.com");
 
-    assert_eq!(actual, expected);
+    let actual = CacheEntry::parse(&entry.to_cache_string(CompressionSettings::configured()));
+
+    assert_eq!(actual, Some(entry));
 }
 
 #[test]
-fn cache_with_code_error_code() {
-    let cached_with_code = CachedWithCode {
-        code: 418,
-        data: "teapot".to_string(),
+fn cache_entry_parse_envelope_without_compression_flag() {
+    let input = "{\"version\":1,\"status\":200,\"body\":\"data\",\"createdAt\":1620000000,\"url\":null,\"contentHash\":null}";
+    let expected = CacheEntry {
+        version: 1,
+        status: 200,
+        body: String::from("data"),
+        created_at: Some(1620000000),
+        url: None,
+        content_hash: None,
+        compressed: false,
     };
 
-    assert!(cached_with_code.is_error())
+    assert_eq!(CacheEntry::parse(input), Some(expected));
 }
 
 #[test]
-fn cache_with_code_success_code() {
-    let cached_with_code = CachedWithCode {
-        code: 200,
-        data: "not a teapot".to_string(),
-    };
+fn cache_entry_parse_corrupt_envelope() {
+    assert_eq!(CacheEntry::parse("{\"version\":1,\"status\":"), None);
+}
+
+#[test]
+fn cache_entry_error_code() {
+    let entry = legacy_entry(418, "teapot");
 
-    assert!(!cached_with_code.is_error())
+    assert!(entry.is_error())
 }
 
 #[test]
-fn cache_with_code_unwrap_ok() {
-    let cached_with_code = CachedWithCode {
-        code: 200,
-        data: "not a teapot".to_string(),
-    };
+fn cache_entry_success_code() {
+    let entry = legacy_entry(200, "not a teapot");
 
-    assert_eq!(cached_with_code.to_result().unwrap(), "not a teapot");
+    assert!(!entry.is_error())
 }
 
 #[test]
-fn cache_with_code_unwrap_err() {
-    let cached_with_code = CachedWithCode {
-        code: 418,
-        data: "teapot".to_string(),
-    };
+fn cache_entry_unwrap_ok() {
+    let entry = legacy_entry(200, "not a teapot");
+
+    assert_eq!(entry.to_result().unwrap(), "not a teapot");
+}
+
+#[test]
+fn cache_entry_unwrap_err() {
+    let entry = legacy_entry(418, "teapot");
     let expected = ApiError {
         status: 418,
         details: ErrorDetails {
@@ -101,5 +129,5 @@ fn cache_with_code_unwrap_err() {
         },
     };
 
-    assert_eq!(cached_with_code.to_result().expect_err(""), expected);
+    assert_eq!(entry.to_result().expect_err(""), expected);
 }
@@ -0,0 +1,151 @@
+use crate::cache::memory::{glob_match, InMemoryCache};
+use crate::cache::Cache;
+
+#[rocket::async_test]
+async fn memory_cache_create_and_fetch() {
+    let cache = InMemoryCache::new(10);
+
+    cache.create("some_key", "some_value", 60).await;
+
+    assert_eq!(
+        cache.fetch("some_key").await,
+        Some(String::from("some_value"))
+    );
+    assert!(cache.has_key("some_key").await);
+    assert_eq!(cache.fetch("other_key").await, None);
+}
+
+#[rocket::async_test]
+async fn memory_cache_expire_entity_zero_deletes_key() {
+    let cache = InMemoryCache::new(10);
+    cache.create("some_key", "some_value", 60).await;
+
+    cache.expire_entity("some_key", 0).await;
+
+    assert_eq!(cache.fetch("some_key").await, None);
+    assert!(!cache.has_key("some_key").await);
+}
+
+#[rocket::async_test]
+async fn memory_cache_evicts_least_recently_used() {
+    let cache = InMemoryCache::new(2);
+    cache.create("first", "1", 60).await;
+    cache.create("second", "2", 60).await;
+    cache.fetch("first").await;
+
+    cache.create("third", "3", 60).await;
+
+    assert_eq!(cache.fetch("first").await, Some(String::from("1")));
+    assert_eq!(cache.fetch("second").await, None);
+    assert_eq!(cache.fetch("third").await, Some(String::from("3")));
+}
+
+#[rocket::async_test]
+async fn memory_cache_does_not_evict_hashes() {
+    let cache = InMemoryCache::new(1);
+    cache.insert_in_hash("settings", "overrides", "{}").await;
+
+    cache.create("first", "1", 60).await;
+    cache.create("second", "2", 60).await;
+
+    assert_eq!(
+        cache.get_from_hash("settings", "overrides").await,
+        Some(String::from("{}"))
+    );
+    assert_eq!(cache.fetch("first").await, None);
+    assert_eq!(cache.fetch("second").await, Some(String::from("2")));
+}
+
+#[rocket::async_test]
+async fn memory_cache_hash_operations() {
+    let cache = InMemoryCache::new(10);
+
+    cache.insert_in_hash("hash", "field", "value").await;
+    cache
+        .insert_in_hash("hash", "other_field", "other_value")
+        .await;
+
+    assert!(cache.has_key("hash").await);
+    assert_eq!(
+        cache.get_from_hash("hash", "field").await,
+        Some(String::from("value"))
+    );
+    assert_eq!(
+        cache.get_from_hash("hash", "other_field").await,
+        Some(String::from("other_value"))
+    );
+    assert_eq!(cache.get_from_hash("hash", "missing").await, None);
+    assert_eq!(cache.fetch("hash").await, None);
+}
+
+#[rocket::async_test]
+async fn memory_cache_invalidate_pattern() {
+    let cache = InMemoryCache::new(10);
+    cache
+        .create("c_resp_/v1/safes/0x1230/balances/usd", "{}", 60)
+        .await;
+    cache
+        .create("c_reqs_/api/v1/safes/0x1230/", "200;{}", 60)
+        .await;
+    cache.create("c_resp_/v1/safes/0x4560", "{}", 60).await;
+
+    cache.invalidate_pattern("c_re*0x1230*").await;
+
+    assert_eq!(
+        cache.fetch("c_resp_/v1/safes/0x1230/balances/usd").await,
+        None
+    );
+    assert_eq!(cache.fetch("c_reqs_/api/v1/safes/0x1230/").await, None);
+    assert_eq!(
+        cache.fetch("c_resp_/v1/safes/0x4560").await,
+        Some(String::from("{}"))
+    );
+}
+
+#[rocket::async_test]
+async fn memory_cache_invalidate() {
+    let cache = InMemoryCache::new(10);
+    cache.create("some_key", "some_value", 60).await;
+
+    cache.invalidate("some_key").await;
+
+    assert_eq!(cache.fetch("some_key").await, None);
+}
+
+#[test]
+fn glob_match_wildcards() {
+    assert!(glob_match("*", ""));
+    assert!(glob_match("c_re*0xabc*", "c_reqs_/v1/safes/0xabc/"));
+    assert!(glob_match("c_re*0xabc*", "c_re0xabc"));
+    assert!(!glob_match("c_re*0xabc*", "c_resp_/v1/safes/0xdef/"));
+    assert!(glob_match("h?llo", "hello"));
+    assert!(!glob_match("h?llo", "hllo"));
+}
+
+#[test]
+fn glob_match_many_wildcards() {
+    let key = "a".repeat(100);
+
+    assert!(!glob_match(&format!("{}b", "*a".repeat(30)), &key));
+    assert!(glob_match(&"*a".repeat(30), &key));
+    assert!(glob_match("**a**", "a"));
+    assert!(!glob_match("a*", ""));
+}
+
+#[test]
+fn glob_match_classes() {
+    assert!(glob_match("h[ae]llo", "hallo"));
+    assert!(!glob_match("h[ae]llo", "hillo"));
+    assert!(glob_match("h[^e]llo", "hallo"));
+    assert!(!glob_match("h[^e]llo", "hello"));
+    assert!(glob_match("h[a-b]llo", "hbllo"));
+    assert!(glob_match("h[b-a]llo", "hallo"));
+    assert!(!glob_match("h[a-b]llo", "hcllo"));
+}
+
+#[test]
+fn glob_match_escapes() {
+    assert!(glob_match("h\\*llo", "h*llo"));
+    assert!(!glob_match("h\\*llo", "hello"));
+    assert!(glob_match("h[\\]]llo", "h]llo"));
+}
@@ -1,12 +1,22 @@
-use crate::cache::cache_operations::{InvalidationPattern, InvalidationScope};
-use crate::cache::{CACHE_REQS_PREFIX, CACHE_REQS_RESP_PREFIX, CACHE_RESP_PREFIX};
+use crate::cache::cache_op_executors::revalidate;
+use crate::cache::cache_operations::{CacheResponse, InvalidationPattern, InvalidationScope};
+use crate::cache::memory::InMemoryCache;
+use crate::cache::{
+    namespaced, Cache, MockCache, ServiceCache, CACHE_REQS_PREFIX, CACHE_REQS_RESP_PREFIX,
+    CACHE_RESP_PREFIX,
+};
 use crate::providers::info::TOKENS_KEY;
+use crate::utils::errors::ApiResult;
+use rocket::futures::future::pending;
+use rocket::tokio::sync::mpsc::unbounded_channel;
+use rocket::tokio::sync::oneshot;
+use std::sync::Arc;
 
 #[test]
 fn invalidation_pattern_any_string() {
     let invalidation_pattern =
         InvalidationPattern::Any(InvalidationScope::Both, "some_address".to_string());
-    let expected = format!("{}*some_address*", CACHE_REQS_RESP_PREFIX);
+    let expected = namespaced(&format!("{}*some_address*", CACHE_REQS_RESP_PREFIX));
 
     let actual = invalidation_pattern.to_pattern_string();
 
@@ -17,7 +27,59 @@ fn invalidation_pattern_any_string() {
 fn invalidation_pattern_transactions_string() {
     let invalidation_pattern =
         InvalidationPattern::Transactions(InvalidationScope::Both, "some_address".to_string());
-    let expected = format!("{}*/some_address/*transactions/*", CACHE_REQS_RESP_PREFIX);
+    let expected = namespaced(&format!(
+        "{}*/some_address/*transactions/*",
+        CACHE_REQS_RESP_PREFIX
+    ));
+
+    let actual = invalidation_pattern.to_pattern_string();
+
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn invalidation_pattern_queued_transactions_string() {
+    let invalidation_pattern = InvalidationPattern::QueuedTransactions(
+        InvalidationScope::Both,
+        "some_address".to_string(),
+    );
+    let expected = namespaced(&format!(
+        "{}*/some_address/*transactions/[q?]*",
+        CACHE_REQS_RESP_PREFIX
+    ));
+
+    let actual = invalidation_pattern.to_pattern_string();
+
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn invalidation_pattern_safe_info_string() {
+    let invalidation_pattern =
+        InvalidationPattern::SafeInfo(InvalidationScope::Responses, "some_address".to_string());
+    let expected = namespaced(&format!("{}*/safes/some_address", CACHE_RESP_PREFIX));
+
+    let actual = invalidation_pattern.to_pattern_string();
+
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn invalidation_pattern_safe_info_requests_string() {
+    let invalidation_pattern =
+        InvalidationPattern::SafeInfo(InvalidationScope::Requests, "some_address".to_string());
+    let expected = namespaced(&format!("{}*/safes/some_address/", CACHE_REQS_PREFIX));
+
+    let actual = invalidation_pattern.to_pattern_string();
+
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn invalidation_pattern_safe_info_both_string() {
+    let invalidation_pattern =
+        InvalidationPattern::SafeInfo(InvalidationScope::Both, "some_address".to_string());
+    let expected = namespaced(&format!("{}*/safes/some_address*", CACHE_REQS_RESP_PREFIX));
 
     let actual = invalidation_pattern.to_pattern_string();
 
@@ -28,7 +90,7 @@ fn invalidation_pattern_transactions_string() {
 fn invalidation_pattern_transfers_string() {
     let invalidation_pattern =
         InvalidationPattern::Transfers(InvalidationScope::Requests, "some_address".to_string());
-    let expected = format!("{}*/some_address/*transfer*", CACHE_REQS_PREFIX);
+    let expected = namespaced(&format!("{}*/some_address/*transfer*", CACHE_REQS_PREFIX));
 
     let actual = invalidation_pattern.to_pattern_string();
 
@@ -38,7 +100,7 @@ fn invalidation_pattern_transfers_string() {
 #[test]
 fn invalidation_pattern_tokens_string() {
     let invalidation_pattern = InvalidationPattern::Tokens;
-    let expected = TOKENS_KEY.to_string();
+    let expected = namespaced(&format!("{}_*", TOKENS_KEY));
 
     let actual = invalidation_pattern.to_pattern_string();
 
@@ -48,7 +110,7 @@ fn invalidation_pattern_tokens_string() {
 #[test]
 fn invalidation_pattern_contracts_string() {
     let invalidation_pattern = InvalidationPattern::Contracts;
-    let expected = String::from("*contract*");
+    let expected = namespaced("*contract*");
 
     let actual = invalidation_pattern.to_pattern_string();
 
@@ -59,7 +121,10 @@ fn invalidation_pattern_contracts_string() {
 fn invalidation_pattern_balances_string() {
     let invalidation_pattern =
         InvalidationPattern::Balances(InvalidationScope::Both, "some_address".to_string());
-    let expected = format!("{}*/some_address/balances*", CACHE_REQS_RESP_PREFIX);
+    let expected = namespaced(&format!(
+        "{}*/some_address/balances*",
+        CACHE_REQS_RESP_PREFIX
+    ));
 
     let actual = invalidation_pattern.to_pattern_string();
 
@@ -70,7 +135,10 @@ fn invalidation_pattern_balances_string() {
 fn invalidation_pattern_collectibles_string() {
     let invalidation_pattern =
         InvalidationPattern::Collectibles(InvalidationScope::Both, "some_address".to_string());
-    let expected = format!("{}*/some_address/collectibles*", CACHE_REQS_RESP_PREFIX);
+    let expected = namespaced(&format!(
+        "{}*/some_address/collectibles*",
+        CACHE_REQS_RESP_PREFIX
+    ));
 
     let actual = invalidation_pattern.to_pattern_string();
 
@@ -100,3 +168,90 @@ fn invalidation_scope_responses_to_string() {
         InvalidationScope::Responses.invalidation_scope_string()
     )
 }
+
+#[rocket::async_test]
+async fn cache_response_serves_stale_value_and_revalidates() {
+    let (sender, mut receiver) = unbounded_channel();
+    let mut mock_cache = MockCache::new();
+    mock_cache
+        .expect_fetch()
+        .return_const(Some(String::from("\"stale\"")));
+    mock_cache.expect_time_to_live().return_const(Some(30usize));
+    mock_cache
+        .expect_create()
+        .times(1)
+        .returning(move |key, value, timeout| {
+            sender
+                .send((key.to_string(), value.to_string(), timeout))
+                .unwrap()
+        });
+    let cache = ServiceCache(Arc::new(mock_cache));
+
+    let actual = CacheResponse::new(String::from("/stale"))
+        .duration(60)
+        .resp_generator(|| async { Ok(String::from("fresh")) })
+        .stale_while_revalidate(10)
+        .execute(&cache)
+        .await
+        .unwrap();
+
+    assert_eq!(actual.0, "\"stale\"");
+    assert_eq!(
+        receiver.recv().await,
+        Some((namespaced("c_resp_/stale"), String::from("\"fresh\""), 60))
+    );
+}
+
+#[rocket::async_test]
+async fn cache_response_does_not_revalidate_fresh_value() {
+    let cache = ServiceCache(Arc::new(InMemoryCache::new(10)));
+    cache
+        .create(&namespaced("c_resp_/fresh"), "\"cached\"", 60)
+        .await;
+
+    let actual = CacheResponse::new(String::from("/fresh"))
+        .duration(60)
+        .resp_generator(|| pending::<ApiResult<String>>())
+        .stale_while_revalidate(10)
+        .execute(&cache)
+        .await
+        .unwrap();
+
+    assert_eq!(actual.0, "\"cached\"");
+    // A revalidation would still be waiting for the generator
+    assert!(revalidate(namespaced("c_resp_/fresh"), async {}).is_some());
+}
+
+#[rocket::async_test]
+async fn revalidate_once_per_key() {
+    let (sender, receiver) = oneshot::channel();
+
+    let first = revalidate(String::from("once"), async move {
+        receiver.await.unwrap();
+    });
+    let concurrent = revalidate(String::from("once"), async {});
+
+    assert!(first.is_some());
+    assert!(concurrent.is_none());
+    sender.send(()).unwrap();
+    first.unwrap().await.unwrap();
+    assert!(revalidate(String::from("once"), async {}).is_some());
+}
+
+#[rocket::async_test]
+async fn revalidate_releases_key_of_failed_revalidation() {
+    let failed = revalidate(String::from("panics"), async {
+        panic!("Revalidation failed");
+    });
+
+    assert!(failed.unwrap().await.is_err());
+    assert!(revalidate(String::from("panics"), async {}).is_some());
+}
+
+#[test]
+fn namespaced_key_includes_schema_version() {
+    let actual = namespaced("c_resp_/about");
+
+    assert!(actual.starts_with("scg:"));
+    assert!(actual.ends_with(":v1:c_resp_/about"));
+}
@@ -0,0 +1,133 @@
+use crate::cache::cache_operations::{InvalidationPattern, InvalidationScope};
+use crate::cache::memory::InMemoryCache;
+use crate::cache::tags::{extract_tags, tag_for};
+use crate::cache::{namespaced, Cache};
+use crate::config::chains::default_chain;
+
+fn tag(chain_id: u64, identifier: &str) -> String {
+    namespaced(&format!("c_tag_{}_{}", chain_id, identifier))
+}
+
+#[test]
+fn extract_tags_from_response_key() {
+    let key = namespaced("c_resp_/v1/transactions/multisig_0x1230B3d59858296A31053C1b8562Ecf89A2f888b_0x65df8a1e5a40703d9c67d5df6f9b552d3830faf0507c3d7350ba3764d3a68621");
+    let chain_id = default_chain().chain_id;
+
+    let actual = extract_tags(&key);
+
+    assert_eq!(
+        actual,
+        vec![
+            tag(chain_id, "0x1230b3d59858296a31053c1b8562ecf89a2f888b"),
+            tag(
+                chain_id,
+                "0x65df8a1e5a40703d9c67d5df6f9b552d3830faf0507c3d7350ba3764d3a68621"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn extract_tags_of_chain_scoped_response_key() {
+    let key = namespaced("c_resp_/v1/chains/1337/safes/0x1230B3d59858296A31053C1b8562Ecf89A2f888b");
+
+    let actual = extract_tags(&key);
+
+    assert_eq!(
+        actual,
+        vec![tag(1337, "0x1230b3d59858296a31053c1b8562ecf89a2f888b")]
+    );
+}
+
+#[test]
+fn extract_tags_deduplicates() {
+    let key = namespaced(&format!(
+        "c_reqs_{}/v1/safes/0x1230B3d59858296A31053C1b8562Ecf89A2f888b/transfers/?safe=0x1230b3d59858296a31053c1b8562ecf89a2f888b",
+        default_chain().base_transaction_service_url()
+    ));
+
+    let actual = extract_tags(&key);
+
+    assert_eq!(
+        actual,
+        vec![tag(
+            default_chain().chain_id,
+            "0x1230b3d59858296a31053c1b8562ecf89a2f888b"
+        )]
+    );
+}
+
+#[test]
+fn extract_tags_without_identifiers() {
+    assert!(extract_tags(&namespaced("c_resp_/about")).is_empty());
+}
+
+#[test]
+fn tag_for_whole_identifier_only() {
+    assert_eq!(
+        tag_for(4, "0x1230B3d59858296A31053C1b8562Ecf89A2f888b"),
+        Some(tag(4, "0x1230b3d59858296a31053c1b8562ecf89a2f888b"))
+    );
+    assert_eq!(
+        tag_for(4, "safes/0x1230B3d59858296A31053C1b8562Ecf89A2f888b"),
+        None
+    );
+    assert_eq!(tag_for(4, "some_value"), None);
+}
+
+#[test]
+fn invalidation_pattern_to_tag() {
+    let address = String::from("0x1230B3d59858296A31053C1b8562Ecf89A2f888b");
+
+    assert_eq!(
+        InvalidationPattern::Balances(InvalidationScope::Both, address).to_tag(4),
+        Some(tag(4, "0x1230b3d59858296a31053c1b8562ecf89a2f888b"))
+    );
+    assert_eq!(
+        InvalidationPattern::Any(InvalidationScope::Both, String::from("balances")).to_tag(4),
+        None
+    );
+    assert_eq!(InvalidationPattern::Contracts.to_tag(4), None);
+    assert_eq!(InvalidationPattern::Tokens.to_tag(4), None);
+}
+
+#[rocket::async_test]
+async fn memory_cache_invalidate_tag_only_matching_keys() {
+    let cache = InMemoryCache::new(10);
+    let tag = "c_tag_0x1230";
+    let balances = "c_resp_/v1/safes/0x1230/balances/usd";
+    let safe = "c_resp_/v1/safes/0x1230";
+    let other = "c_resp_/v1/safes/0x4560";
+    for key in &[balances, safe, other] {
+        cache.create(key, "{}", 60).await;
+    }
+    cache.tag(balances, &[String::from(tag)], 60).await;
+    cache.tag(safe, &[String::from(tag)], 60).await;
+
+    cache.invalidate_tag(tag, "c_re*/0x1230/balances*").await;
+
+    assert_eq!(cache.fetch(balances).await, None);
+    assert_eq!(cache.fetch(safe).await, Some(String::from("{}")));
+    assert_eq!(cache.fetch(other).await, Some(String::from("{}")));
+
+    cache.invalidate_tag(tag, "c_re*0x1230*").await;
+
+    assert_eq!(cache.fetch(safe).await, None);
+    assert_eq!(cache.fetch(other).await, Some(String::from("{}")));
+}
+
+#[rocket::async_test]
+async fn memory_cache_keeps_tags_when_evicting() {
+    let cache = InMemoryCache::new(2);
+    let tag = "c_tag_0x1230";
+    let safe = "c_resp_/v1/safes/0x1230";
+    cache.create(safe, "{}", 60).await;
+    cache.tag(safe, &[String::from(tag)], 60).await;
+
+    cache.create("c_resp_/v1/about", "{}", 60).await;
+    cache.create("c_resp_/v1/chains", "{}", 60).await;
+    cache.create(safe, "{}", 60).await;
+    cache.invalidate_tag(tag, "c_re*0x1230*").await;
+
+    assert_eq!(cache.fetch(safe).await, None);
+}
@@ -0,0 +1,73 @@
+use crate::cache::memory::InMemoryCache;
+use crate::cache::tiered::apply_invalidation;
+use crate::cache::Cache;
+
+#[rocket::async_test]
+async fn apply_invalidation_pattern_message() {
+    let local = InMemoryCache::new(10);
+    local.create("c_resp_/v1/safes/0x1230", "{}", 60).await;
+    local.create("c_resp_/v1/safes/0x4560", "{}", 60).await;
+
+    apply_invalidation(&local, "pattern:c_re*0x1230*").await;
+
+    assert_eq!(local.fetch("c_resp_/v1/safes/0x1230").await, None);
+    assert_eq!(
+        local.fetch("c_resp_/v1/safes/0x4560").await,
+        Some(String::from("{}"))
+    );
+}
+
+#[rocket::async_test]
+async fn apply_invalidation_tag_message_ignores_case() {
+    let checksummed = "c_resp_/v1/safes/0x1230B3d59858296A31053C1b8562Ecf89A2f888b/balances/USD";
+    let lowercase = "c_reqs_/api/v1/safes/0x1230b3d59858296a31053c1b8562ecf89a2f888b/";
+    let other = "c_resp_/v1/safes/0x4560";
+    let local = InMemoryCache::new(10);
+    for key in &[checksummed, lowercase, other] {
+        local.create(key, "{}", 60).await;
+    }
+
+    apply_invalidation(
+        &local,
+        "tag:c_re*0x1230B3d59858296A31053C1b8562Ecf89A2f888b*",
+    )
+    .await;
+
+    assert_eq!(local.fetch(checksummed).await, None);
+    assert_eq!(local.fetch(lowercase).await, None);
+    assert_eq!(local.fetch(other).await, Some(String::from("{}")));
+}
+
+#[rocket::async_test]
+async fn apply_invalidation_key_message() {
+    let local = InMemoryCache::new(10);
+    local.create("dip_ti", "{}", 60).await;
+    local.create("c_resp_*", "{}", 60).await;
+
+    apply_invalidation(&local, "key:c_resp_*").await;
+
+    assert_eq!(local.fetch("c_resp_*").await, None);
+    assert_eq!(local.fetch("dip_ti").await, Some(String::from("{}")));
+}
+
+#[rocket::async_test]
+async fn apply_invalidation_unknown_message() {
+    let local = InMemoryCache::new(10);
+    local.create("some_key", "some_value", 60).await;
+
+    apply_invalidation(&local, "some_key").await;
+
+    assert_eq!(
+        local.fetch("some_key").await,
+        Some(String::from("some_value"))
+    );
+}
+
+#[rocket::async_test]
+async fn memory_cache_max_timeout_expires_hashes() {
+    let local = InMemoryCache::new(10).with_max_timeout(0);
+
+    local.insert_in_hash("hash", "field", "value").await;
+
+    assert_eq!(local.get_from_hash("hash", "field").await, None);
+}
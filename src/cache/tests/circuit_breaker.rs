@@ -0,0 +1,67 @@
+use crate::cache::circuit_breaker::CircuitBreaker;
+use std::time::Duration;
+
+#[test]
+fn circuit_breaker_closed_allows_requests() {
+    let circuit_breaker = CircuitBreaker::new(Duration::from_secs(60), Duration::from_secs(60));
+
+    assert!(circuit_breaker.allows_request());
+    assert!(circuit_breaker.allows_request());
+    assert!(!circuit_breaker.is_open());
+}
+
+#[test]
+fn circuit_breaker_open_rejects_requests_during_cooldown() {
+    let circuit_breaker = CircuitBreaker::new(Duration::from_secs(60), Duration::from_secs(60));
+
+    circuit_breaker.record_failure();
+
+    assert!(circuit_breaker.is_open());
+    assert!(!circuit_breaker.allows_request());
+}
+
+#[test]
+fn circuit_breaker_allows_single_probe_after_cooldown() {
+    let circuit_breaker = CircuitBreaker::new(Duration::from_secs(0), Duration::from_secs(60));
+
+    circuit_breaker.record_failure();
+
+    assert!(circuit_breaker.allows_request());
+    assert!(!circuit_breaker.allows_request());
+}
+
+#[test]
+fn circuit_breaker_closes_on_success() {
+    let circuit_breaker = CircuitBreaker::new(Duration::from_secs(0), Duration::from_secs(60));
+    circuit_breaker.record_failure();
+    circuit_breaker.allows_request();
+
+    circuit_breaker.record_success();
+
+    assert!(!circuit_breaker.is_open());
+    assert!(circuit_breaker.allows_request());
+    assert!(circuit_breaker.allows_request());
+}
+
+#[test]
+fn circuit_breaker_failed_probe_reopens() {
+    let circuit_breaker = CircuitBreaker::new(Duration::from_secs(60), Duration::from_secs(60));
+    circuit_breaker.record_failure();
+
+    circuit_breaker.record_failure();
+
+    assert!(circuit_breaker.is_open());
+    assert!(!circuit_breaker.allows_request());
+}
+
+#[test]
+fn circuit_breaker_probes_again_after_probe_timeout() {
+    let circuit_breaker = CircuitBreaker::new(Duration::from_secs(0), Duration::from_secs(0));
+    circuit_breaker.record_failure();
+
+    // The first probe never reports back, e.g. because its request was dropped
+    assert!(circuit_breaker.allows_request());
+
+    assert!(circuit_breaker.allows_request());
+    assert!(circuit_breaker.is_open());
+}
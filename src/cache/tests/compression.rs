@@ -0,0 +1,67 @@
+use crate::cache::compression::{pack, unpack, CompressionSettings};
+use crate::cache::inner_cache::CacheEntry;
+
+const ENABLED: CompressionSettings = CompressionSettings {
+    enabled: true,
+    threshold: 1024,
+};
+
+#[test]
+fn pack_large_value_round_trip() {
+    let value = format!("[{}]", vec!["{\"address\":\"0x1230\"}"; 100].join(","));
+
+    let packed = pack(&value, ENABLED);
+
+    assert!(packed.starts_with("gz:"));
+    assert!(packed.len() < value.len());
+    assert_eq!(unpack(packed), Some(value));
+}
+
+#[test]
+fn pack_small_value_is_stored_as_is() {
+    let value = String::from("{\"address\":\"0x1230\"}");
+
+    let packed = pack(&value, ENABLED);
+
+    assert_eq!(packed, value);
+    assert_eq!(unpack(packed), Some(value));
+}
+
+#[test]
+fn unpack_corrupt_value() {
+    assert_eq!(unpack(String::from("gz:not_gzipped")), None);
+}
+
+#[test]
+fn cache_entry_large_body_round_trip() {
+    let body = format!("[{}]", vec!["{\"address\":\"0x1230\"}"; 100].join(","));
+    let entry = CacheEntry::new(200, &body, "https://example.com");
+
+    let cache_string = entry.to_cache_string(ENABLED);
+
+    assert!(cache_string.contains("\"compressed\":true"));
+    assert!(!cache_string.contains("0x1230"));
+    assert_eq!(CacheEntry::parse(&cache_string), Some(entry));
+}
+
+#[test]
+fn pack_with_compression_disabled() {
+    let value = format!("[{}]", vec!["{\"address\":\"0x1230\"}"; 100].join(","));
+    let disabled = CompressionSettings {
+        enabled: false,
+        ..ENABLED
+    };
+
+    assert_eq!(pack(&value, disabled), value);
+}
+
+#[test]
+fn pack_value_that_does_not_shrink_is_stored_as_is() {
+    let value = String::from("{\"address\":\"0x1230\"}");
+    let without_threshold = CompressionSettings {
+        threshold: 0,
+        ..ENABLED
+    };
+
+    assert_eq!(pack(&value, without_threshold), value);
+}
@@ -1,2 +1,9 @@
+mod cache_admin;
 mod cache_inner;
+mod cache_memory;
 mod cache_operations;
+mod cache_tags;
+mod cache_tiered;
+mod circuit_breaker;
+mod compression;
+mod single_flight;
@@ -0,0 +1,163 @@
+use crate::cache::memory::InMemoryCache;
+use crate::cache::single_flight::coalesce;
+use crate::cache::{Cache, MockCache};
+use crate::utils::errors::{ApiError, ApiResult};
+use rocket::futures::future::{join, join3};
+use rocket::tokio::time::{sleep, timeout};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+async fn generate(cache: &InMemoryCache, calls: &AtomicUsize) -> ApiResult<String> {
+    calls.fetch_add(1, Ordering::SeqCst);
+    sleep(Duration::from_millis(20)).await;
+    cache.create("some_key", "generated", 60).await;
+    Ok(String::from("generated"))
+}
+
+#[rocket::async_test]
+async fn coalesce_runs_generator_once_for_concurrent_misses() {
+    let cache = InMemoryCache::new(10);
+    let calls = AtomicUsize::new(0);
+    let lookup = || async { cache.fetch("some_key").await.map(Ok) };
+
+    let (first, second) = join(
+        coalesce(&cache, "some_key", lookup, || generate(&cache, &calls)),
+        coalesce(&cache, "some_key", lookup, || generate(&cache, &calls)),
+    )
+    .await;
+
+    assert_eq!(first.unwrap(), "generated");
+    assert_eq!(second.unwrap(), "generated");
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[rocket::async_test]
+async fn coalesce_waits_for_lock_held_elsewhere() {
+    let cache = InMemoryCache::new(10);
+    let calls = AtomicUsize::new(0);
+    assert!(cache.acquire_lock("c_lock_some_key", "other", 1000).await);
+    let other_instance = async {
+        sleep(Duration::from_millis(20)).await;
+        cache.create("some_key", "from_other_instance", 60).await;
+    };
+
+    let (actual, _) = join(
+        coalesce(
+            &cache,
+            "some_key",
+            || async { cache.fetch("some_key").await.map(Ok) },
+            || generate(&cache, &calls),
+        ),
+        other_instance,
+    )
+    .await;
+
+    assert_eq!(actual.unwrap(), "from_other_instance");
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+}
+
+#[rocket::async_test]
+async fn coalesce_shares_generator_error_with_waiters() {
+    let cache = InMemoryCache::new(10);
+    let calls = AtomicUsize::new(0);
+    let lookup = || async { cache.fetch("some_key").await.map(Ok) };
+    let failing = || async {
+        calls.fetch_add(1, Ordering::SeqCst);
+        sleep(Duration::from_millis(20)).await;
+        Err::<String, _>(ApiError::new_from_message_with_code(
+            503,
+            String::from("Upstream unavailable"),
+        ))
+    };
+
+    let (first, second) = join(
+        coalesce(&cache, "some_key", lookup, failing),
+        coalesce(&cache, "some_key", lookup, failing),
+    )
+    .await;
+
+    assert_eq!(first.unwrap_err().status, 503);
+    assert_eq!(second.unwrap_err().status, 503);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    // Nobody is waiting anymore, so the next request tries again
+    let next = coalesce(&cache, "some_key", lookup, || generate(&cache, &calls)).await;
+    assert_eq!(next.unwrap(), "generated");
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[rocket::async_test]
+async fn coalesce_waiter_runs_generator_after_cancelled_request() {
+    let cache = InMemoryCache::new(10);
+    let calls = AtomicUsize::new(0);
+    let lookup = || async { cache.fetch("some_key").await.map(Ok) };
+    let never_done = || async {
+        calls.fetch_add(1, Ordering::SeqCst);
+        sleep(Duration::from_secs(60)).await;
+        Ok(String::from("never"))
+    };
+    let waiter = async {
+        sleep(Duration::from_millis(5)).await;
+        coalesce(&cache, "some_key", lookup, || generate(&cache, &calls)).await
+    };
+    // The cache lock of the cancelled request expires on its own
+    let lock_expiry = async {
+        sleep(Duration::from_millis(30)).await;
+        cache.invalidate("c_lock_some_key").await;
+    };
+
+    let (cancelled, actual, _) = join3(
+        timeout(
+            Duration::from_millis(20),
+            coalesce(&cache, "some_key", lookup, never_done),
+        ),
+        waiter,
+        lock_expiry,
+    )
+    .await;
+
+    assert!(cancelled.is_err());
+    assert_eq!(actual.unwrap(), "generated");
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[rocket::async_test]
+async fn coalesce_skips_locks_while_cache_is_unavailable() {
+    let mut mock_cache = MockCache::new();
+    mock_cache.expect_is_available().return_const(false);
+    mock_cache.expect_acquire_lock().times(0);
+    mock_cache.expect_fetch().times(0);
+
+    let actual = coalesce(
+        &mock_cache,
+        "some_key",
+        || async { Some(Ok(String::from("cached"))) },
+        || async { Ok(String::from("generated")) },
+    )
+    .await;
+
+    assert_eq!(actual.unwrap(), "generated");
+}
+
+#[rocket::async_test]
+async fn memory_cache_acquire_lock_is_exclusive_until_released() {
+    let cache = InMemoryCache::new(10);
+
+    assert!(cache.acquire_lock("c_lock_some_key", "first", 1000).await);
+    assert!(!cache.acquire_lock("c_lock_some_key", "second", 1000).await);
+
+    cache.release_lock("c_lock_some_key", "first").await;
+
+    assert!(cache.acquire_lock("c_lock_some_key", "second", 1000).await);
+}
+
+#[rocket::async_test]
+async fn memory_cache_release_lock_keeps_lock_of_other_holder() {
+    let cache = InMemoryCache::new(10);
+    assert!(cache.acquire_lock("c_lock_some_key", "holder", 1000).await);
+
+    cache
+        .release_lock("c_lock_some_key", "expired_holder")
+        .await;
+
+    assert!(!cache.acquire_lock("c_lock_some_key", "other", 1000).await);
+}
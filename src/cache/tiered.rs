@@ -0,0 +1,196 @@
+use crate::cache::memory::InMemoryCache;
+use crate::cache::redis::RedisCache;
+use crate::cache::Cache;
+use crate::config::redis_url;
+use rocket::futures::StreamExt;
+use rocket::tokio::time::sleep;
+use std::sync::Arc;
+use std::time::Duration;
+
+const INVALIDATION_CHANNEL: &'static str = "c_invalidations";
+const PATTERN_MESSAGE_PREFIX: &'static str = "pattern:";
+const KEY_MESSAGE_PREFIX: &'static str = "key:";
+const TAG_MESSAGE_PREFIX: &'static str = "tag:";
+
+/// Process-local (L1) cache in front of Redis.
+///
+/// L1 entries live for at most `local_timeout` seconds. Invalidations are applied locally and
+/// broadcast over Redis pub/sub, so that every gateway instance evicts the matching L1 entries.
+pub struct TieredCache {
+    local: Arc<InMemoryCache>,
+    remote: RedisCache,
+    local_timeout: usize,
+}
+
+impl TieredCache {
+    pub fn new(remote: RedisCache, max_entries: usize, local_timeout: usize) -> Self {
+        let local = Arc::new(InMemoryCache::new(max_entries).with_max_timeout(local_timeout));
+        subscribe_to_invalidations(local.clone());
+        TieredCache {
+            local,
+            remote,
+            local_timeout,
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl Cache for TieredCache {
+    async fn fetch(&self, id: &str) -> Option<String> {
+        if let Some(value) = self.local.fetch(id).await {
+            return Some(value);
+        }
+        let value = self.remote.fetch(id).await?;
+        self.local.create(id, &value, self.local_timeout).await;
+        Some(value)
+    }
+
+    async fn create(&self, id: &str, dest: &str, timeout: usize) {
+        self.remote.create(id, dest, timeout).await;
+        self.local.create(id, dest, timeout).await;
+    }
+
+    async fn insert_in_hash(&self, hash: &str, id: &str, dest: &str) -> bool {
+        let inserted = self.remote.insert_in_hash(hash, id, dest).await;
+        self.local.insert_in_hash(hash, id, dest).await;
+        inserted
+    }
+
+    async fn get_from_hash(&self, hash: &str, id: &str) -> Option<String> {
+        if let Some(value) = self.local.get_from_hash(hash, id).await {
+            return Some(value);
+        }
+        let value = self.remote.get_from_hash(hash, id).await?;
+        self.local.insert_in_hash(hash, id, &value).await;
+        Some(value)
+    }
+
+    async fn has_key(&self, id: &str) -> bool {
+        self.local.has_key(id).await || self.remote.has_key(id).await
+    }
+
+    async fn acquire_lock(&self, id: &str, token: &str, timeout_millis: usize) -> bool {
+        self.remote.acquire_lock(id, token, timeout_millis).await
+    }
+
+    // Locks are never copied to the local cache, so there is nothing to publish
+    async fn release_lock(&self, id: &str, token: &str) {
+        self.remote.release_lock(id, token).await
+    }
+
+    // Only the remote cache is shared between instances
+    async fn create_if_absent(&self, id: &str, dest: &str, timeout_millis: usize) -> Option<bool> {
+        self.remote.create_if_absent(id, dest, timeout_millis).await
+    }
+
+    async fn time_to_live(&self, id: &str) -> Option<usize> {
+        self.remote.time_to_live(id).await
+    }
+
+    async fn expire_entity(&self, id: &str, timeout: usize) {
+        self.remote.expire_entity(id, timeout).await;
+        self.local.expire_entity(id, timeout).await;
+    }
+
+    async fn invalidate_pattern(&self, pattern: &str) -> bool {
+        let deleted = self.remote.invalidate_pattern(pattern).await;
+        self.local.invalidate_pattern(pattern).await;
+        self.remote
+            .publish(
+                INVALIDATION_CHANNEL,
+                &format!("{}{}", PATTERN_MESSAGE_PREFIX, pattern),
+            )
+            .await;
+        deleted
+    }
+
+    async fn invalidate(&self, id: &str) {
+        self.remote.invalidate(id).await;
+        self.local.invalidate(id).await;
+        self.remote
+            .publish(
+                INVALIDATION_CHANNEL,
+                &format!("{}{}", KEY_MESSAGE_PREFIX, id),
+            )
+            .await;
+    }
+
+    async fn tag(&self, id: &str, tags: &[String], timeout: usize) {
+        self.remote.tag(id, tags, timeout).await;
+    }
+
+    // Tags are only tracked in Redis, L1 entries are few enough to be matched by pattern. Tags
+    // match keys in any case, as addresses show up both checksummed and lowercased.
+    async fn invalidate_tag(&self, tag: &str, pattern: &str) -> bool {
+        let deleted = self.remote.invalidate_tag(tag, pattern).await;
+        self.local.invalidate_pattern_ignoring_case(pattern);
+        self.remote
+            .publish(
+                INVALIDATION_CHANNEL,
+                &format!("{}{}", TAG_MESSAGE_PREFIX, pattern),
+            )
+            .await;
+        deleted
+    }
+
+    async fn tagged_keys(&self, tag: &str, pattern: &str) -> Vec<String> {
+        self.remote.tagged_keys(tag, pattern).await
+    }
+
+    async fn keys(&self, pattern: &str) -> Vec<String> {
+        self.remote.keys(pattern).await
+    }
+
+    async fn times_to_live(&self, ids: &[String]) -> Vec<Option<usize>> {
+        self.remote.times_to_live(ids).await
+    }
+
+    async fn memory_usage(&self, ids: &[String]) -> Vec<Option<usize>> {
+        self.remote.memory_usage(ids).await
+    }
+
+    async fn info(&self) -> Option<String> {
+        self.remote.info().await
+    }
+
+    async fn is_available(&self) -> bool {
+        self.remote.is_available().await
+    }
+}
+
+pub(super) async fn apply_invalidation(local: &InMemoryCache, message: &str) {
+    if let Some(pattern) = message.strip_prefix(PATTERN_MESSAGE_PREFIX) {
+        local.invalidate_pattern(pattern).await;
+    } else if let Some(pattern) = message.strip_prefix(TAG_MESSAGE_PREFIX) {
+        local.invalidate_pattern_ignoring_case(pattern);
+    } else if let Some(id) = message.strip_prefix(KEY_MESSAGE_PREFIX) {
+        local.invalidate(id).await;
+    } else {
+        log::warn!("Unknown cache invalidation message: {}", message);
+    }
+}
+
+fn subscribe_to_invalidations(local: Arc<InMemoryCache>) {
+    rocket::tokio::spawn(async move {
+        loop {
+            if let Err(error) = listen_for_invalidations(local.as_ref()).await {
+                log::error!("Cache invalidation subscription failed: {:?}", error);
+            }
+            sleep(Duration::from_secs(1)).await;
+        }
+    });
+}
+
+async fn listen_for_invalidations(local: &InMemoryCache) -> redis::RedisResult<()> {
+    let client = redis::Client::open(redis_url())?;
+    let mut pubsub = client.get_async_connection().await?.into_pubsub();
+    pubsub.subscribe(INVALIDATION_CHANNEL).await?;
+    // Invalidations could have been missed while (re)connecting
+    local.invalidate_pattern("*").await;
+    let mut messages = pubsub.on_message();
+    while let Some(message) = messages.next().await {
+        let payload: String = message.get_payload()?;
+        apply_invalidation(local, &payload).await;
+    }
+    Ok(())
+}
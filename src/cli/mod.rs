@@ -0,0 +1,93 @@
+pub mod warm_cache;
+
+#[cfg(test)]
+mod tests;
+
+pub const USAGE: &'static str = "\
+Usage:
+    safe-client-gateway
+        Starts the gateway
+
+    safe-client-gateway --print-config
+        Prints the configuration read from the environment and CONFIG_FILE, without secrets
+
+    safe-client-gateway warm-cache [--chain <id>] [--limit <n>] [--concurrency <n>] [<source>]
+        Fills the cache with the upstream responses of up to <n> Safes (default 300) of the chain
+        (default CHAIN_ID), requires the redis CACHE_BACKEND. <source> is either `analytics` (the
+        default) for the Safes with the most transactions, a file with one address per line or
+        `-` to read the addresses from stdin. <n> for --concurrency defaults to
+        CACHE_WARMER_CONCURRENCY";
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Serve,
+    PrintConfig,
+    WarmCache(WarmCacheArgs),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct WarmCacheArgs {
+    /// `None` for the default chain
+    pub chain_id: Option<u64>,
+    pub source: SafeSource,
+    pub limit: usize,
+    pub concurrency: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SafeSource {
+    Analytics,
+    File(String),
+    Stdin,
+}
+
+/// `args` without the name of the binary, `default_concurrency` is used for `warm-cache` without
+/// `--concurrency`
+pub fn parse_args(
+    args: impl Iterator<Item = String>,
+    default_concurrency: usize,
+) -> Result<Command, String> {
+    let args: Vec<String> = args.collect();
+    match args.split_first() {
+        None => Ok(Command::Serve),
+        Some((command, [])) if command == "--print-config" => Ok(Command::PrintConfig),
+        Some((command, options)) if command == "warm-cache" => {
+            parse_warm_cache(options, default_concurrency).map(Command::WarmCache)
+        }
+        Some((command, _)) => Err(format!("Unknown command: {}", command)),
+    }
+}
+
+fn parse_warm_cache(
+    options: &[String],
+    default_concurrency: usize,
+) -> Result<WarmCacheArgs, String> {
+    let mut args = WarmCacheArgs {
+        chain_id: None,
+        source: SafeSource::Analytics,
+        limit: 300,
+        concurrency: default_concurrency,
+    };
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        match option.as_str() {
+            "--chain" => args.chain_id = Some(positive_number(option, options.next())? as u64),
+            "--limit" => args.limit = positive_number(option, options.next())?,
+            "--concurrency" => args.concurrency = positive_number(option, options.next())?,
+            "analytics" => args.source = SafeSource::Analytics,
+            "-" => args.source = SafeSource::Stdin,
+            unknown if unknown.starts_with("--") => {
+                return Err(format!("Unknown option: {}", unknown))
+            }
+            path => args.source = SafeSource::File(path.to_string()),
+        }
+    }
+    Ok(args)
+}
+
+fn positive_number(option: &str, value: Option<&String>) -> Result<usize, String> {
+    value
+        .and_then(|value| value.parse().ok())
+        .filter(|value| *value > 0)
+        .ok_or(format!("{} expects a positive number", option))
+}
@@ -0,0 +1,2 @@
+mod parse_args;
+mod warm_cache;
@@ -0,0 +1,94 @@
+use crate::cli::{parse_args, Command, SafeSource, WarmCacheArgs};
+
+fn args(args: &[&str]) -> impl Iterator<Item = String> {
+    args.iter()
+        .map(|arg| arg.to_string())
+        .collect::<Vec<String>>()
+        .into_iter()
+}
+
+const DEFAULT_CONCURRENCY: usize = 5;
+
+#[test]
+fn parse_args_without_command() {
+    assert_eq!(
+        parse_args(args(&[]), DEFAULT_CONCURRENCY),
+        Ok(Command::Serve)
+    );
+}
+
+#[test]
+fn parse_args_print_config() {
+    assert_eq!(
+        parse_args(args(&["--print-config"]), DEFAULT_CONCURRENCY),
+        Ok(Command::PrintConfig)
+    );
+}
+
+#[test]
+fn parse_args_warm_cache_defaults() {
+    let expected = Command::WarmCache(WarmCacheArgs {
+        chain_id: None,
+        source: SafeSource::Analytics,
+        limit: 300,
+        concurrency: 3,
+    });
+
+    assert_eq!(parse_args(args(&["warm-cache"]), 3), Ok(expected));
+}
+
+#[test]
+fn parse_args_warm_cache_options() {
+    let expected = Command::WarmCache(WarmCacheArgs {
+        chain_id: Some(4),
+        source: SafeSource::File(String::from("safes.txt")),
+        limit: 10,
+        concurrency: 2,
+    });
+
+    let actual = parse_args(
+        args(&[
+            "warm-cache",
+            "--limit",
+            "10",
+            "safes.txt",
+            "--concurrency",
+            "2",
+            "--chain",
+            "4",
+        ]),
+        DEFAULT_CONCURRENCY,
+    );
+
+    assert_eq!(actual, Ok(expected));
+}
+
+#[test]
+fn parse_args_warm_cache_stdin() {
+    let actual = parse_args(args(&["warm-cache", "-"]), DEFAULT_CONCURRENCY);
+
+    match actual {
+        Ok(Command::WarmCache(args)) => assert_eq!(args.source, SafeSource::Stdin),
+        _ => panic!("Expected the warm-cache command"),
+    }
+}
+
+#[test]
+fn parse_args_invalid() {
+    assert_eq!(
+        parse_args(args(&["warm"]), DEFAULT_CONCURRENCY),
+        Err(String::from("Unknown command: warm"))
+    );
+    assert_eq!(
+        parse_args(args(&["warm-cache", "--limit", "0"]), DEFAULT_CONCURRENCY),
+        Err(String::from("--limit expects a positive number"))
+    );
+    assert_eq!(
+        parse_args(args(&["warm-cache", "--concurrency"]), DEFAULT_CONCURRENCY),
+        Err(String::from("--concurrency expects a positive number"))
+    );
+    assert_eq!(
+        parse_args(args(&["warm-cache", "--verbose"]), DEFAULT_CONCURRENCY),
+        Err(String::from("Unknown option: --verbose"))
+    );
+}
@@ -0,0 +1,72 @@
+use crate::cli::warm_cache::{check_cache_backend, parse_safes, report, EndpointReport};
+use crate::services::cache_warmer::WarmUp;
+use crate::utils::errors::ApiError;
+use std::time::Duration;
+
+#[test]
+fn parse_safes_skips_comments_and_empty_lines() {
+    let safes = "# top safes\n0x1230B3d59858296A31053C1b8562Ecf89A2f888b\n\n  0x65F8236309e5A99Ff0d129d04E486EBCE20DC7B0  \n0x81D0FF4fE216fB6aC98ED609086A92d94dbfE666\n";
+
+    let actual = parse_safes(safes, 2);
+
+    assert_eq!(
+        actual,
+        vec![
+            "0x1230B3d59858296A31053C1b8562Ecf89A2f888b",
+            "0x65F8236309e5A99Ff0d129d04E486EBCE20DC7B0"
+        ]
+    );
+}
+
+#[test]
+fn report_per_endpoint() {
+    let warm_ups = vec![
+        WarmUp {
+            endpoint: "safe info",
+            duration: Duration::from_millis(100),
+            success: true,
+        },
+        WarmUp {
+            endpoint: "balances",
+            duration: Duration::from_millis(50),
+            success: false,
+        },
+        WarmUp {
+            endpoint: "safe info",
+            duration: Duration::from_millis(300),
+            success: true,
+        },
+    ];
+
+    let actual = report(&warm_ups);
+
+    assert_eq!(
+        actual,
+        vec![
+            EndpointReport {
+                endpoint: "safe info",
+                requests: 2,
+                errors: 0,
+                average: Duration::from_millis(200),
+                max: Duration::from_millis(300),
+            },
+            EndpointReport {
+                endpoint: "balances",
+                requests: 1,
+                errors: 1,
+                average: Duration::from_millis(50),
+                max: Duration::from_millis(50),
+            },
+        ]
+    );
+}
+
+#[test]
+fn check_cache_backend_rejects_memory() {
+    let expected = ApiError::new_from_message(String::from(
+        "warm-cache requires the redis CACHE_BACKEND, the memory cache is dropped when the command exits",
+    ));
+
+    assert_eq!(check_cache_backend("memory"), Err(expected));
+    assert_eq!(check_cache_backend("redis"), Ok(()));
+}
@@ -0,0 +1,239 @@
+use crate::cache::{create_cache, ServiceCache};
+use crate::cli::{SafeSource, WarmCacheArgs};
+use crate::config::chains::{chain, default_chain, ChainConfig};
+use crate::config::{cache_backend, cache_warmer_host, default_request_timeout};
+use crate::models::backend::analytics::SafeTransactionCount;
+use crate::models::commons::Page;
+use crate::services::cache_warmer::{Endpoint, WarmUp};
+use crate::services::{balances, collectibles, safes, transactions_history, transactions_queued};
+use crate::utils::chains::chain_scoped_path;
+use crate::utils::context::Context;
+use crate::utils::errors::{ApiError, ApiResult};
+use rocket::futures::stream::{self, StreamExt};
+use std::future::Future;
+use std::io::Read;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, PartialEq)]
+pub struct EndpointReport {
+    pub endpoint: &'static str,
+    pub requests: usize,
+    pub errors: usize,
+    pub average: Duration,
+    pub max: Duration,
+}
+
+/// Fills the configured cache with the upstream responses the endpoints of the Safes of
+/// `args.source` are built from, and prints the timings per endpoint
+pub async fn run(client: reqwest::Client, args: WarmCacheArgs) -> ApiResult<()> {
+    check_cache_backend(&cache_backend())?;
+    let chain = match args.chain_id {
+        Some(chain_id) => chain(chain_id).ok_or_else(|| {
+            ApiError::new_from_message(format!("Chain {} is not configured", chain_id))
+        })?,
+        None => default_chain(),
+    };
+    let safes = load_safes(&client, chain, &args.source, args.limit).await?;
+    let cache = ServiceCache::new(create_cache());
+
+    let started = Instant::now();
+    let warm_ups: Vec<WarmUp> = stream::iter(&safes)
+        .map(|safe| warm_up(chain, safe, &cache, &client))
+        .buffer_unordered(args.concurrency)
+        .collect::<Vec<Vec<WarmUp>>>()
+        .await
+        .into_iter()
+        .flatten()
+        .collect();
+
+    println!(
+        "{:<24}{:>10}{:>10}{:>12}{:>12}",
+        "endpoint", "requests", "errors", "avg ms", "max ms"
+    );
+    for report in report(&warm_ups) {
+        println!(
+            "{:<24}{:>10}{:>10}{:>12}{:>12}",
+            report.endpoint,
+            report.requests,
+            report.errors,
+            report.average.as_millis(),
+            report.max.as_millis()
+        );
+    }
+    println!(
+        "Warmed {} Safes of chain {} in {:.1}s",
+        safes.len(),
+        chain.chain_id,
+        started.elapsed().as_secs_f64()
+    );
+    Ok(())
+}
+
+/// The warmed entries only outlive the command in a cache shared with the running service
+pub(super) fn check_cache_backend(backend: &str) -> ApiResult<()> {
+    if backend == "memory" {
+        return Err(ApiError::new_from_message(String::from(
+            "warm-cache requires the redis CACHE_BACKEND, the memory cache is dropped when the command exits",
+        )));
+    }
+    Ok(())
+}
+
+// The services send the same upstream requests as for client requests, so that those are cached
+// for the service. Responses are cached per client uri and are left to the service.
+async fn warm_up(
+    chain: &'static ChainConfig,
+    safe_address: &str,
+    cache: &ServiceCache,
+    client: &reqwest::Client,
+) -> Vec<WarmUp> {
+    let context = Context::new(
+        chain,
+        chain_scoped_path(chain.chain_id, &format!("/v1/safes/{}", safe_address)),
+        Some(cache_warmer_host()),
+        cache.clone(),
+        client.clone(),
+    );
+    let safe_address = safe_address.to_string();
+    vec![
+        timed(
+            Endpoint::SafeInfo,
+            &safe_address,
+            safes::get_safe_info_ex(&context, &safe_address),
+        )
+        .await,
+        timed(
+            Endpoint::QueuedTransactions,
+            &safe_address,
+            transactions_queued::get_queued_transactions(
+                &context,
+                &safe_address,
+                &None,
+                &None,
+                &None,
+            ),
+        )
+        .await,
+        timed(
+            Endpoint::TransactionHistory,
+            &safe_address,
+            transactions_history::get_history_transactions(&context, &safe_address, &None, &None),
+        )
+        .await,
+        timed(
+            Endpoint::Balances,
+            &safe_address,
+            balances::balances(&context, &safe_address, "USD", false, true),
+        )
+        .await,
+        timed(
+            Endpoint::Collectibles,
+            &safe_address,
+            collectibles::collectibles(&context, &safe_address, false, true),
+        )
+        .await,
+    ]
+}
+
+async fn timed<T>(
+    endpoint: Endpoint,
+    safe_address: &str,
+    request: impl Future<Output = ApiResult<T>>,
+) -> WarmUp {
+    let started = Instant::now();
+    let result = request.await;
+    if let Err(error) = &result {
+        log::warn!(
+            "Could not warm {} of {}: {:?}",
+            endpoint.name(),
+            safe_address,
+            error
+        );
+    }
+    WarmUp {
+        endpoint: endpoint.name(),
+        duration: started.elapsed(),
+        success: result.is_ok(),
+    }
+}
+
+async fn load_safes(
+    client: &reqwest::Client,
+    chain: &ChainConfig,
+    source: &SafeSource,
+    limit: usize,
+) -> ApiResult<Vec<String>> {
+    let safes = match source {
+        SafeSource::Analytics => {
+            let url = format!(
+                "{}/v1/analytics/multisig-transactions/by-safe/?limit={}",
+                chain.base_transaction_service_url(),
+                limit
+            );
+            let body = client
+                .get(&url)
+                .timeout(Duration::from_millis(default_request_timeout()))
+                .send()
+                .await?
+                .text()
+                .await?;
+            let page: Page<SafeTransactionCount> = serde_json::from_str(&body)?;
+            return Ok(page.results.into_iter().map(|count| count.safe).collect());
+        }
+        SafeSource::File(path) => std::fs::read_to_string(path).map_err(|error| {
+            ApiError::new_from_message(format!("Could not read {}: {}", path, error))
+        })?,
+        SafeSource::Stdin => {
+            let mut safes = String::new();
+            std::io::stdin()
+                .read_to_string(&mut safes)
+                .map_err(|error| {
+                    ApiError::new_from_message(format!("Could not read stdin: {}", error))
+                })?;
+            safes
+        }
+    };
+    Ok(parse_safes(&safes, limit))
+}
+
+/// One address per line, empty lines and lines starting with `#` are skipped
+pub(super) fn parse_safes(safes: &str, limit: usize) -> Vec<String> {
+    safes
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .take(limit)
+        .map(|line| line.to_string())
+        .collect()
+}
+
+/// Endpoints in the order they were warmed
+pub(super) fn report(warm_ups: &[WarmUp]) -> Vec<EndpointReport> {
+    let mut endpoints: Vec<&'static str> = vec![];
+    for warm_up in warm_ups {
+        if !endpoints.contains(&warm_up.endpoint) {
+            endpoints.push(warm_up.endpoint);
+        }
+    }
+    endpoints
+        .into_iter()
+        .map(|endpoint| {
+            let warm_ups: Vec<&WarmUp> = warm_ups
+                .iter()
+                .filter(|warm_up| warm_up.endpoint == endpoint)
+                .collect();
+            let total: Duration = warm_ups.iter().map(|warm_up| warm_up.duration).sum();
+            EndpointReport {
+                endpoint,
+                requests: warm_ups.len(),
+                errors: warm_ups.iter().filter(|warm_up| !warm_up.success).count(),
+                average: total / warm_ups.len() as u32,
+                max: warm_ups
+                    .iter()
+                    .map(|warm_up| warm_up.duration)
+                    .max()
+                    .unwrap_or_default(),
+            }
+        })
+        .collect()
+}
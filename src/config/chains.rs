@@ -0,0 +1,145 @@
+use crate::config::{
+    balances_cache_duration, chain_id, config, safe_info_cache_duration, token_info_cache_duration,
+};
+use lazy_static::lazy_static;
+use serde::Deserialize;
+
+lazy_static! {
+    // Read once, a change of the registry requires a restart
+    static ref CHAINS: Vec<ChainConfig> = load_chains();
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainConfig {
+    pub chain_id: u64,
+    pub chain_name: Option<String>,
+    /// Without the `/api` suffix, same as `TRANSACTION_SERVICE_URL`
+    pub transaction_service_url: String,
+    pub native_currency: NativeCurrency,
+    pub block_explorer_uri_template: Option<BlockExplorerUriTemplate>,
+    #[serde(default)]
+    pub rpc_uris: Vec<String>,
+    /// Names of the features clients can enable for the chain
+    #[serde(default)]
+    pub features: Vec<String>,
+    #[serde(default)]
+    pub cache_durations: ChainCacheDurations,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct NativeCurrency {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u64,
+}
+
+/// `{{address}}` and `{{txHash}}` are replaced by the clients
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockExplorerUriTemplate {
+    pub address: String,
+    pub tx_hash: String,
+}
+
+/// Seconds, the global duration is used for every value that is not set
+#[derive(Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainCacheDurations {
+    pub safe_info: Option<usize>,
+    pub token_info: Option<usize>,
+    pub balances: Option<usize>,
+}
+
+impl ChainCacheDurations {
+    /// Runtime setting each duration takes the place of, with the duration if it is set
+    pub fn settings(&self) -> [(&'static str, Option<usize>); 3] {
+        [
+            ("safe_info_cache_duration", self.safe_info),
+            ("token_info_cache_duration", self.token_info),
+            ("balances_request_cache_duration", self.balances),
+        ]
+    }
+}
+
+impl ChainConfig {
+    pub fn base_transaction_service_url(&self) -> String {
+        format!("{}/api", self.transaction_service_url.trim_end_matches('/'))
+    }
+
+    pub fn safe_info_cache_duration(&self) -> usize {
+        self.cache_durations
+            .safe_info
+            .unwrap_or_else(safe_info_cache_duration)
+    }
+
+    pub fn token_info_cache_duration(&self) -> usize {
+        self.cache_durations
+            .token_info
+            .unwrap_or_else(token_info_cache_duration)
+    }
+
+    pub fn balances_cache_duration(&self) -> usize {
+        self.cache_durations
+            .balances
+            .unwrap_or_else(balances_cache_duration)
+    }
+}
+
+pub fn chains() -> &'static [ChainConfig] {
+    &CHAINS
+}
+
+pub fn chain(chain_id: u64) -> Option<&'static ChainConfig> {
+    CHAINS.iter().find(|chain| chain.chain_id == chain_id)
+}
+
+/// Chain of the routes without a `/v1/chains/<chain_id>` prefix: `CHAIN_ID`, or the first chain of
+/// the registry if it doesn't contain `CHAIN_ID`
+pub fn default_chain() -> &'static ChainConfig {
+    chain(chain_id()).unwrap_or(&CHAINS[0])
+}
+
+pub fn parse_chains(json: &str) -> Result<Vec<ChainConfig>, String> {
+    let chains: Vec<ChainConfig> = serde_json::from_str(json).map_err(|error| error.to_string())?;
+    if chains.is_empty() {
+        return Err(String::from("At least one chain is required"));
+    }
+    for (index, chain) in chains.iter().enumerate() {
+        if chains[..index]
+            .iter()
+            .any(|other| other.chain_id == chain.chain_id)
+        {
+            return Err(format!("Chain {} is configured twice", chain.chain_id));
+        }
+    }
+    Ok(chains)
+}
+
+/// `CHAINS_CONFIG_PATH` if set, otherwise the single chain of `CHAIN_ID`,
+/// `TRANSACTION_SERVICE_URL` and `NATIVE_COIN_*`
+fn load_chains() -> Vec<ChainConfig> {
+    let config = config();
+    let path = &config.chains_config_path;
+    if !path.is_empty() {
+        // Validated with the rest of the configuration at launch
+        let json = std::fs::read_to_string(path)
+            .unwrap_or_else(|error| panic!("Could not read {}: {}", path, error));
+        parse_chains(&json).unwrap_or_else(|error| panic!("Invalid {}: {}", path, error))
+    } else {
+        vec![ChainConfig {
+            chain_id: config.chain_id,
+            chain_name: None,
+            transaction_service_url: config.transaction_service_url.to_string(),
+            native_currency: NativeCurrency {
+                name: config.native_coin_name.to_string(),
+                symbol: config.native_coin_symbol.to_string(),
+                decimals: config.native_coin_decimals,
+            },
+            block_explorer_uri_template: None,
+            rpc_uris: vec![],
+            features: vec![],
+            cache_durations: ChainCacheDurations::default(),
+        }]
+    }
+}
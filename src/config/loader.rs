@@ -0,0 +1,550 @@
+use crate::config::chains::parse_chains;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+/// Replaced when the configuration is printed
+const SECRETS: &[&str] = &[
+    "redis_url",
+    "exchange_api_key",
+    "webhook_token",
+    "webhook_secrets",
+];
+const REDACTED: &'static str = "<redacted>";
+
+/// Settings that are read whenever they are used, so that they can be changed while the gateway
+/// is running, see [Config::with_settings]
+pub const RUNTIME_SETTINGS: &[&str] = &[
+    "short_error_duration",
+    "long_error_duration",
+    "safe_info_cache_duration",
+    "address_info_cache_duration",
+    "token_info_cache_duration",
+    "exchange_api_cache_duration",
+    "request_cache_duration",
+    "about_cache_duration",
+    "chain_info_cache_duration",
+    "balances_request_cache_duration",
+    "safe_app_manifest_cache_duration",
+    "response_stale_duration",
+    "request_stale_duration",
+    "webhook_dedupe_duration",
+    "reqs_error_cache_duration",
+    "log_all_error_responses",
+    "feature_flag_nested_decoding",
+    "exchange_rates_max_age",
+];
+
+/// Every setting of the gateway. The fields are named after their environment variables, which
+/// are also the keys of the configuration file in lower case.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Config {
+    pub redis_url: String,
+    pub cache_backend: String,
+    pub exchange_api_base_url: String,
+    pub exchange_api_key: String,
+    pub exchange_rate_providers: Vec<String>,
+    pub exchange_rates_max_age: u64,
+    pub coinbase_exchange_api_url: String,
+    pub exchange_rates_file_path: String,
+    pub webhook_token: String,
+    pub legacy_webhook_token_enabled: bool,
+    pub webhook_secrets: Vec<String>,
+    pub webhook_signature_tolerance: u64,
+    pub webhook_queue_enabled: bool,
+    pub webhook_queue_consumer: String,
+    pub webhook_queue_batch_size: usize,
+    pub webhook_queue_max_attempts: u32,
+    pub webhook_dead_letter_max_length: usize,
+    pub cache_warmer_enabled: bool,
+    pub cache_warmer_host: String,
+    pub cache_warmer_concurrency: usize,
+    pub scheme: String,
+    pub transaction_service_url: String,
+    pub chains_config_path: String,
+    pub chain_id: u64,
+    pub native_coin_decimals: u64,
+    pub native_coin_symbol: String,
+    pub native_coin_name: String,
+    // TIME DURATION VALUES
+    pub indefinite_timeout: usize,
+    pub short_error_duration: usize,
+    pub long_error_duration: usize,
+    // FUNCTIONAL TIMEOUTS
+    pub safe_info_cache_duration: usize,
+    pub address_info_cache_duration: usize,
+    pub token_info_cache_duration: usize,
+    pub exchange_api_cache_duration: usize,
+    pub request_cache_duration: usize,
+    pub about_cache_duration: usize,
+    pub chain_info_cache_duration: usize,
+    pub balances_request_cache_duration: usize,
+    pub safe_app_manifest_cache_duration: usize,
+    pub response_stale_duration: usize,
+    pub request_stale_duration: usize,
+    pub redis_circuit_breaker_cooldown: u64,
+    pub l1_cache_duration: usize,
+    pub webhook_dedupe_duration: usize,
+    // REQUEST TIMEOUTS
+    pub internal_client_connect_timeout: u64,
+    pub safe_app_info_request_timeout: u64,
+    pub transaction_request_timeout: u64,
+    pub safe_info_request_timeout: u64,
+    pub token_info_request_timeout: u64,
+    pub balances_request_timeout: u64,
+    pub collectibles_request_timeout: u64,
+    pub default_request_timeout: u64,
+    pub redis_connection_timeout: u64,
+    pub single_flight_lock_timeout: usize,
+    pub single_flight_poll_interval: u64,
+    pub webhook_queue_block_timeout: u64,
+    pub webhook_queue_retry_delay: u64,
+    pub webhook_queue_claim_idle_time: u64,
+    pub cache_warmer_debounce: u64,
+    // ERRORS
+    pub reqs_error_cache_duration: usize,
+    pub log_all_error_responses: bool,
+    pub log_format: String,
+    // OTHERS
+    pub redis_pool_size: u32,
+    pub redis_scan_count: usize,
+    pub memory_cache_max_entries: usize,
+    pub l1_cache_enabled: bool,
+    pub cache_key_namespace: String,
+    pub cache_schema_version: usize,
+    pub tracing_exporter: String,
+    pub tracing_otlp_endpoint: String,
+    pub cache_compression_enabled: bool,
+    pub cache_compression_threshold: usize,
+    pub cache_compression_hash_field_threshold: usize,
+    pub feature_flag_nested_decoding: bool,
+    pub runtime_settings_path: String,
+    pub runtime_settings_refresh_interval: u64,
+}
+
+/// Every problem found in the configuration, so that they can be fixed at once
+#[derive(Debug, PartialEq)]
+pub struct ConfigErrors(pub Vec<String>);
+
+impl fmt::Display for ConfigErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid configuration:")?;
+        for error in &self.0 {
+            write!(f, "\n  - {}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl Config {
+    /// `env` takes precedence over the TOML `file`
+    pub fn load(
+        env: &dyn Fn(&str) -> Option<String>,
+        file: Option<&str>,
+    ) -> Result<Config, ConfigErrors> {
+        let (config, mut errors) = Config::read(env, file);
+        errors.append(&mut config.validate());
+        if errors.is_empty() {
+            Ok(config)
+        } else {
+            Err(ConfigErrors(errors))
+        }
+    }
+
+    /// Values that can't be parsed are replaced by their defaults and reported next to the config
+    pub(super) fn read(
+        env: &dyn Fn(&str) -> Option<String>,
+        file: Option<&str>,
+    ) -> (Config, Vec<String>) {
+        let mut source = Source::new(env, file);
+        let indefinite_timeout = source.number("INDEFINITE_TIMEOUT", 60 * 60);
+        let short_error_duration = source.number("SHORT_ERROR_DURATION", 60);
+        let config = Config {
+            redis_url: source.string("REDIS_URL", ""),
+            cache_backend: source.string("CACHE_BACKEND", "redis"),
+            exchange_api_base_url: source.string("EXCHANGE_API_BASE_URL", ""),
+            exchange_api_key: source.string("EXCHANGE_API_KEY", ""),
+            exchange_rate_providers: source.list("EXCHANGE_RATE_PROVIDERS", "exchangeratesapi"),
+            exchange_rates_max_age: source.number("EXCHANGE_RATES_MAX_AGE", 60 * 60 * 24 * 2),
+            coinbase_exchange_api_url: source.string(
+                "COINBASE_EXCHANGE_API_URL",
+                "https://api.coinbase.com/v2/exchange-rates?currency=USD",
+            ),
+            exchange_rates_file_path: source.string("EXCHANGE_RATES_FILE_PATH", ""),
+            webhook_token: source.string("WEBHOOK_TOKEN", ""),
+            legacy_webhook_token_enabled: source.boolean("LEGACY_WEBHOOK_TOKEN_ENABLED", true),
+            webhook_secrets: source.list("WEBHOOK_SECRETS", ""),
+            webhook_signature_tolerance: source.number("WEBHOOK_SIGNATURE_TOLERANCE", 300),
+            webhook_queue_enabled: source.boolean("WEBHOOK_QUEUE_ENABLED", true),
+            // Pending messages of a consumer are picked up again when it restarts, so it needs a
+            // stable name
+            webhook_queue_consumer: {
+                let hostname = env("HOSTNAME").unwrap_or(String::from("gateway"));
+                source.string("WEBHOOK_QUEUE_CONSUMER", &hostname)
+            },
+            webhook_queue_batch_size: source.number("WEBHOOK_QUEUE_BATCH_SIZE", 10),
+            webhook_queue_max_attempts: source.number("WEBHOOK_QUEUE_MAX_ATTEMPTS", 5),
+            webhook_dead_letter_max_length: source.number("WEBHOOK_DEAD_LETTER_MAX_LENGTH", 1000),
+            cache_warmer_enabled: source.boolean("CACHE_WARMER_ENABLED", false),
+            // Links to other pages are part of the cached responses, so they need the public host
+            cache_warmer_host: source.string("CACHE_WARMER_HOST", ""),
+            cache_warmer_concurrency: source.number("CACHE_WARMER_CONCURRENCY", 5),
+            scheme: source.string("SCHEME", "https"),
+            transaction_service_url: source.string("TRANSACTION_SERVICE_URL", ""),
+            chains_config_path: source.string("CHAINS_CONFIG_PATH", ""),
+            chain_id: source.number("CHAIN_ID", 1),
+            native_coin_decimals: source.number("NATIVE_COIN_DECIMALS", 18),
+            native_coin_symbol: source.string("NATIVE_COIN_SYMBOL", "ETH"),
+            native_coin_name: source.string("NATIVE_COIN_NAME", "Ether"),
+            indefinite_timeout,
+            short_error_duration,
+            long_error_duration: source.number("LONG_ERROR_DURATION", 60 * 15),
+            safe_info_cache_duration: source.number("SAFE_INFO_CACHE_DURATION", indefinite_timeout),
+            address_info_cache_duration: source
+                .number("ADDRESS_INFO_CACHE_DURATION", indefinite_timeout),
+            token_info_cache_duration: source.number("TOKEN_INFO_CACHE_DURATION", 60 * 60 * 24),
+            exchange_api_cache_duration: source.number("EXCHANGE_API_CACHE_DURATION", 60 * 60 * 12),
+            request_cache_duration: source.number("REQUEST_CACHE_DURATION", indefinite_timeout),
+            about_cache_duration: source.number("ABOUT_CACHE_DURATION", 60 * 15),
+            chain_info_cache_duration: source.number("CHAIN_INFO_CACHE_DURATION", 60 * 15),
+            balances_request_cache_duration: source.number("BALANCES_REQUEST_CACHE_DURATION", 60),
+            safe_app_manifest_cache_duration: source
+                .number("SAFE_APP_MANIFEST_CACHE_DURATION", indefinite_timeout),
+            response_stale_duration: source.number("RESPONSE_STALE_DURATION", 60 * 5),
+            request_stale_duration: source.number("REQUEST_STALE_DURATION", 60 * 30),
+            redis_circuit_breaker_cooldown: source.number("REDIS_CIRCUIT_BREAKER_COOLDOWN", 10),
+            l1_cache_duration: source.number("L1_CACHE_DURATION", 5),
+            webhook_dedupe_duration: source.number("WEBHOOK_DEDUPE_DURATION", 60),
+            internal_client_connect_timeout: source.number("INTERNAL_CLIENT_CONNECT_TIMEOUT", 1000),
+            safe_app_info_request_timeout: source.number("SAFE_APP_INFO_REQUEST_TIMEOUT", 3000),
+            transaction_request_timeout: source.number("TRANSACTION_REQUEST_TIMEOUT", 30000),
+            safe_info_request_timeout: source.number("SAFE_INFO_REQUEST_TIMEOUT", 10000),
+            token_info_request_timeout: source.number("TOKEN_INFO_REQUEST_TIMEOUT", 15000),
+            balances_request_timeout: source.number("BALANCES_REQUEST_TIMEOUT", 20000),
+            collectibles_request_timeout: source.number("COLLECTIBLES_REQUEST_TIMEOUT", 20000),
+            default_request_timeout: source.number("DEFAULT_REQUEST_TIMEOUT", 10000),
+            redis_connection_timeout: source.number("REDIS_CONNECTION_TIMEOUT", 1000),
+            single_flight_lock_timeout: source.number("SINGLE_FLIGHT_LOCK_TIMEOUT", 15000),
+            single_flight_poll_interval: source.number("SINGLE_FLIGHT_POLL_INTERVAL", 50),
+            webhook_queue_block_timeout: source.number("WEBHOOK_QUEUE_BLOCK_TIMEOUT", 5000),
+            webhook_queue_retry_delay: source.number("WEBHOOK_QUEUE_RETRY_DELAY", 1000),
+            webhook_queue_claim_idle_time: source.number("WEBHOOK_QUEUE_CLAIM_IDLE_TIME", 60000),
+            cache_warmer_debounce: source.number("CACHE_WARMER_DEBOUNCE", 2000),
+            reqs_error_cache_duration: source
+                .number("REQS_ERROR_CACHE_DURATION", short_error_duration),
+            log_all_error_responses: source.boolean("LOG_ALL_ERROR_RESPONSES", false),
+            log_format: source.string("LOG_FORMAT", "json"),
+            redis_pool_size: source.number("REDIS_POOL_SIZE", 15),
+            redis_scan_count: source.number("REDIS_SCAN_COUNT", 300),
+            memory_cache_max_entries: source.number("MEMORY_CACHE_MAX_ENTRIES", 10000),
+            l1_cache_enabled: source.boolean("L1_CACHE_ENABLED", false),
+            cache_key_namespace: source.string("CACHE_KEY_NAMESPACE", "scg"),
+            cache_schema_version: source.number("CACHE_SCHEMA_VERSION", 1),
+            tracing_exporter: source.string("TRACING_EXPORTER", "none"),
+            tracing_otlp_endpoint: source.string("TRACING_OTLP_ENDPOINT", "http://localhost:4317"),
+            cache_compression_enabled: source.boolean("CACHE_COMPRESSION_ENABLED", false),
+            cache_compression_threshold: source.number("CACHE_COMPRESSION_THRESHOLD", 8192),
+            cache_compression_hash_field_threshold: source
+                .number("CACHE_COMPRESSION_HASH_FIELD_THRESHOLD", 512),
+            feature_flag_nested_decoding: source.boolean("FEATURE_FLAG_NESTED_DECODING", true),
+            runtime_settings_path: source.string("RUNTIME_SETTINGS_PATH", ""),
+            runtime_settings_refresh_interval: source
+                .number("RUNTIME_SETTINGS_REFRESH_INTERVAL", 30),
+        };
+        (config, source.finish())
+    }
+
+    /// Settings that are required by other settings, or only accept some values
+    fn validate(&self) -> Vec<String> {
+        let mut errors = vec![];
+        let mut check = |valid: bool, error: String| {
+            if !valid {
+                errors.push(error)
+            }
+        };
+        check(
+            one_of(&self.cache_backend, &["redis", "memory"]),
+            format!(
+                "CACHE_BACKEND: expected `redis` or `memory`, got `{}`",
+                self.cache_backend
+            ),
+        );
+        check(
+            self.cache_backend != "redis" || !self.redis_url.is_empty(),
+            String::from("REDIS_URL: required for the `redis` cache backend"),
+        );
+        check(
+            !self.exchange_rate_providers.is_empty(),
+            String::from("EXCHANGE_RATE_PROVIDERS: at least one provider is required"),
+        );
+        for provider in &self.exchange_rate_providers {
+            check(
+                one_of(provider, &["exchangeratesapi", "coinbase", "static"]),
+                format!(
+                    "EXCHANGE_RATE_PROVIDERS: expected `exchangeratesapi`, `coinbase` or `static`, got `{}`",
+                    provider
+                ),
+            );
+        }
+        let uses = |provider: &str| self.exchange_rate_providers.iter().any(|it| it == provider);
+        check(
+            !uses("exchangeratesapi") || !self.exchange_api_base_url.is_empty(),
+            String::from("EXCHANGE_API_BASE_URL: required"),
+        );
+        check(
+            !uses("exchangeratesapi") || !self.exchange_api_key.is_empty(),
+            String::from("EXCHANGE_API_KEY: required"),
+        );
+        check(
+            !uses("static") || !self.exchange_rates_file_path.is_empty(),
+            String::from(
+                "EXCHANGE_RATES_FILE_PATH: required by the `static` exchange rate provider",
+            ),
+        );
+        check(
+            !self.legacy_webhook_token_enabled || !self.webhook_token.is_empty(),
+            String::from("WEBHOOK_TOKEN: required while LEGACY_WEBHOOK_TOKEN_ENABLED is set"),
+        );
+        check(
+            !self.cache_warmer_enabled || !self.cache_warmer_host.is_empty(),
+            String::from("CACHE_WARMER_HOST: required while CACHE_WARMER_ENABLED is set"),
+        );
+        check(
+            one_of(&self.scheme, &["http", "https"]),
+            format!("SCHEME: expected `http` or `https`, got `{}`", self.scheme),
+        );
+        check(
+            one_of(&self.log_format, &["json", "text"]),
+            format!(
+                "LOG_FORMAT: expected `json` or `text`, got `{}`",
+                self.log_format
+            ),
+        );
+        check(
+            one_of(&self.tracing_exporter, &["none", "stdout", "otlp"]),
+            format!(
+                "TRACING_EXPORTER: expected `none`, `stdout` or `otlp`, got `{}`",
+                self.tracing_exporter
+            ),
+        );
+        for (key, value) in &[
+            ("WEBHOOK_QUEUE_BATCH_SIZE", self.webhook_queue_batch_size),
+            (
+                "WEBHOOK_QUEUE_MAX_ATTEMPTS",
+                self.webhook_queue_max_attempts as usize,
+            ),
+            (
+                "WEBHOOK_QUEUE_CLAIM_IDLE_TIME",
+                self.webhook_queue_claim_idle_time as usize,
+            ),
+            ("CACHE_WARMER_CONCURRENCY", self.cache_warmer_concurrency),
+            ("REDIS_POOL_SIZE", self.redis_pool_size as usize),
+            (
+                "RUNTIME_SETTINGS_REFRESH_INTERVAL",
+                self.runtime_settings_refresh_interval as usize,
+            ),
+        ] {
+            check(*value > 0, format!("{}: must be greater than 0", key));
+        }
+        if self.chains_config_path.is_empty() {
+            check(
+                !self.transaction_service_url.is_empty(),
+                String::from("TRANSACTION_SERVICE_URL: required without CHAINS_CONFIG_PATH"),
+            );
+        } else {
+            let chains = std::fs::read_to_string(&self.chains_config_path)
+                .map_err(|error| error.to_string())
+                .and_then(|json| parse_chains(&json));
+            if let Err(error) = chains {
+                check(false, format!("CHAINS_CONFIG_PATH: {}", error));
+            }
+        }
+        errors
+    }
+
+    /// Copy of the configuration with the [RUNTIME_SETTINGS] in `settings` replaced, keys are case
+    /// insensitive and values are parsed like environment variables
+    pub fn with_settings(
+        &self,
+        settings: &BTreeMap<String, String>,
+    ) -> Result<Config, ConfigErrors> {
+        let mut table = match toml::Value::try_from(self) {
+            Ok(toml::Value::Table(table)) => table,
+            _ => panic!("Config is a flat table"),
+        };
+        let mut errors = vec![];
+        for (key, value) in settings {
+            let key = key.to_lowercase();
+            if RUNTIME_SETTINGS.contains(&key.as_str()) {
+                table.insert(key, toml::Value::from(value.as_str()));
+            } else {
+                errors.push(format!(
+                    "{}: can't be changed at runtime",
+                    key.to_uppercase()
+                ));
+            }
+        }
+        let file = toml::to_string(&table).expect("Config is a flat table");
+        let (config, mut read_errors) = Config::read(&|_| None, Some(&file));
+        errors.append(&mut read_errors);
+        if errors.is_empty() {
+            Ok(config)
+        } else {
+            Err(ConfigErrors(errors))
+        }
+    }
+
+    /// TOML that can be used as configuration file, with the secrets replaced
+    pub fn to_redacted_toml(&self) -> String {
+        let mut config = toml::Value::try_from(self).expect("Config is a flat table");
+        if let Some(table) = config.as_table_mut() {
+            for key in SECRETS {
+                let is_set = match table.get(*key) {
+                    Some(toml::Value::String(value)) => !value.is_empty(),
+                    Some(toml::Value::Array(values)) => !values.is_empty(),
+                    _ => false,
+                };
+                if is_set {
+                    table.insert(key.to_string(), toml::Value::from(REDACTED));
+                }
+            }
+        }
+        toml::to_string(&config).expect("Config is a flat table")
+    }
+}
+
+fn one_of(value: &str, values: &[&str]) -> bool {
+    values.contains(&value)
+}
+
+struct Source<'e> {
+    env: &'e dyn Fn(&str) -> Option<String>,
+    file: HashMap<String, String>,
+    read: HashSet<String>,
+    errors: Vec<String>,
+}
+
+impl<'e> Source<'e> {
+    fn new(env: &'e dyn Fn(&str) -> Option<String>, file: Option<&str>) -> Self {
+        let mut source = Source {
+            env,
+            file: HashMap::new(),
+            read: HashSet::new(),
+            errors: vec![],
+        };
+        if let Some(file) = file {
+            source.parse_file(file);
+        }
+        source
+    }
+
+    // Keys are case insensitive, lists are read like comma separated environment variables
+    fn parse_file(&mut self, file: &str) {
+        let table = match toml::from_str::<toml::value::Table>(file) {
+            Ok(table) => table,
+            Err(error) => {
+                self.errors.push(format!("CONFIG_FILE: {}", error));
+                return;
+            }
+        };
+        for (key, value) in table {
+            let key = key.to_uppercase();
+            let value = match value {
+                toml::Value::String(value) => value,
+                toml::Value::Array(values) => values
+                    .iter()
+                    .map(|value| match value {
+                        toml::Value::String(value) => value.to_string(),
+                        value => value.to_string(),
+                    })
+                    .collect::<Vec<String>>()
+                    .join(","),
+                toml::Value::Table(_) => {
+                    self.errors
+                        .push(format!("{}: expected a value, got a table", key));
+                    continue;
+                }
+                value => value.to_string(),
+            };
+            self.file.insert(key, value);
+        }
+    }
+
+    fn value(&mut self, key: &str) -> Option<String> {
+        self.read.insert(key.to_string());
+        (self.env)(key).or_else(|| self.file.get(key).cloned())
+    }
+
+    fn string(&mut self, key: &str, default: &str) -> String {
+        self.value(key).unwrap_or(default.to_string())
+    }
+
+    // Comma separated, like the arrays of the file
+    fn list(&mut self, key: &str, default: &str) -> Vec<String> {
+        self.string(key, default)
+            .split(',')
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+            .collect()
+    }
+
+    // The parse error of unsigned numbers doesn't tell that the sign is the problem
+    fn number<T>(&mut self, key: &str, default: T) -> T
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        self.parsed(key, default, |value, error| {
+            if value.starts_with('-') {
+                String::from("expected a positive number")
+            } else {
+                error.to_string()
+            }
+        })
+    }
+
+    fn boolean(&mut self, key: &str, default: bool) -> bool {
+        self.parsed(key, default, |_, _| {
+            String::from("expected `true` or `false`")
+        })
+    }
+
+    /// `describe` turns the value and its parse error into the message of the error
+    fn parsed<T: FromStr>(
+        &mut self,
+        key: &str,
+        default: T,
+        describe: impl FnOnce(&str, T::Err) -> String,
+    ) -> T {
+        match self.value(key) {
+            Some(value) => match value.trim().parse() {
+                Ok(parsed) => parsed,
+                Err(error) => {
+                    let message = describe(value.trim(), error);
+                    self.errors
+                        .push(format!("{}: {}, got `{}`", key, message, value));
+                    default
+                }
+            },
+            None => default,
+        }
+    }
+
+    /// Errors of the values read so far, and keys of the file that aren't settings
+    fn finish(mut self) -> Vec<String> {
+        let mut unknown: Vec<&String> = self
+            .file
+            .keys()
+            .filter(|key| !self.read.contains(*key))
+            .collect();
+        unknown.sort();
+        for key in unknown {
+            self.errors.push(format!(
+                "CONFIG_FILE: unknown setting `{}`",
+                key.to_lowercase()
+            ));
+        }
+        self.errors
+    }
+}
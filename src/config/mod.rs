@@ -1,165 +1,326 @@
+use lazy_static::lazy_static;
 use std::env;
+use std::sync::{Arc, RwLock};
+
+pub mod chains;
+mod loader;
+
+#[cfg(test)]
+mod tests;
+
+pub use loader::{Config, ConfigErrors, RUNTIME_SETTINGS};
+
+lazy_static! {
+    // Replaced by `init_config` at launch. Until then, e.g. in tests, the environment is read
+    // without validation and invalid values fall back to their defaults.
+    static ref CONFIG: RwLock<Arc<Config>> = RwLock::new(Arc::new(Config::read(&env_var, None).0));
+}
+
+/// The environment and the TOML file at `CONFIG_FILE`, environment variables take precedence
+pub fn load_config() -> Result<Config, ConfigErrors> {
+    let file = match env::var("CONFIG_FILE") {
+        Ok(path) => Some(std::fs::read_to_string(&path).map_err(|error| {
+            ConfigErrors(vec![format!(
+                "CONFIG_FILE: could not read {}: {}",
+                path, error
+            )])
+        })?),
+        Err(_) => None,
+    };
+    Config::load(&env_var, file.as_deref())
+}
+
+pub fn init_config(config: Arc<Config>) {
+    *CONFIG.write().unwrap() = config;
+}
+
+pub fn config() -> Arc<Config> {
+    CONFIG.read().unwrap().clone()
+}
+
+fn env_var(key: &str) -> Option<String> {
+    env::var(key).ok()
+}
 
 pub fn redis_url() -> String {
-    env::var("REDIS_URL").unwrap()
+    config().redis_url.to_string()
 }
 
-pub fn base_transaction_service_url() -> String {
-    format!("{}{}", env::var("TRANSACTION_SERVICE_URL").unwrap(), "/api")
+pub fn cache_backend() -> String {
+    config().cache_backend.to_string()
 }
 
 pub fn base_exchange_api_url() -> String {
     format!(
         "{}?access_key={}",
-        env::var("EXCHANGE_API_BASE_URL").unwrap(),
-        env::var("EXCHANGE_API_KEY").unwrap()
+        config().exchange_api_base_url,
+        config().exchange_api_key
     )
 }
 
+pub fn exchange_rate_providers() -> Vec<String> {
+    config().exchange_rate_providers.to_owned()
+}
+
+pub fn exchange_rates_max_age() -> u64 {
+    config().exchange_rates_max_age
+}
+
+pub fn coinbase_exchange_api_url() -> String {
+    config().coinbase_exchange_api_url.to_string()
+}
+
+pub fn exchange_rates_file_path() -> String {
+    config().exchange_rates_file_path.to_string()
+}
+
 pub fn webhook_token() -> String {
-    env::var("WEBHOOK_TOKEN").unwrap()
+    config().webhook_token.to_string()
 }
 
-pub fn scheme() -> String {
-    env::var("SCHEME").unwrap_or(String::from("https"))
+pub fn legacy_webhook_token_enabled() -> bool {
+    config().legacy_webhook_token_enabled
 }
 
-fn usize_with_default(key: &str, default: usize) -> usize {
-    match env::var(key) {
-        Ok(value) => value.parse().unwrap(),
-        Err(_) => default,
-    }
+pub fn webhook_secrets() -> Vec<String> {
+    config().webhook_secrets.to_owned()
 }
 
-fn u64_with_default(key: &str, default: u64) -> u64 {
-    match env::var(key) {
-        Ok(value) => value.parse().unwrap(),
-        Err(_) => default,
-    }
+pub fn webhook_signature_tolerance() -> u64 {
+    config().webhook_signature_tolerance
 }
 
-fn bool_with_default(key: &str, default: bool) -> bool {
-    match env::var(key) {
-        Ok(value) => value.parse().unwrap(),
-        Err(_) => default,
-    }
+pub fn webhook_queue_enabled() -> bool {
+    config().webhook_queue_enabled
 }
 
-// TIME DURATION VALUES
-fn indefinite_timeout() -> usize {
-    usize_with_default("INDEFINITE_TIMEOUT", 60 * 60)
+pub fn webhook_queue_consumer() -> String {
+    config().webhook_queue_consumer.to_string()
 }
 
+pub fn webhook_queue_batch_size() -> usize {
+    config().webhook_queue_batch_size
+}
+
+pub fn webhook_queue_max_attempts() -> u32 {
+    config().webhook_queue_max_attempts
+}
+
+pub fn webhook_dead_letter_max_length() -> usize {
+    config().webhook_dead_letter_max_length
+}
+
+pub fn cache_warmer_enabled() -> bool {
+    config().cache_warmer_enabled
+}
+
+pub fn cache_warmer_host() -> String {
+    config().cache_warmer_host.to_string()
+}
+
+pub fn cache_warmer_concurrency() -> usize {
+    config().cache_warmer_concurrency
+}
+
+pub fn scheme() -> String {
+    config().scheme.to_string()
+}
+
+// TIME DURATION VALUES
 pub fn short_error_duration() -> usize {
-    usize_with_default("SHORT_ERROR_DURATION", 60)
+    config().short_error_duration
 }
 
 pub fn long_error_duration() -> usize {
-    usize_with_default("LONG_ERROR_DURATION", 60 * 15)
+    config().long_error_duration
 }
 
 // FUNCTIONAL TIMEOUTS
 pub fn safe_info_cache_duration() -> usize {
-    usize_with_default("SAFE_INFO_CACHE_DURATION", indefinite_timeout())
+    config().safe_info_cache_duration
 }
 
 pub fn address_info_cache_duration() -> usize {
-    usize_with_default("ADDRESS_INFO_CACHE_DURATION", indefinite_timeout())
+    config().address_info_cache_duration
 }
 
 pub fn token_info_cache_duration() -> usize {
-    usize_with_default("TOKEN_INFO_CACHE_DURATION", 60 * 60 * 24)
+    config().token_info_cache_duration
 }
 
 pub fn exchange_api_cache_duration() -> usize {
-    usize_with_default("EXCHANGE_API_CACHE_DURATION", 60 * 60 * 12)
+    config().exchange_api_cache_duration
 }
 
 pub fn request_cache_duration() -> usize {
-    usize_with_default("REQUEST_CACHE_DURATION", indefinite_timeout())
+    config().request_cache_duration
 }
 
 pub fn about_cache_duration() -> usize {
-    usize_with_default("ABOUT_CACHE_DURATION", 60 * 15)
+    config().about_cache_duration
+}
+
+pub fn chain_info_cache_duration() -> usize {
+    config().chain_info_cache_duration
 }
 
 pub fn balances_cache_duration() -> usize {
-    usize_with_default("BALANCES_REQUEST_CACHE_DURATION", 60)
+    config().balances_request_cache_duration
 }
 
 pub fn safe_app_manifest_cache_duration() -> usize {
-    usize_with_default("SAFE_APP_MANIFEST_CACHE_DURATION", indefinite_timeout())
+    config().safe_app_manifest_cache_duration
+}
+
+pub fn response_stale_duration() -> usize {
+    config().response_stale_duration
+}
+
+pub fn request_stale_duration() -> usize {
+    config().request_stale_duration
+}
+
+pub fn redis_circuit_breaker_cooldown() -> u64 {
+    config().redis_circuit_breaker_cooldown
+}
+
+pub fn l1_cache_duration() -> usize {
+    config().l1_cache_duration
+}
+
+pub fn webhook_dedupe_duration() -> usize {
+    config().webhook_dedupe_duration
 }
 
 // REQUEST TIMEOUTS
 pub fn internal_client_connect_timeout() -> u64 {
-    u64_with_default("INTERNAL_CLIENT_CONNECT_TIMEOUT", 1000)
+    config().internal_client_connect_timeout
 }
 
 pub fn safe_app_info_request_timeout() -> u64 {
-    u64_with_default("SAFE_APP_INFO_REQUEST_TIMEOUT", 3000)
+    config().safe_app_info_request_timeout
 }
 
 pub fn transaction_request_timeout() -> u64 {
-    u64_with_default("TRANSACTION_REQUEST_TIMEOUT", 30000)
+    config().transaction_request_timeout
 }
 
 pub fn safe_info_request_timeout() -> u64 {
-    u64_with_default("SAFE_INFO_REQUEST_TIMEOUT", 10000)
+    config().safe_info_request_timeout
 }
 
 pub fn token_info_request_timeout() -> u64 {
-    u64_with_default("TOKEN_INFO_REQUEST_TIMEOUT", 15000)
+    config().token_info_request_timeout
 }
 
 pub fn balances_request_timeout() -> u64 {
-    u64_with_default("BALANCES_REQUEST_TIMEOUT", 20000)
+    config().balances_request_timeout
 }
 
 pub fn collectibles_request_timeout() -> u64 {
-    u64_with_default("COLLECTIBLES_REQUEST_TIMEOUT", 20000)
+    config().collectibles_request_timeout
 }
 
 pub fn default_request_timeout() -> u64 {
-    u64_with_default("DEFAULT_REQUEST_TIMEOUT", 10000)
+    config().default_request_timeout
+}
+
+pub fn redis_connection_timeout() -> u64 {
+    config().redis_connection_timeout
+}
+
+pub fn single_flight_lock_timeout() -> usize {
+    config().single_flight_lock_timeout
+}
+
+pub fn single_flight_poll_interval() -> u64 {
+    config().single_flight_poll_interval
+}
+
+pub fn webhook_queue_block_timeout() -> u64 {
+    config().webhook_queue_block_timeout
+}
+
+pub fn webhook_queue_retry_delay() -> u64 {
+    config().webhook_queue_retry_delay
+}
+
+pub fn webhook_queue_claim_idle_time() -> u64 {
+    config().webhook_queue_claim_idle_time
+}
+
+pub fn cache_warmer_debounce() -> u64 {
+    config().cache_warmer_debounce
 }
 
 // ERRORS
 pub fn request_error_cache_duration() -> usize {
-    usize_with_default("REQS_ERROR_CACHE_DURATION", short_error_duration())
+    config().reqs_error_cache_duration
 }
 
 pub fn log_all_error_responses() -> bool {
-    bool_with_default("LOG_ALL_ERROR_RESPONSES", false)
+    config().log_all_error_responses
+}
+
+pub fn log_format() -> String {
+    config().log_format.to_string()
 }
 
 // OTHERS
+pub fn redis_pool_size() -> u32 {
+    config().redis_pool_size
+}
+
 pub fn redis_scan_count() -> usize {
-    usize_with_default("REDIS_SCAN_COUNT", 300)
+    config().redis_scan_count
 }
 
-pub fn feature_flag_nested_decoding() -> bool {
-    bool_with_default("FEATURE_FLAG_NESTED_DECODING", true)
+pub fn memory_cache_max_entries() -> usize {
+    config().memory_cache_max_entries
 }
 
-pub fn build_number() -> Option<String> {
-    option_env!("BUILD_NUMBER").map(|it| it.to_string())
+pub fn l1_cache_enabled() -> bool {
+    config().l1_cache_enabled
 }
 
-pub fn native_coin_decimals() -> u64 {
-    u64_with_default("NATIVE_COIN_DECIMALS", 18)
+pub fn cache_key_namespace() -> String {
+    config().cache_key_namespace.to_string()
 }
 
-pub fn native_coin_symbol() -> String {
-    env::var("NATIVE_COIN_SYMBOL")
-        .unwrap_or(String::from("ETH"))
-        .to_string()
+pub fn cache_schema_version() -> usize {
+    config().cache_schema_version
 }
 
-pub fn native_coin_name() -> String {
-    env::var("NATIVE_COIN_NAME")
-        .unwrap_or(String::from("Ether"))
-        .to_string()
+pub fn tracing_exporter() -> String {
+    config().tracing_exporter.to_string()
+}
+
+pub fn tracing_otlp_endpoint() -> String {
+    config().tracing_otlp_endpoint.to_string()
+}
+
+pub fn cache_compression_enabled() -> bool {
+    config().cache_compression_enabled
+}
+
+pub fn cache_compression_threshold() -> usize {
+    config().cache_compression_threshold
+}
+
+pub fn cache_compression_hash_field_threshold() -> usize {
+    config().cache_compression_hash_field_threshold
+}
+
+pub fn feature_flag_nested_decoding() -> bool {
+    config().feature_flag_nested_decoding
+}
+
+pub fn runtime_settings_refresh_interval() -> u64 {
+    config().runtime_settings_refresh_interval
+}
+
+pub fn build_number() -> Option<String> {
+    option_env!("BUILD_NUMBER").map(|it| it.to_string())
 }
 
 pub fn version() -> String {
@@ -169,5 +330,5 @@ pub fn version() -> String {
 }
 
 pub fn chain_id() -> u64 {
-    u64_with_default("CHAIN_ID", 1)
+    config().chain_id
 }
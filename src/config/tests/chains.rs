@@ -0,0 +1,102 @@
+use crate::config::chains::{
+    parse_chains, BlockExplorerUriTemplate, ChainCacheDurations, ChainConfig, NativeCurrency,
+};
+
+#[test]
+fn parse_chains_with_and_without_cache_durations() {
+    let json = r#"[
+        {
+            "chainId": 1,
+            "transactionServiceUrl": "https://safe-transaction.gnosis.io",
+            "nativeCurrency": { "name": "Ether", "symbol": "ETH", "decimals": 18 }
+        },
+        {
+            "chainId": 100,
+            "chainName": "xDai",
+            "transactionServiceUrl": "https://safe-transaction.xdai.gnosis.io/",
+            "nativeCurrency": { "name": "xDai", "symbol": "XDAI", "decimals": 18 },
+            "blockExplorerUriTemplate": {
+                "address": "https://blockscout.com/xdai/mainnet/address/{{address}}",
+                "txHash": "https://blockscout.com/xdai/mainnet/tx/{{txHash}}"
+            },
+            "rpcUris": ["https://rpc.xdaichain.com"],
+            "features": ["ERC721", "SAFE_APPS"],
+            "cacheDurations": { "safeInfo": 30, "balances": 120 }
+        }
+    ]"#;
+    let expected = vec![
+        ChainConfig {
+            chain_id: 1,
+            chain_name: None,
+            transaction_service_url: String::from("https://safe-transaction.gnosis.io"),
+            native_currency: NativeCurrency {
+                name: String::from("Ether"),
+                symbol: String::from("ETH"),
+                decimals: 18,
+            },
+            block_explorer_uri_template: None,
+            rpc_uris: vec![],
+            features: vec![],
+            cache_durations: ChainCacheDurations::default(),
+        },
+        ChainConfig {
+            chain_id: 100,
+            chain_name: Some(String::from("xDai")),
+            transaction_service_url: String::from("https://safe-transaction.xdai.gnosis.io/"),
+            native_currency: NativeCurrency {
+                name: String::from("xDai"),
+                symbol: String::from("XDAI"),
+                decimals: 18,
+            },
+            block_explorer_uri_template: Some(BlockExplorerUriTemplate {
+                address: String::from("https://blockscout.com/xdai/mainnet/address/{{address}}"),
+                tx_hash: String::from("https://blockscout.com/xdai/mainnet/tx/{{txHash}}"),
+            }),
+            rpc_uris: vec![String::from("https://rpc.xdaichain.com")],
+            features: vec![String::from("ERC721"), String::from("SAFE_APPS")],
+            cache_durations: ChainCacheDurations {
+                safe_info: Some(30),
+                token_info: None,
+                balances: Some(120),
+            },
+        },
+    ];
+
+    let actual = parse_chains(json).unwrap();
+
+    assert_eq!(actual, expected);
+    assert_eq!(
+        actual[1].base_transaction_service_url(),
+        "https://safe-transaction.xdai.gnosis.io/api"
+    );
+    assert_eq!(actual[1].safe_info_cache_duration(), 30);
+}
+
+#[test]
+fn parse_chains_rejects_duplicates() {
+    let json = r#"[
+        {
+            "chainId": 4,
+            "transactionServiceUrl": "https://safe-transaction.rinkeby.gnosis.io",
+            "nativeCurrency": { "name": "Ether", "symbol": "ETH", "decimals": 18 }
+        },
+        {
+            "chainId": 4,
+            "transactionServiceUrl": "https://safe-transaction.rinkeby.staging.gnosisdev.com",
+            "nativeCurrency": { "name": "Ether", "symbol": "ETH", "decimals": 18 }
+        }
+    ]"#;
+
+    assert_eq!(
+        parse_chains(json),
+        Err(String::from("Chain 4 is configured twice"))
+    );
+}
+
+#[test]
+fn parse_chains_rejects_empty_registry() {
+    assert_eq!(
+        parse_chains("[]"),
+        Err(String::from("At least one chain is required"))
+    );
+}
@@ -0,0 +1,224 @@
+use crate::config::{Config, ConfigErrors};
+use std::collections::HashMap;
+
+fn env(values: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+    let values: HashMap<String, String> = values
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+    move |key| values.get(key).cloned()
+}
+
+fn required_env() -> Vec<(&'static str, &'static str)> {
+    vec![
+        ("REDIS_URL", "redis://:password@localhost:6379"),
+        (
+            "EXCHANGE_API_BASE_URL",
+            "http://api.exchangeratesapi.io/latest",
+        ),
+        ("EXCHANGE_API_KEY", "exchange_key"),
+        ("WEBHOOK_TOKEN", "webhook_token"),
+        (
+            "TRANSACTION_SERVICE_URL",
+            "https://safe-transaction.gnosis.io",
+        ),
+    ]
+}
+
+#[test]
+fn load_defaults() {
+    let config = Config::load(&env(&required_env()), None).unwrap();
+
+    assert_eq!(config.cache_backend, "redis");
+    assert_eq!(config.balances_request_timeout, 20000);
+    assert_eq!(config.safe_info_cache_duration, 60 * 60);
+    assert_eq!(config.reqs_error_cache_duration, 60);
+    assert_eq!(config.webhook_queue_max_attempts, 5);
+    assert_eq!(config.webhook_secrets, Vec::<String>::new());
+    assert!(config.legacy_webhook_token_enabled);
+}
+
+#[test]
+fn load_file_with_env_precedence() {
+    let mut values = required_env();
+    values.push(("BALANCES_REQUEST_TIMEOUT", "5000"));
+    let file = r#"
+        balances_request_timeout = 1000
+        INDEFINITE_TIMEOUT = 600
+        log_format = "text"
+        webhook_secrets = ["first", "second"]
+        l1_cache_enabled = true
+    "#;
+
+    let config = Config::load(&env(&values), Some(file)).unwrap();
+
+    assert_eq!(config.balances_request_timeout, 5000);
+    assert_eq!(config.indefinite_timeout, 600);
+    assert_eq!(config.safe_info_cache_duration, 600);
+    assert_eq!(config.log_format, "text");
+    assert_eq!(
+        config.webhook_secrets,
+        vec![String::from("first"), String::from("second")]
+    );
+    assert!(config.l1_cache_enabled);
+}
+
+#[test]
+fn load_reports_every_error() {
+    let values = [
+        ("BALANCES_REQUEST_TIMEOUT", "20s"),
+        ("L1_CACHE_ENABLED", "yes"),
+        ("CACHE_BACKEND", "memcached"),
+        (
+            "TRANSACTION_SERVICE_URL",
+            "https://safe-transaction.gnosis.io",
+        ),
+    ];
+    let file = "balance_request_timeout = 1000";
+    let expected = ConfigErrors(vec![
+        String::from("BALANCES_REQUEST_TIMEOUT: invalid digit found in string, got `20s`"),
+        String::from("L1_CACHE_ENABLED: expected `true` or `false`, got `yes`"),
+        String::from("CONFIG_FILE: unknown setting `balance_request_timeout`"),
+        String::from("CACHE_BACKEND: expected `redis` or `memory`, got `memcached`"),
+        String::from("EXCHANGE_API_BASE_URL: required"),
+        String::from("EXCHANGE_API_KEY: required"),
+        String::from("WEBHOOK_TOKEN: required while LEGACY_WEBHOOK_TOKEN_ENABLED is set"),
+    ]);
+
+    let actual = Config::load(&env(&values), Some(file));
+
+    assert_eq!(actual, Err(expected));
+}
+
+#[test]
+fn load_reports_why_numbers_are_invalid() {
+    let mut values = required_env();
+    values.push(("ABOUT_CACHE_DURATION", "-1"));
+    values.push(("BALANCES_REQUEST_TIMEOUT", "99999999999999999999"));
+    let expected = ConfigErrors(vec![
+        String::from("ABOUT_CACHE_DURATION: expected a positive number, got `-1`"),
+        String::from(
+            "BALANCES_REQUEST_TIMEOUT: number too large to fit in target type, got `99999999999999999999`",
+        ),
+    ]);
+
+    let actual = Config::load(&env(&values), None);
+
+    assert_eq!(actual, Err(expected));
+}
+
+#[test]
+fn load_requires_settings_of_enabled_features() {
+    let values = [
+        ("CACHE_BACKEND", "memory"),
+        (
+            "EXCHANGE_API_BASE_URL",
+            "http://api.exchangeratesapi.io/latest",
+        ),
+        ("EXCHANGE_API_KEY", "exchange_key"),
+        ("LEGACY_WEBHOOK_TOKEN_ENABLED", "false"),
+        ("CACHE_WARMER_ENABLED", "true"),
+        ("CACHE_WARMER_CONCURRENCY", "0"),
+    ];
+    let expected = ConfigErrors(vec![
+        String::from("CACHE_WARMER_HOST: required while CACHE_WARMER_ENABLED is set"),
+        String::from("CACHE_WARMER_CONCURRENCY: must be greater than 0"),
+        String::from("TRANSACTION_SERVICE_URL: required without CHAINS_CONFIG_PATH"),
+    ]);
+
+    let actual = Config::load(&env(&values), None);
+
+    assert_eq!(actual, Err(expected));
+}
+
+#[test]
+fn redacted_toml_is_a_config_file() {
+    let mut values = required_env();
+    values.push(("WEBHOOK_SECRETS", "first,second"));
+    let config = Config::load(&env(&values), None).unwrap();
+
+    let printed = config.to_redacted_toml();
+
+    assert!(!printed.contains("password"));
+    assert!(!printed.contains("exchange_key"));
+    assert!(!printed.contains("first"));
+    assert!(printed.contains("webhook_secrets = \"<redacted>\""));
+    let reloaded = Config::load(&env(&[]), Some(&printed)).unwrap();
+    assert_eq!(
+        reloaded.balances_request_timeout,
+        config.balances_request_timeout
+    );
+    assert_eq!(
+        reloaded.transaction_service_url,
+        config.transaction_service_url
+    );
+    assert_eq!(reloaded.redis_url, "<redacted>");
+}
+
+#[test]
+fn with_settings_replaces_runtime_settings() {
+    let config = Config::load(&env(&required_env()), None).unwrap();
+    let settings = vec![
+        ("FEATURE_FLAG_NESTED_DECODING", "false"),
+        ("balances_request_cache_duration", "5"),
+    ]
+    .into_iter()
+    .map(|(key, value)| (key.to_string(), value.to_string()))
+    .collect();
+
+    let actual = config.with_settings(&settings).unwrap();
+
+    assert!(!actual.feature_flag_nested_decoding);
+    assert_eq!(actual.balances_request_cache_duration, 5);
+    assert_eq!(
+        Config {
+            feature_flag_nested_decoding: true,
+            balances_request_cache_duration: 60,
+            ..actual
+        },
+        config
+    );
+}
+
+#[test]
+fn with_settings_rejects_other_settings_and_invalid_values() {
+    let config = Config::load(&env(&required_env()), None).unwrap();
+    let settings = vec![
+        ("redis_url", "redis://other"),
+        ("about_cache_duration", "-1"),
+    ]
+    .into_iter()
+    .map(|(key, value)| (key.to_string(), value.to_string()))
+    .collect();
+    let expected = ConfigErrors(vec![
+        String::from("REDIS_URL: can't be changed at runtime"),
+        String::from("ABOUT_CACHE_DURATION: expected a positive number, got `-1`"),
+    ]);
+
+    let actual = config.with_settings(&settings);
+
+    assert_eq!(actual, Err(expected));
+}
+
+#[test]
+fn load_validates_exchange_rate_providers() {
+    let values = [
+        ("EXCHANGE_RATE_PROVIDERS", "coinbase, static, fixer"),
+        ("LEGACY_WEBHOOK_TOKEN_ENABLED", "false"),
+        ("REDIS_URL", "redis://localhost:6379"),
+        (
+            "TRANSACTION_SERVICE_URL",
+            "https://safe-transaction.gnosis.io",
+        ),
+    ];
+    let expected = ConfigErrors(vec![
+        String::from(
+            "EXCHANGE_RATE_PROVIDERS: expected `exchangeratesapi`, `coinbase` or `static`, got `fixer`",
+        ),
+        String::from("EXCHANGE_RATES_FILE_PATH: required by the `static` exchange rate provider"),
+    ]);
+
+    let actual = Config::load(&env(&values), None);
+
+    assert_eq!(actual, Err(expected));
+}
@@ -19,6 +19,8 @@ pub mod macros;
 #[doc(hidden)]
 mod cache;
 #[doc(hidden)]
+mod cli;
+#[doc(hidden)]
 mod config;
 
 /// Models exposed by this service
@@ -33,6 +35,8 @@ mod models;
 mod monitoring;
 #[doc(hidden)]
 mod providers;
+#[doc(hidden)]
+mod queue;
 
 /// Collection of all endpoints all endpoints
 mod routes;
@@ -45,17 +49,39 @@ mod utils;
 mod json;
 
 use crate::routes::error_catchers;
-use cache::redis::create_pool;
+use cache::{create_cache, ServiceCache};
+use cli::Command;
+use config::Config;
 use dotenv::dotenv;
+use queue::{create_queue, ServiceQueue};
+use rocket::{Build, Rocket};
 use routes::active_routes;
+use std::sync::Arc;
 use std::time::Duration;
+use utils::chains::ChainScope;
 use utils::cors::CORS;
 
 #[doc(hidden)]
-#[launch]
-fn rocket() -> _ {
+#[rocket::main]
+async fn main() {
     dotenv().ok();
-    env_logger::init();
+
+    let config = match config::load_config() {
+        Ok(config) => Arc::new(config),
+        Err(errors) => {
+            eprintln!("{}", errors);
+            std::process::exit(2);
+        }
+    };
+    let command = match cli::parse_args(std::env::args().skip(1), config.cache_warmer_concurrency) {
+        Ok(command) => command,
+        Err(error) => {
+            eprintln!("{}\n\n{}", error, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    config::init_config(config.clone());
+    monitoring::logging::init_logger();
 
     let client = reqwest::Client::builder()
         .connect_timeout(Duration::from_millis(
@@ -64,11 +90,51 @@ fn rocket() -> _ {
         .build()
         .unwrap();
 
+    match command {
+        Command::Serve => {
+            monitoring::tracing::init_tracing();
+            let _ = rocket(config, client).launch().await;
+        }
+        Command::PrintConfig => print!("{}", config.to_redacted_toml()),
+        Command::WarmCache(args) => {
+            if let Err(error) = cli::warm_cache::run(client, args).await {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+fn rocket(config: Arc<Config>, client: reqwest::Client) -> Rocket<Build> {
+    let chain_ids: Vec<u64> = config::chains::chains()
+        .iter()
+        .map(|chain| chain.chain_id)
+        .collect();
+    log::info!(
+        "Serving chains {:?}, {} by default",
+        chain_ids,
+        config::chains::default_chain().chain_id
+    );
+    let cache = create_cache();
+    let queue = create_queue();
+    services::settings::spawn_settings_refresher(ServiceCache::new(cache.clone()), config.clone());
+    services::cache_warmer::start_cache_warmer(ServiceCache::new(cache.clone()), client.clone());
+    if config::webhook_queue_enabled() {
+        services::hooks::spawn_webhook_worker(
+            ServiceCache::new(cache.clone()),
+            ServiceQueue::new(queue.clone()),
+        );
+    }
+
     rocket::build()
-        .mount("/", active_routes())
+        .mount("/", monitoring::tracing::traced(active_routes()))
         .register("/", error_catchers())
-        .manage(create_pool())
+        .manage(cache)
+        .manage(queue)
         .manage(client)
+        .manage(config)
+        .attach(ChainScope())
+        .attach(monitoring::logging::RequestIdMonitor())
         .attach(monitoring::performance::PerformanceMonitor())
         .attach(CORS())
 }
@@ -0,0 +1,7 @@
+use serde::Deserialize;
+
+/// Entry of `/v1/analytics/multisig-transactions/by-safe/`, sorted by number of transactions
+#[derive(Deserialize, Debug)]
+pub struct SafeTransactionCount {
+    pub safe: String,
+}
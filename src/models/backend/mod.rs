@@ -1,4 +1,5 @@
 pub mod about;
+pub mod analytics;
 pub mod balances;
 pub mod transactions;
 pub mod transfers;
@@ -4,6 +4,9 @@ use serde::Deserialize;
 #[serde(tag = "type")]
 pub struct Payload {
     pub address: String,
+    /// Only sent by transaction services that notify about several chains
+    #[serde(default, rename = "chainId")]
+    pub chain_id: Option<String>,
     #[serde(flatten)]
     pub details: Option<PayloadDetails>,
 }
@@ -20,6 +23,32 @@ pub enum PayloadDetails {
     Unknown,
 }
 
+impl PayloadDetails {
+    /// Same as the `type` the payload was received with
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            PayloadDetails::NewConfirmation(_) => "NEW_CONFIRMATION",
+            PayloadDetails::ExecutedMultisigTransaction(_) => "EXECUTED_MULTISIG_TRANSACTION",
+            PayloadDetails::PendingMultisigTransaction(_) => "PENDING_MULTISIG_TRANSACTION",
+            PayloadDetails::IncomingEther(_) => "INCOMING_ETHER",
+            PayloadDetails::IncomingToken(_) => "INCOMING_TOKEN",
+            PayloadDetails::Unknown => "UNKNOWN",
+        }
+    }
+
+    /// Hash of the transaction the event is about
+    pub fn transaction_hash(&self) -> Option<&str> {
+        match self {
+            PayloadDetails::NewConfirmation(data) => Some(&data.safe_tx_hash),
+            PayloadDetails::ExecutedMultisigTransaction(data) => Some(&data.safe_tx_hash),
+            PayloadDetails::PendingMultisigTransaction(data) => Some(&data.safe_tx_hash),
+            PayloadDetails::IncomingEther(data) => Some(&data.tx_hash),
+            PayloadDetails::IncomingToken(data) => Some(&data.tx_hash),
+            PayloadDetails::Unknown => None,
+        }
+    }
+}
+
 #[derive(Deserialize, Debug, Hash)]
 #[serde(rename_all = "camelCase")]
 pub struct NewConfirmation {
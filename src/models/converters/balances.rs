@@ -1,12 +1,19 @@
-use crate::config::{native_coin_decimals, native_coin_name, native_coin_symbol};
+use crate::config::chains::NativeCurrency;
 use crate::models::backend::balances::Balance as BalanceDto;
 use crate::models::service::balances::Balance;
 use crate::providers::info::{TokenInfo, TokenType};
 
 impl BalanceDto {
-    pub fn to_balance(&self, usd_to_fiat: f64) -> Balance {
-        let fiat_conversion = self.fiat_conversion.parse::<f64>().unwrap_or(0.0) * usd_to_fiat;
-        let fiat_balance = self.fiat_balance.parse::<f64>().unwrap_or(0.0) * usd_to_fiat;
+    /// Fiat values are left out without a `usd_to_fiat` rate
+    pub fn to_balance(
+        &self,
+        usd_to_fiat: Option<f64>,
+        native_currency: &NativeCurrency,
+    ) -> Balance {
+        let to_fiat = |usd: &str| {
+            usd_to_fiat
+                .map(|usd_to_fiat| (usd.parse::<f64>().unwrap_or(0.0) * usd_to_fiat).to_string())
+        };
         let token_type = self
             .token_address
             .as_ref()
@@ -23,22 +30,22 @@ impl BalanceDto {
                     .token
                     .as_ref()
                     .map(|it| it.decimals)
-                    .unwrap_or(native_coin_decimals()),
+                    .unwrap_or(native_currency.decimals),
                 symbol: self
                     .token
                     .as_ref()
                     .map(|it| it.symbol.to_string())
-                    .unwrap_or(native_coin_symbol()),
+                    .unwrap_or(native_currency.symbol.to_string()),
                 name: self
                     .token
                     .as_ref()
                     .map(|it| it.name.to_string())
-                    .unwrap_or(native_coin_name()),
+                    .unwrap_or(native_currency.name.to_string()),
                 logo_uri: self.token.as_ref().map(|it| it.logo_uri.to_string()),
             },
             balance: self.balance.to_owned(),
-            fiat_balance: fiat_balance.to_string(),
-            fiat_conversion: fiat_conversion.to_string(),
+            fiat_balance: to_fiat(&self.fiat_balance),
+            fiat_conversion: to_fiat(&self.fiat_conversion),
         }
     }
 }
@@ -0,0 +1,25 @@
+use crate::config::chains::ChainConfig;
+use crate::models::service::chains::{BlockExplorerUriTemplate, ChainInfo, NativeCurrency};
+
+impl ChainConfig {
+    pub fn to_chain_info(&self) -> ChainInfo {
+        ChainInfo {
+            chain_id: self.chain_id,
+            chain_name: self.chain_name.to_owned(),
+            transaction_service: self.transaction_service_url.to_owned(),
+            native_currency: NativeCurrency {
+                name: self.native_currency.name.to_owned(),
+                symbol: self.native_currency.symbol.to_owned(),
+                decimals: self.native_currency.decimals,
+            },
+            block_explorer_uri_template: self.block_explorer_uri_template.as_ref().map(
+                |template| BlockExplorerUriTemplate {
+                    address: template.address.to_owned(),
+                    tx_hash: template.tx_hash.to_owned(),
+                },
+            ),
+            rpc_uris: self.rpc_uris.to_owned(),
+            features: self.features.to_owned(),
+        }
+    }
+}
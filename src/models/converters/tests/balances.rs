@@ -1,8 +1,17 @@
+use crate::config::chains::NativeCurrency;
 use crate::json::{BALANCE_COMPOUND_ETHER, BALANCE_ETHER};
 use crate::models::backend::balances::Balance as BalanceDto;
 use crate::models::service::balances::Balance;
 use crate::providers::info::{TokenInfo, TokenType};
 
+fn ether() -> NativeCurrency {
+    NativeCurrency {
+        name: "Ether".to_string(),
+        symbol: "ETH".to_string(),
+        decimals: 18,
+    }
+}
+
 #[test]
 fn ether_balance() {
     let balance_dto = serde_json::from_str::<BalanceDto>(BALANCE_ETHER).unwrap();
@@ -17,12 +26,41 @@ fn ether_balance() {
             logo_uri: None,
         },
         balance: "7457594371050000001".to_string(),
-        fiat_balance: "2523.7991".to_string(),
-        fiat_conversion: "338.42".to_string(),
+        fiat_balance: Some("2523.7991".to_string()),
+        fiat_conversion: Some("338.42".to_string()),
     };
 
     let usd_to_fiat = 1.0;
-    let actual = balance_dto.to_balance(usd_to_fiat);
+    let actual = balance_dto.to_balance(Some(usd_to_fiat), &ether());
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn native_balance_of_chain() {
+    let balance_dto = serde_json::from_str::<BalanceDto>(BALANCE_ETHER).unwrap();
+    let xdai = NativeCurrency {
+        name: "xDai".to_string(),
+        symbol: "XDAI".to_string(),
+        decimals: 18,
+    };
+
+    let expected = Balance {
+        token_info: TokenInfo {
+            token_type: TokenType::Ether,
+            address: "0x0000000000000000000000000000000000000000".to_string(),
+            decimals: 18,
+            symbol: "XDAI".to_string(),
+            name: "xDai".to_string(),
+            logo_uri: None,
+        },
+        balance: "7457594371050000001".to_string(),
+        fiat_balance: Some("2523.7991".to_string()),
+        fiat_conversion: Some("338.42".to_string()),
+    };
+
+    let usd_to_fiat = 1.0;
+    let actual = balance_dto.to_balance(Some(usd_to_fiat), &xdai);
 
     assert_eq!(actual, expected);
 }
@@ -41,12 +79,12 @@ fn erc20_token_balance_usd_balance() {
             logo_uri: Some("https://gnosis-safe-token-logos.s3.amazonaws.com/0xd6801a1DfFCd0a410336Ef88DeF4320D6DF1883e.png".to_string()),
         },
         balance: "5002".to_string(),
-        fiat_balance: "0.0014".to_string(),
-        fiat_conversion: "28.5462".to_string(),
+        fiat_balance: Some("0.0014".to_string()),
+        fiat_conversion: Some("28.5462".to_string()),
     };
 
     let usd_to_fiat = 1.0;
-    let actual = balance_dto.to_balance(usd_to_fiat);
+    let actual = balance_dto.to_balance(Some(usd_to_fiat), &ether());
 
     assert_eq!(actual, expected);
 }
@@ -65,12 +103,23 @@ fn erc20_token_balance_fiat_is_twice_usd() {
             logo_uri: Some("https://gnosis-safe-token-logos.s3.amazonaws.com/0xd6801a1DfFCd0a410336Ef88DeF4320D6DF1883e.png".to_string()),
         },
         balance: "5002".to_string(),
-        fiat_balance: "0.0028".to_string(),
-        fiat_conversion: "57.0924".to_string(),
+        fiat_balance: Some("0.0028".to_string()),
+        fiat_conversion: Some("57.0924".to_string()),
     };
 
     let usd_to_fiat = 2.0;
-    let actual = balance_dto.to_balance(usd_to_fiat);
+    let actual = balance_dto.to_balance(Some(usd_to_fiat), &ether());
 
     assert_eq!(actual, expected);
 }
+
+#[test]
+fn balance_without_exchange_rate() {
+    let balance_dto = serde_json::from_str::<BalanceDto>(BALANCE_ETHER).unwrap();
+
+    let actual = balance_dto.to_balance(None, &ether());
+
+    assert_eq!(actual.balance, "7457594371050000001");
+    assert_eq!(actual.fiat_balance, None);
+    assert_eq!(actual.fiat_conversion, None);
+}
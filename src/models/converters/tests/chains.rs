@@ -0,0 +1,75 @@
+use crate::config::chains::{
+    BlockExplorerUriTemplate, ChainCacheDurations, ChainConfig, NativeCurrency,
+};
+use crate::models::service::chains::{
+    BlockExplorerUriTemplate as ServiceBlockExplorerUriTemplate, ChainInfo,
+    NativeCurrency as ServiceNativeCurrency,
+};
+
+#[test]
+fn chain_config_to_chain_info() {
+    let chain_config = ChainConfig {
+        chain_id: 4,
+        chain_name: Some(String::from("Rinkeby")),
+        transaction_service_url: String::from("https://safe-transaction.rinkeby.gnosis.io"),
+        native_currency: NativeCurrency {
+            name: String::from("Ether"),
+            symbol: String::from("ETH"),
+            decimals: 18,
+        },
+        block_explorer_uri_template: Some(BlockExplorerUriTemplate {
+            address: String::from("https://rinkeby.etherscan.io/address/{{address}}"),
+            tx_hash: String::from("https://rinkeby.etherscan.io/tx/{{txHash}}"),
+        }),
+        rpc_uris: vec![String::from("https://rinkeby.infura.io/v3/")],
+        features: vec![String::from("ERC721")],
+        cache_durations: ChainCacheDurations {
+            safe_info: Some(10),
+            token_info: None,
+            balances: None,
+        },
+    };
+    let expected = ChainInfo {
+        chain_id: 4,
+        chain_name: Some(String::from("Rinkeby")),
+        transaction_service: String::from("https://safe-transaction.rinkeby.gnosis.io"),
+        native_currency: ServiceNativeCurrency {
+            name: String::from("Ether"),
+            symbol: String::from("ETH"),
+            decimals: 18,
+        },
+        block_explorer_uri_template: Some(ServiceBlockExplorerUriTemplate {
+            address: String::from("https://rinkeby.etherscan.io/address/{{address}}"),
+            tx_hash: String::from("https://rinkeby.etherscan.io/tx/{{txHash}}"),
+        }),
+        rpc_uris: vec![String::from("https://rinkeby.infura.io/v3/")],
+        features: vec![String::from("ERC721")],
+    };
+
+    let actual = chain_config.to_chain_info();
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn chain_info_without_name_and_block_explorer() {
+    let chain_config = ChainConfig {
+        chain_id: 4,
+        chain_name: None,
+        transaction_service_url: String::from("https://safe-transaction.rinkeby.gnosis.io"),
+        native_currency: NativeCurrency {
+            name: String::from("Ether"),
+            symbol: String::from("ETH"),
+            decimals: 18,
+        },
+        block_explorer_uri_template: None,
+        rpc_uris: vec![],
+        features: vec![],
+        cache_durations: ChainCacheDurations::default(),
+    };
+
+    let actual = serde_json::to_value(chain_config.to_chain_info()).unwrap();
+
+    assert_eq!(actual["chainName"], serde_json::Value::Null);
+    assert_eq!(actual["blockExplorerUriTemplate"], serde_json::Value::Null);
+}
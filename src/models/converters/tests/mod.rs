@@ -1,4 +1,5 @@
 pub(super) mod balances;
+mod chains;
 mod data_decoded;
 mod get_address_info;
 mod get_transfer_direction;
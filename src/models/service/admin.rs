@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// CacheKey
+///
+/// <details>
+/// <summary>Sample</summary>
+///
+/// ```json
+/// {
+///   "key": "scg:1:v1:c_resp_/v1/safes/0x1230B3d59858296A31053C1b8562Ecf89A2f888b",
+///   "ttl": 3540,
+///   "size": 1024
+/// }
+/// ```
+/// </details>
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheKey {
+    pub key: String,
+    /// Seconds until the key expires, `null` for keys without a timeout
+    pub ttl: Option<usize>,
+    /// Bytes used by the key, as reported by the cache backend
+    pub size: Option<usize>,
+}
+
+/// CachedEntry
+///
+/// Metadata is only available for upstream requests stored by the current entry format
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CachedEntry {
+    pub key: String,
+    pub ttl: Option<usize>,
+    pub version: Option<u8>,
+    pub status: Option<u16>,
+    pub created_at: Option<i64>,
+    pub url: Option<String>,
+    pub content_hash: Option<String>,
+    pub body: String,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheFamilyStats {
+    pub family: String,
+    pub keys: usize,
+    /// Keys whose memory usage was looked up
+    pub sampled_keys: usize,
+    /// Bytes used by all keys of the family, extrapolated from the sampled keys
+    pub estimated_memory: usize,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FlushReport {
+    pub dry_run: bool,
+    /// Keys matching the pattern, these are only deleted if `dryRun` is `false`
+    pub keys: Vec<String>,
+}
+
+/// DeadLetter
+///
+/// Webhook that could not be processed, see `WEBHOOK_QUEUE_MAX_ATTEMPTS`
+///
+/// <details>
+/// <summary>Sample</summary>
+///
+/// ```json
+/// {
+///   "id": "1620000000000-0",
+///   "body": "{\"address\":\"0x1230B3d59858296A31053C1b8562Ecf89A2f888b\",\"type\":\"NEW_CONFIRMATION\"}",
+///   "attempts": 5,
+///   "reason": "Cache unavailable",
+///   "failedAt": 1620000000
+/// }
+/// ```
+/// </details>
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadLetter {
+    pub id: String,
+    /// Payload as received by `/v1/hook/update`
+    pub body: String,
+    pub attempts: u32,
+    pub reason: String,
+    pub failed_at: i64,
+}
+
+/// RuntimeSettings
+///
+/// <details>
+/// <summary>Sample</summary>
+///
+/// ```json
+/// {
+///   "source": "cache",
+///   "overrides": {
+///     "feature_flag_nested_decoding": "false"
+///   },
+///   "values": {
+///     "balances_request_cache_duration": 60,
+///     "feature_flag_nested_decoding": false
+///   },
+///   "chainValues": {
+///     "balances_request_cache_duration": {
+///       "100": 120
+///     }
+///   }
+/// }
+/// ```
+/// </details>
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeSettings {
+    /// `cache`, or the `RUNTIME_SETTINGS_PATH` the settings are read from
+    pub source: String,
+    /// Settings that replace the configuration of the launch
+    pub overrides: BTreeMap<String, String>,
+    /// Current value of every setting that can be changed at runtime
+    pub values: BTreeMap<String, serde_json::Value>,
+    /// Cache durations by chain id that the `cacheDurations` of a chain set instead of `values`
+    pub chain_values: BTreeMap<String, BTreeMap<String, usize>>,
+}
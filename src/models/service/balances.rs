@@ -6,15 +6,19 @@ use serde::Serialize;
 pub struct Balance {
     pub token_info: TokenInfo,
     pub balance: String,
-    pub fiat_balance: String,
-    pub fiat_conversion: String,
+    /// `null` without an exchange rate to the fiat
+    pub fiat_balance: Option<String>,
+    /// `null` without an exchange rate to the fiat
+    pub fiat_conversion: Option<String>,
 }
 
 #[derive(Serialize, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Balances {
-    /// Aggregated fiat balance
-    pub fiat_total: String,
+    /// Aggregated fiat balance, `null` without an exchange rate to the fiat
+    pub fiat_total: Option<String>,
+    /// `false` if no exchange rate to the fiat was available, the fiat values are `null` then
+    pub fiat_available: bool,
     /// Individual [Balance] entries for each ERC20 in the Safe
     pub items: Vec<Balance>,
 }
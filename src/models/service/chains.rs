@@ -0,0 +1,60 @@
+use serde::Serialize;
+
+/// ChainInfo
+///
+/// <details>
+/// <summary>Sample</summary>
+///
+/// ```json
+/// {
+///   "chainId": 4,
+///   "chainName": "Rinkeby",
+///   "transactionService": "https://safe-transaction.rinkeby.gnosis.io",
+///   "nativeCurrency": {
+///     "name": "Ether",
+///     "symbol": "ETH",
+///     "decimals": 18
+///   },
+///   "blockExplorerUriTemplate": {
+///     "address": "https://rinkeby.etherscan.io/address/{{address}}",
+///     "txHash": "https://rinkeby.etherscan.io/tx/{{txHash}}"
+///   },
+///   "rpcUris": [
+///     "https://rinkeby.infura.io/v3/"
+///   ],
+///   "features": [
+///     "ERC721",
+///     "SAFE_APPS"
+///   ]
+/// }
+/// ```
+/// </details>
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainInfo {
+    pub chain_id: u64,
+    pub chain_name: Option<String>,
+    /// Without the `/api` suffix
+    pub transaction_service: String,
+    pub native_currency: NativeCurrency,
+    /// `{{address}}` and `{{txHash}}` have to be replaced with the values to link to, `null` if
+    /// the chain has no block explorer configured
+    pub block_explorer_uri_template: Option<BlockExplorerUriTemplate>,
+    pub rpc_uris: Vec<String>,
+    pub features: Vec<String>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct NativeCurrency {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u64,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockExplorerUriTemplate {
+    pub address: String,
+    pub tx_hash: String,
+}
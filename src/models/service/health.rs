@@ -0,0 +1,26 @@
+use serde::Serialize;
+
+/// Health
+///
+/// <details>
+/// <summary>Sample</summary>
+///
+/// ```json
+/// {
+///   "cache": "AVAILABLE"
+/// }
+/// ```
+/// </details>
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Health {
+    /// `BYPASSED` while the cache backend is unreachable and requests go directly upstream
+    pub cache: CacheStatus,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CacheStatus {
+    Available,
+    Bypassed,
+}
@@ -1,4 +1,7 @@
 pub mod about;
+pub mod admin;
 pub mod balances;
+pub mod chains;
+pub mod health;
 pub mod safes;
 pub mod transactions;
@@ -0,0 +1,92 @@
+use crate::monitoring::logging::log_fields;
+use crate::monitoring::metrics::{
+    add, endpoint_host, endpoint_template, increment, observe, observe_value, set, RATIO_BUCKETS,
+};
+use serde_json::Value;
+use std::time::Duration;
+
+pub fn report_cache_bypass(active: bool) {
+    let _fields = log_fields(&[("cacheBypassActive", Value::from(active))]);
+    if active {
+        log::warn!("Bypassing the cache, the backend is unavailable");
+    } else {
+        log::info!("Stopped bypassing the cache, the backend is available again");
+    }
+    set(
+        "scg_cache_bypass_active",
+        &[],
+        if active { 1.0 } else { 0.0 },
+    );
+}
+
+/// Cache operations that were skipped while the circuit of the backend was open
+pub fn report_cache_bypassed_operation() {
+    increment("scg_cache_bypassed_operations_total", &[]);
+}
+
+/// Ratios of 1 and above are values that didn't get smaller and were stored uncompressed
+pub fn report_compression(original_size: usize, compressed_size: usize) {
+    observe_value(
+        "scg_cache_compression_ratio",
+        &[],
+        RATIO_BUCKETS,
+        compressed_size as f64 / original_size as f64,
+    );
+    add(
+        "scg_cache_compression_original_bytes_total",
+        &[],
+        original_size as u64,
+    );
+    add(
+        "scg_cache_compression_compressed_bytes_total",
+        &[],
+        compressed_size as u64,
+    );
+}
+
+/// `outcome` is one of `hit` or `miss`, labelled by the template of the cached route
+pub fn report_response_cache(key: &str, outcome: &str) {
+    increment(
+        "scg_cache_responses_total",
+        &[("route", &endpoint_template(key)), ("outcome", outcome)],
+    );
+}
+
+/// `outcome` is one of `hit`, `error_hit` or `miss`, labelled by the upstream endpoint family
+pub fn report_request_cache(url: &str, outcome: &str) {
+    increment(
+        "scg_cache_requests_total",
+        &[("endpoint", &endpoint_template(url)), ("outcome", outcome)],
+    );
+}
+
+pub fn report_generator_duration(key: &str, duration: Duration) {
+    observe(
+        "scg_cache_generator_duration_seconds",
+        &[("route", &endpoint_template(key))],
+        duration,
+    );
+}
+
+/// `status` is the status code of the response, or `error` if none was received
+pub fn report_upstream_duration(url: &str, status: &str, duration: Duration) {
+    observe(
+        "scg_upstream_duration_seconds",
+        &[
+            ("host", &endpoint_host(url)),
+            ("endpoint", &endpoint_template(url)),
+            ("status", status),
+        ],
+        duration,
+    );
+}
+
+pub fn report_redis_pool(connections: u32, idle_connections: u32, max_size: u32) {
+    set("scg_redis_pool_connections", &[], connections as f64);
+    set(
+        "scg_redis_pool_idle_connections",
+        &[],
+        idle_connections as f64,
+    );
+    set("scg_redis_pool_max_connections", &[], max_size as f64);
+}
@@ -0,0 +1,138 @@
+use crate::config::log_format;
+use chrono::Utc;
+use env_logger::fmt::Formatter;
+use log::Record;
+use opentelemetry::{Context, ContextGuard};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::Header;
+use rocket::{Data, Request, Response};
+use serde_json::{Map, Value};
+use std::io::Write;
+use uuid::Uuid;
+
+pub const REQUEST_ID_HEADER: &'static str = "X-Request-Id";
+const MAX_REQUEST_ID_LENGTH: usize = 64;
+
+/// Id of the request being handled, taken from the `X-Request-Id` header if the caller sent a
+/// valid one
+#[derive(Clone, Debug)]
+pub struct RequestId(pub String);
+
+// Fields of the lines logged within a `log_fields` scope, next to the message
+#[derive(Clone, Debug)]
+struct LogFields(Map<String, Value>);
+
+/// Writes one JSON object per line, unless `LOG_FORMAT` is `text`
+pub fn init_logger() {
+    let mut builder = env_logger::Builder::from_default_env();
+    if log_format() != "text" {
+        builder.format(format_json);
+    } else {
+        builder.format(format_text);
+    }
+    builder.init();
+}
+
+fn format_text(buf: &mut Formatter, record: &Record) -> std::io::Result<()> {
+    let mut line = format!(
+        "[{} {} {}] {}",
+        Utc::now().to_rfc3339(),
+        record.level(),
+        record.target(),
+        record.args()
+    );
+    if let Some(fields) = Context::current().get::<LogFields>() {
+        for (key, value) in &fields.0 {
+            line.push_str(&format!(" {}={}", key, value));
+        }
+    }
+    writeln!(buf, "{}", line)
+}
+
+fn format_json(buf: &mut Formatter, record: &Record) -> std::io::Result<()> {
+    writeln!(buf, "{}", json_line(record))
+}
+
+pub(super) fn json_line(record: &Record) -> Value {
+    let mut line = serde_json::json!({
+        "timestamp": Utc::now().to_rfc3339(),
+        "level": record.level().as_str(),
+        "target": record.target(),
+        "requestId": current_request_id(),
+        "message": record.args().to_string(),
+    });
+    if let Some(fields) = Context::current().get::<LogFields>() {
+        for (key, value) in &fields.0 {
+            line[key] = value.clone();
+        }
+    }
+    line
+}
+
+/// Lines logged while the guard is alive carry `fields`, so that they can be queried without
+/// parsing the message
+pub fn log_fields(fields: &[(&str, Value)]) -> ContextGuard {
+    let mut all = Context::current()
+        .get::<LogFields>()
+        .map(|fields| fields.0.clone())
+        .unwrap_or_default();
+    for (key, value) in fields {
+        all.insert(key.to_string(), value.clone());
+    }
+    Context::current_with_value(LogFields(all)).attach()
+}
+
+pub fn current_request_id() -> Option<String> {
+    Context::current()
+        .get::<RequestId>()
+        .map(|request_id| request_id.0.to_string())
+}
+
+pub fn request_id(request: &Request<'_>) -> RequestId {
+    request
+        .local_cache(|| {
+            let request_id = request
+                .headers()
+                .get_one(REQUEST_ID_HEADER)
+                .filter(|request_id| is_valid_request_id(request_id))
+                .map(|request_id| request_id.to_string())
+                .unwrap_or_else(|| Uuid::new_v4().to_string());
+            RequestId(request_id)
+        })
+        .clone()
+}
+
+// The id ends up in log lines and response headers, so only ids that can't break either are taken
+fn is_valid_request_id(request_id: &str) -> bool {
+    !request_id.is_empty()
+        && request_id.len() <= MAX_REQUEST_ID_LENGTH
+        && request_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Lines logged while the guard is alive carry the id of `request`, for logging outside of
+/// route handlers, e.g. in fairings and responders
+pub fn request_scope(request: &Request<'_>) -> ContextGuard {
+    Context::current_with_value(request_id(request)).attach()
+}
+
+pub struct RequestIdMonitor();
+
+#[rocket::async_trait]
+impl Fairing for RequestIdMonitor {
+    fn info(&self) -> Info {
+        Info {
+            name: "RequestIdMonitor",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _data: &mut Data) {
+        request_id(request);
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        response.set_header(Header::new(REQUEST_ID_HEADER, request_id(request).0));
+    }
+}
@@ -0,0 +1,240 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+
+lazy_static! {
+    static ref REGISTRY: Mutex<Registry> = Mutex::new(Registry::default());
+    // Path segments holding an address, a hash or a database id
+    static ref IDENTIFIER_SEGMENT: Regex = Regex::new(r"^(?:.*0x[0-9a-fA-F]{40}.*|\d+)$").unwrap();
+}
+
+const DURATION_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// For ratios such as the size of compressed values relative to their original size
+pub const RATIO_BUCKETS: &[f64] = &[0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 1.0];
+
+type Labels = Vec<(&'static str, String)>;
+
+struct Histogram {
+    upper_bounds: &'static [f64],
+    buckets: Vec<u64>,
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn new(upper_bounds: &'static [f64]) -> Self {
+        Histogram {
+            upper_bounds,
+            buckets: vec![0; upper_bounds.len()],
+            count: 0,
+            sum: 0.0,
+        }
+    }
+
+    fn observe(&mut self, value: f64) {
+        for (bucket, upper_bound) in self.buckets.iter_mut().zip(self.upper_bounds.iter()) {
+            if value <= *upper_bound {
+                *bucket += 1;
+            }
+        }
+        self.count += 1;
+        self.sum += value;
+    }
+}
+
+/// In-process store of all metrics, rendered in the Prometheus text format on scrape
+#[derive(Default)]
+pub(super) struct Registry {
+    counters: BTreeMap<&'static str, BTreeMap<Labels, u64>>,
+    gauges: BTreeMap<&'static str, BTreeMap<Labels, f64>>,
+    histograms: BTreeMap<&'static str, BTreeMap<Labels, Histogram>>,
+}
+
+impl Registry {
+    pub(super) fn increment(&mut self, name: &'static str, labels: &[(&'static str, &str)]) {
+        self.add(name, labels, 1);
+    }
+
+    pub(super) fn add(&mut self, name: &'static str, labels: &[(&'static str, &str)], value: u64) {
+        *self
+            .counters
+            .entry(name)
+            .or_default()
+            .entry(to_labels(labels))
+            .or_default() += value;
+    }
+
+    pub(super) fn set(&mut self, name: &'static str, labels: &[(&'static str, &str)], value: f64) {
+        self.gauges
+            .entry(name)
+            .or_default()
+            .insert(to_labels(labels), value);
+    }
+
+    pub(super) fn observe(
+        &mut self,
+        name: &'static str,
+        labels: &[(&'static str, &str)],
+        duration: Duration,
+    ) {
+        self.observe_value(name, labels, DURATION_BUCKETS, duration.as_secs_f64());
+    }
+
+    /// `upper_bounds` of the buckets are taken from the first observation of every series
+    pub(super) fn observe_value(
+        &mut self,
+        name: &'static str,
+        labels: &[(&'static str, &str)],
+        upper_bounds: &'static [f64],
+        value: f64,
+    ) {
+        self.histograms
+            .entry(name)
+            .or_default()
+            .entry(to_labels(labels))
+            .or_insert_with(|| Histogram::new(upper_bounds))
+            .observe(value);
+    }
+
+    pub(super) fn render(&self) -> String {
+        let mut output = String::new();
+        for (name, series) in &self.counters {
+            writeln!(output, "# TYPE {} counter", name).unwrap();
+            for (labels, value) in series {
+                writeln!(output, "{}{} {}", name, format_labels(labels, None), value).unwrap();
+            }
+        }
+        for (name, series) in &self.gauges {
+            writeln!(output, "# TYPE {} gauge", name).unwrap();
+            for (labels, value) in series {
+                writeln!(output, "{}{} {}", name, format_labels(labels, None), value).unwrap();
+            }
+        }
+        for (name, series) in &self.histograms {
+            writeln!(output, "# TYPE {} histogram", name).unwrap();
+            for (labels, histogram) in series {
+                for (bucket, upper_bound) in
+                    histogram.buckets.iter().zip(histogram.upper_bounds.iter())
+                {
+                    let le = upper_bound.to_string();
+                    writeln!(
+                        output,
+                        "{}_bucket{} {}",
+                        name,
+                        format_labels(labels, Some(&le)),
+                        bucket
+                    )
+                    .unwrap();
+                }
+                writeln!(
+                    output,
+                    "{}_bucket{} {}",
+                    name,
+                    format_labels(labels, Some("+Inf")),
+                    histogram.count
+                )
+                .unwrap();
+                let labels = format_labels(labels, None);
+                writeln!(output, "{}_sum{} {}", name, labels, histogram.sum).unwrap();
+                writeln!(output, "{}_count{} {}", name, labels, histogram.count).unwrap();
+            }
+        }
+        output
+    }
+}
+
+fn to_labels(labels: &[(&'static str, &str)]) -> Labels {
+    labels
+        .iter()
+        .map(|(name, value)| (*name, value.to_string()))
+        .collect()
+}
+
+fn format_labels(labels: &Labels, le: Option<&str>) -> String {
+    let mut formatted: Vec<String> = labels
+        .iter()
+        .map(|(name, value)| format!("{}=\"{}\"", name, escape(value)))
+        .collect();
+    if let Some(le) = le {
+        formatted.push(format!("le=\"{}\"", le));
+    }
+    if formatted.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", formatted.join(","))
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Path of `url` without host and query, with every address, hash or id replaced by `{id}`,
+/// so that all requests for the same endpoint share their labels
+pub fn endpoint_template(url: &str) -> String {
+    let path = url.split_once("://").map_or(url, |(_, without_scheme)| {
+        without_scheme
+            .find('/')
+            .map_or("/", |start| &without_scheme[start..])
+    });
+    let path = path.split('?').next().unwrap_or_default();
+    path.split('/')
+        .map(|segment| {
+            if IDENTIFIER_SEGMENT.is_match(segment) {
+                "{id}"
+            } else {
+                segment
+            }
+        })
+        .collect::<Vec<&str>>()
+        .join("/")
+}
+
+/// Host of `url`, including the port if there is one
+pub fn endpoint_host(url: &str) -> String {
+    url.split_once("://")
+        .and_then(|(_, without_scheme)| without_scheme.split('/').next())
+        .unwrap_or_default()
+        .to_string()
+}
+
+pub fn increment(name: &'static str, labels: &[(&'static str, &str)]) {
+    REGISTRY.lock().unwrap().increment(name, labels);
+}
+
+pub fn set(name: &'static str, labels: &[(&'static str, &str)], value: f64) {
+    REGISTRY.lock().unwrap().set(name, labels, value);
+}
+
+pub fn add(name: &'static str, labels: &[(&'static str, &str)], value: u64) {
+    REGISTRY.lock().unwrap().add(name, labels, value);
+}
+
+pub fn observe(name: &'static str, labels: &[(&'static str, &str)], duration: Duration) {
+    REGISTRY.lock().unwrap().observe(name, labels, duration);
+}
+
+pub fn observe_value(
+    name: &'static str,
+    labels: &[(&'static str, &str)],
+    upper_bounds: &'static [f64],
+    value: f64,
+) {
+    REGISTRY
+        .lock()
+        .unwrap()
+        .observe_value(name, labels, upper_bounds, value);
+}
+
+pub fn render() -> String {
+    REGISTRY.lock().unwrap().render()
+}
@@ -1 +1,9 @@
+pub mod cache;
+pub mod logging;
+pub mod metrics;
 pub mod performance;
+pub mod services;
+pub mod tracing;
+
+#[cfg(test)]
+mod tests;
@@ -1,6 +1,10 @@
+use crate::monitoring::logging::{log_fields, request_scope};
+use crate::monitoring::metrics::{increment, observe};
 use chrono::Utc;
 use rocket::fairing::{Fairing, Info, Kind};
 use rocket::{Data, Request, Response};
+use serde_json::Value;
+use std::time::Duration;
 
 pub struct PerformanceMonitor();
 
@@ -28,6 +32,35 @@ impl Fairing for PerformanceMonitor {
         let method = request.method().as_str();
         let status_code = response.status().code;
         let delta = Utc::now().timestamp_millis() - cached;
-        log::info!("MT::{}::{}::{}::{}", method, path_data, delta, status_code)
+        // The request id is added to the line by the request scope
+        let _scope = request_scope(request);
+        let _fields = log_fields(&[
+            ("method", Value::from(method)),
+            ("route", Value::from(path_data)),
+            ("durationMs", Value::from(delta)),
+            ("status", Value::from(status_code)),
+        ]);
+        log::info!("Request handled");
+        report_request(request, status_code, delta);
     }
 }
+
+// Unmatched requests share a single label, so that random paths can't blow up the metrics
+fn report_request(request: &Request<'_>, status_code: u16, delta: i64) {
+    let route = request
+        .route()
+        .map(|route| route.uri.to_string())
+        .unwrap_or(String::from("unmatched"));
+    let status = status_code.to_string();
+    let labels = [
+        ("method", request.method().as_str()),
+        ("route", route.as_str()),
+        ("status", status.as_str()),
+    ];
+    increment("scg_http_requests_total", &labels);
+    observe(
+        "scg_http_request_duration_seconds",
+        &labels,
+        Duration::from_millis(delta.max(0) as u64),
+    );
+}
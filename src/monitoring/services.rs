@@ -0,0 +1,21 @@
+use crate::monitoring::metrics::increment;
+use crate::utils::errors::ApiResult;
+
+/// `event_type` is the `type` of the received
+/// [PayloadDetails](crate::models::backend::webhooks::PayloadDetails)
+pub fn report_webhook(event_type: &str) {
+    increment("scg_webhooks_total", &[("type", event_type)]);
+}
+
+/// `outcome` is one of `queued`, `duplicate`, `processed`, `retried` or `dead_lettered`
+pub fn report_webhook_queue(outcome: &str) {
+    increment("scg_webhook_queue_total", &[("outcome", outcome)]);
+}
+
+pub fn report_info_lookup<T>(lookup: &str, result: &ApiResult<T>) {
+    let outcome = if result.is_ok() { "found" } else { "error" };
+    increment(
+        "scg_info_lookups_total",
+        &[("lookup", lookup), ("outcome", outcome)],
+    );
+}
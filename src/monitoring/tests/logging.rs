@@ -0,0 +1,80 @@
+use crate::monitoring::logging::{json_line, log_fields, request_id, RequestIdMonitor};
+use log::{Level, Record};
+use rocket::http::Header;
+use rocket::local::asynchronous::Client;
+use serde_json::Value;
+
+#[rocket::async_test]
+async fn request_id_taken_from_header() {
+    let client = Client::debug(rocket::build())
+        .await
+        .expect("valid rocket instance");
+    let request = client
+        .get("/")
+        .header(Header::new("X-Request-Id", "some_request_id"));
+
+    assert_eq!(request_id(request.inner()).0, "some_request_id");
+}
+
+#[rocket::async_test]
+async fn request_id_replaced_when_invalid() {
+    let client = Client::debug(rocket::build())
+        .await
+        .expect("valid rocket instance");
+    let too_long = "a".repeat(65);
+
+    for invalid in &["some request id", "id\u{1b}[31m", "", too_long.as_str()] {
+        let request = client
+            .get("/")
+            .header(Header::new("X-Request-Id", invalid.to_string()));
+
+        assert_eq!(request_id(request.inner()).0.len(), 36, "{:?}", invalid);
+    }
+}
+
+#[rocket::async_test]
+async fn request_id_generated_once() {
+    let client = Client::debug(rocket::build())
+        .await
+        .expect("valid rocket instance");
+    let request = client.get("/");
+
+    let actual = request_id(request.inner());
+
+    assert_eq!(actual.0.len(), 36);
+    assert_eq!(request_id(request.inner()).0, actual.0);
+}
+
+#[rocket::async_test]
+async fn request_id_echoed_in_response() {
+    let rocket = rocket::build().attach(RequestIdMonitor());
+    let client = Client::debug(rocket).await.expect("valid rocket instance");
+
+    let response = client
+        .get("/")
+        .header(Header::new("X-Request-Id", "some_request_id"))
+        .dispatch()
+        .await;
+
+    assert_eq!(
+        response.headers().get_one("X-Request-Id"),
+        Some("some_request_id")
+    );
+}
+
+#[test]
+fn json_line_includes_fields() {
+    let _fields = log_fields(&[("status", Value::from(500)), ("uri", Value::from("/about"))]);
+
+    let line = json_line(
+        &Record::builder()
+            .args(format_args!("Error response"))
+            .level(Level::Error)
+            .target("errors")
+            .build(),
+    );
+
+    assert_eq!(line["message"], "Error response");
+    assert_eq!(line["status"], 500);
+    assert_eq!(line["uri"], "/about");
+}
@@ -0,0 +1,111 @@
+use crate::monitoring::metrics::{endpoint_host, endpoint_template, Registry};
+use std::time::Duration;
+
+#[test]
+fn endpoint_template_replaces_identifiers() {
+    let url = "https://safe-transaction.rinkeby.gnosis.io/api/v1/safes/0x1230B3d59858296A31053C1b8562Ecf89A2f888b/all-transactions/?limit=20&offset=0";
+
+    assert_eq!(
+        endpoint_template(url),
+        "/api/v1/safes/{id}/all-transactions/"
+    );
+}
+
+#[test]
+fn endpoint_template_for_route_keys() {
+    let key = "/v1/transactions/multisig_0x1230B3d59858296A31053C1b8562Ecf89A2f888b_0xa1dfa1f3d2b1b7e7e5e96a0f3c5e4c7a0c6c1d7ac0b7f1c3a4e3b3d7f9b6f1a2";
+
+    assert_eq!(endpoint_template(key), "/v1/transactions/{id}");
+    assert_eq!(
+        endpoint_template("/v1/safes/4/balances"),
+        "/v1/safes/{id}/balances"
+    );
+    assert_eq!(endpoint_template("/health"), "/health");
+}
+
+#[test]
+fn registry_renders_counters() {
+    let mut registry = Registry::default();
+    registry.increment("requests_total", &[("outcome", "hit")]);
+    registry.increment("requests_total", &[("outcome", "hit")]);
+    registry.increment("requests_total", &[("outcome", "miss")]);
+
+    let expected = "# TYPE requests_total counter\n\
+        requests_total{outcome=\"hit\"} 2\n\
+        requests_total{outcome=\"miss\"} 1\n";
+
+    assert_eq!(registry.render(), expected);
+}
+
+#[test]
+fn registry_renders_histograms() {
+    let mut registry = Registry::default();
+    registry.observe("duration_seconds", &[], Duration::from_millis(20));
+    registry.observe("duration_seconds", &[], Duration::from_secs(20));
+
+    let actual = registry.render();
+
+    assert!(actual.starts_with("# TYPE duration_seconds histogram\n"));
+    assert!(actual.contains("duration_seconds_bucket{le=\"0.01\"} 0\n"));
+    assert!(actual.contains("duration_seconds_bucket{le=\"0.025\"} 1\n"));
+    assert!(actual.contains("duration_seconds_bucket{le=\"10\"} 1\n"));
+    assert!(actual.contains("duration_seconds_bucket{le=\"+Inf\"} 2\n"));
+    assert!(actual.contains("duration_seconds_sum 20.02\n"));
+    assert!(actual.contains("duration_seconds_count 2\n"));
+}
+
+#[test]
+fn registry_renders_histograms_with_own_buckets() {
+    let mut registry = Registry::default();
+    registry.observe_value("ratio", &[], &[0.5, 1.0], 0.25);
+    registry.observe_value("ratio", &[], &[0.5, 1.0], 0.75);
+
+    let expected = "# TYPE ratio histogram\n\
+        ratio_bucket{le=\"0.5\"} 1\n\
+        ratio_bucket{le=\"1\"} 2\n\
+        ratio_bucket{le=\"+Inf\"} 2\n\
+        ratio_sum 1\n\
+        ratio_count 2\n";
+
+    assert_eq!(registry.render(), expected);
+}
+
+#[test]
+fn registry_adds_to_counters() {
+    let mut registry = Registry::default();
+    registry.add("bytes_total", &[], 1024);
+    registry.add("bytes_total", &[], 512);
+
+    assert!(registry.render().contains("bytes_total 1536\n"));
+}
+
+#[test]
+fn registry_escapes_label_values() {
+    let mut registry = Registry::default();
+    registry.increment("requests_total", &[("route", "/\"quoted\"")]);
+
+    assert!(registry
+        .render()
+        .contains("requests_total{route=\"/\\\"quoted\\\"\"} 1\n"));
+}
+
+#[test]
+fn endpoint_host_includes_port() {
+    assert_eq!(
+        endpoint_host("http://localhost:8000/api/v1/safes/"),
+        "localhost:8000"
+    );
+    assert_eq!(endpoint_host("/v1/safes"), "");
+}
+
+#[test]
+fn registry_renders_latest_gauge_value() {
+    let mut registry = Registry::default();
+    registry.set("pool_connections", &[], 3.0);
+    registry.set("pool_connections", &[], 5.0);
+
+    assert_eq!(
+        registry.render(),
+        "# TYPE pool_connections gauge\npool_connections 5\n"
+    );
+}
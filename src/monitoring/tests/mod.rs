@@ -0,0 +1,3 @@
+mod logging;
+mod metrics;
+mod tracing;
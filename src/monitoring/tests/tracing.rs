@@ -0,0 +1,58 @@
+use crate::config::chains::default_chain;
+use crate::monitoring::logging::RequestId;
+use crate::monitoring::tracing::{in_span, upstream_headers};
+use opentelemetry::global;
+use opentelemetry::sdk::propagation::TraceContextPropagator;
+use opentelemetry::trace::FutureExt;
+use opentelemetry::Context;
+use std::collections::HashMap;
+
+#[test]
+fn upstream_headers_empty_without_trace() {
+    global::set_text_map_propagator(TraceContextPropagator::new());
+
+    assert!(upstream_headers("https://example.com")
+        .get("traceparent")
+        .is_none());
+}
+
+#[rocket::async_test]
+async fn upstream_headers_continue_incoming_trace() {
+    global::set_text_map_propagator(TraceContextPropagator::new());
+    let traceparent = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+    let mut incoming = HashMap::new();
+    incoming.insert(String::from("traceparent"), String::from(traceparent));
+    let parent = global::get_text_map_propagator(|propagator| propagator.extract(&incoming));
+
+    // Without an exporter spans are no-ops that keep the context of their parent
+    let actual = in_span("upstream", async {
+        upstream_headers("https://example.com")
+    })
+    .with_context(parent)
+    .await;
+
+    assert_eq!(actual.get("traceparent").unwrap(), traceparent);
+}
+
+#[test]
+fn upstream_headers_include_request_id() {
+    let _guard = Context::current_with_value(RequestId(String::from("some_request_id"))).attach();
+
+    let url = format!(
+        "{}/v1/about/",
+        default_chain().base_transaction_service_url()
+    );
+
+    let actual = upstream_headers(&url);
+
+    assert_eq!(actual.get("X-Request-Id").unwrap(), "some_request_id");
+}
+
+#[test]
+fn upstream_headers_keep_request_id_from_other_hosts() {
+    let _guard = Context::current_with_value(RequestId(String::from("some_request_id"))).attach();
+
+    let actual = upstream_headers("https://api.coinbase.com/v2/exchange-rates");
+
+    assert!(actual.get("X-Request-Id").is_none());
+}
@@ -0,0 +1,148 @@
+use crate::config::chains::chains;
+use crate::config::{tracing_exporter, tracing_otlp_endpoint};
+use crate::monitoring::logging::{current_request_id, request_id, REQUEST_ID_HEADER};
+use opentelemetry::global;
+use opentelemetry::propagation::{Extractor, Injector};
+use opentelemetry::sdk::propagation::TraceContextPropagator;
+use opentelemetry::sdk::{trace, Resource};
+use opentelemetry::trace::{get_active_span, FutureExt, SpanKind, TraceContextExt, Tracer};
+use opentelemetry::{Context, KeyValue};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use rocket::http::HeaderMap as RequestHeaderMap;
+use rocket::route::{Handler, Outcome};
+use rocket::{Data, Request, Route};
+use std::future::Future;
+
+const TRACER_NAME: &'static str = "safe-client-gateway";
+
+/// Installs the exporter selected with `TRACING_EXPORTER`, with `none` all spans are dropped
+/// but incoming trace context is still passed on to upstream services
+pub fn init_tracing() {
+    global::set_text_map_propagator(TraceContextPropagator::new());
+    let config = trace::config().with_resource(Resource::new(vec![KeyValue::new(
+        "service.name",
+        TRACER_NAME,
+    )]));
+    match tracing_exporter().as_str() {
+        "otlp" => {
+            opentelemetry_otlp::new_pipeline()
+                .with_endpoint(tracing_otlp_endpoint())
+                .with_trace_config(config)
+                .with_tonic()
+                .install_batch(opentelemetry::runtime::Tokio)
+                .expect("Could not install the OTLP exporter");
+        }
+        "stdout" => {
+            opentelemetry::sdk::export::trace::stdout::new_pipeline()
+                .with_trace_config(config)
+                .install_simple();
+        }
+        "none" => {}
+        exporter => panic!("Unsupported tracing exporter: {}", exporter),
+    }
+}
+
+/// Wraps every route handler in a server span, continuing the trace of the caller if the
+/// request carries W3C trace context headers. The request id is part of the same context.
+pub fn traced(routes: Vec<Route>) -> Vec<Route> {
+    routes
+        .into_iter()
+        .map(|mut route| {
+            route.handler = Box::new(TracedHandler(route.handler));
+            route
+        })
+        .collect()
+}
+
+#[derive(Clone)]
+struct TracedHandler(Box<dyn Handler>);
+
+#[rocket::async_trait]
+impl Handler for TracedHandler {
+    async fn handle<'r, 's: 'r>(&'s self, request: &'r Request<'_>, data: Data) -> Outcome<'r> {
+        let parent = global::get_text_map_propagator(|propagator| {
+            propagator.extract(&RequestHeaders(request.headers()))
+        });
+        let route = request
+            .route()
+            .map(|route| route.uri.to_string())
+            .unwrap_or_default();
+        let tracer = global::tracer(TRACER_NAME);
+        let span = tracer
+            .span_builder(&format!("{} {}", request.method(), route))
+            .with_kind(SpanKind::Server)
+            .with_parent_context(parent)
+            .with_attributes(vec![
+                KeyValue::new("http.method", request.method().as_str()),
+                KeyValue::new("http.route", route),
+            ])
+            .start(&tracer);
+        let context = Context::current_with_span(span).with_value(request_id(request));
+
+        let outcome = self
+            .0
+            .handle(request, data)
+            .with_context(context.clone())
+            .await;
+        if let Outcome::Success(response) = &outcome {
+            context.span().set_attribute(KeyValue::new(
+                "http.status_code",
+                response.status().code as i64,
+            ));
+        }
+        outcome
+    }
+}
+
+struct RequestHeaders<'a, 'h>(&'a RequestHeaderMap<'h>);
+
+impl Extractor for RequestHeaders<'_, '_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get_one(key)
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.names().map(|name| name.as_str()).collect()
+    }
+}
+
+struct UpstreamHeaders(HeaderMap);
+
+impl Injector for UpstreamHeaders {
+    fn set(&mut self, key: &str, value: String) {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(key.as_bytes()),
+            HeaderValue::from_str(&value),
+        ) {
+            self.0.insert(name, value);
+        }
+    }
+}
+
+/// Trace context of the current request, to be sent along with the request to `url`. The request
+/// id is only sent to the transaction services, other hosts are not ours to correlate with.
+pub fn upstream_headers(url: &str) -> HeaderMap {
+    let mut headers = UpstreamHeaders(HeaderMap::new());
+    global::get_text_map_propagator(|propagator| propagator.inject(&mut headers));
+    if let Some(request_id) = current_request_id().filter(|_| is_transaction_service(url)) {
+        headers.set(REQUEST_ID_HEADER, request_id);
+    }
+    headers.0
+}
+
+fn is_transaction_service(url: &str) -> bool {
+    chains()
+        .iter()
+        .any(|chain| url.starts_with(&chain.base_transaction_service_url()))
+}
+
+/// Runs `future` in a child span of the current one, `name` must not contain ids so that spans
+/// of the same operation can be grouped
+pub async fn in_span<F: Future>(name: &str, future: F) -> F::Output {
+    let span = global::tracer(TRACER_NAME).start(name);
+    future.with_context(Context::current_with_span(span)).await
+}
+
+pub fn set_span_attribute(attribute: KeyValue) {
+    get_active_span(|span| span.set_attribute(attribute));
+}
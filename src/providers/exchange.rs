@@ -0,0 +1,257 @@
+use crate::cache::cache_operations::RequestCached;
+use crate::cache::Cache;
+use crate::config::{
+    base_exchange_api_url, coinbase_exchange_api_url, exchange_api_cache_duration,
+    exchange_rate_providers, exchange_rates_file_path, short_error_duration,
+};
+use crate::utils::errors::{ApiError, ApiResult};
+use mockall::automock;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Exchange rates of a single provider
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExchangeRates {
+    /// Units of the currency per USD by upper case currency code, always contains `USD`
+    pub rates: HashMap<String, f64>,
+    /// Seconds since epoch the rates were published at, `None` if the provider doesn't tell
+    pub timestamp: Option<i64>,
+}
+
+impl ExchangeRates {
+    /// `rates` are units of the currency per unit of `base`, `None` if they can't be converted
+    /// to USD
+    pub fn new(base: &str, rates: HashMap<String, f64>, timestamp: Option<i64>) -> Option<Self> {
+        let mut rates: HashMap<String, f64> = rates
+            .into_iter()
+            .map(|(code, rate)| (code.to_uppercase(), rate))
+            .collect();
+        rates.insert(base.to_uppercase(), 1.0);
+        let base_per_usd = *rates.get("USD").filter(|rate| **rate > 0.0)?;
+        Some(ExchangeRates {
+            rates: rates
+                .into_iter()
+                .map(|(code, rate)| (code, rate / base_per_usd))
+                .collect(),
+            timestamp,
+        })
+    }
+
+    pub fn usd_to(&self, currency_code: &str) -> Option<f64> {
+        self.rates.get(&currency_code.to_uppercase()).cloned()
+    }
+
+    /// Rates without a timestamp are never stale
+    pub fn is_older_than(&self, max_age: u64, now: i64) -> bool {
+        self.timestamp
+            .map_or(false, |timestamp| now - timestamp > max_age as i64)
+    }
+}
+
+#[automock]
+#[rocket::async_trait]
+pub trait ExchangeRateProvider: Send + Sync {
+    /// As listed in `EXCHANGE_RATE_PROVIDERS`
+    fn name(&self) -> &'static str;
+    async fn usd_rates(&self) -> ApiResult<ExchangeRates>;
+}
+
+/// The providers of `EXCHANGE_RATE_PROVIDERS`, in order
+pub fn configured_providers<'p, C: Cache + Clone + 'static>(
+    client: &'p reqwest::Client,
+    cache: &'p C,
+) -> Vec<Box<dyn ExchangeRateProvider + 'p>> {
+    exchange_rate_providers()
+        .iter()
+        .filter_map(|name| -> Option<Box<dyn ExchangeRateProvider + 'p>> {
+            match name.as_str() {
+                "exchangeratesapi" => Some(Box::new(ExchangeRatesApiProvider { client, cache })),
+                "coinbase" => Some(Box::new(CoinbaseProvider { client, cache })),
+                "static" => Some(Box::new(StaticFileProvider {
+                    path: exchange_rates_file_path(),
+                })),
+                // Rejected when the configuration is loaded
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Rates of the first provider that returns rates that are at most `max_age` seconds old and
+/// include `currency_code`, if given. The failures of the providers before it are logged.
+pub async fn first_fresh_rates(
+    providers: &[Box<dyn ExchangeRateProvider + '_>],
+    currency_code: Option<&str>,
+    max_age: u64,
+    now: i64,
+) -> ApiResult<ExchangeRates> {
+    let mut currency_missing = false;
+    for provider in providers {
+        match provider.usd_rates().await {
+            Ok(rates) if rates.is_older_than(max_age, now) => log::warn!(
+                "Exchange rates of {} are older than {} seconds",
+                provider.name(),
+                max_age
+            ),
+            Ok(rates) if currency_code.map_or(false, |code| rates.usd_to(code).is_none()) => {
+                currency_missing = true;
+                log::warn!(
+                    "Exchange rates of {} don't include {}",
+                    provider.name(),
+                    currency_code.unwrap_or_default()
+                )
+            }
+            Ok(rates) => return Ok(rates),
+            Err(error) => log::warn!(
+                "Exchange rate provider {} failed: {}",
+                provider.name(),
+                error
+            ),
+        }
+    }
+    if currency_missing {
+        Err(client_error!(422, "Currency not found"))
+    } else {
+        Err(client_error!(503, "Exchange rates unavailable"))
+    }
+}
+
+async fn fetch_cached(
+    client: &reqwest::Client,
+    cache: &(impl Cache + Clone + 'static),
+    url: String,
+) -> ApiResult<String> {
+    RequestCached::new(url)
+        .cache_duration(exchange_api_cache_duration())
+        .error_cache_duration(short_error_duration())
+        .execute(client, cache)
+        .await
+}
+
+fn unsupported_rates(provider: &str) -> ApiError {
+    ApiError::new_from_message(format!("Rates of {} can't be converted to USD", provider))
+}
+
+#[derive(Deserialize, Debug)]
+struct ExchangeRatesApiResponse {
+    rates: Option<HashMap<String, f64>>,
+    base: String,
+    timestamp: Option<i64>,
+}
+
+/// https://exchangeratesapi.io, `EXCHANGE_API_BASE_URL` with the `EXCHANGE_API_KEY`
+pub struct ExchangeRatesApiProvider<'p, C: Cache> {
+    client: &'p reqwest::Client,
+    cache: &'p C,
+}
+
+#[rocket::async_trait]
+impl<C: Cache + Clone + 'static> ExchangeRateProvider for ExchangeRatesApiProvider<'_, C> {
+    fn name(&self) -> &'static str {
+        "exchangeratesapi"
+    }
+
+    async fn usd_rates(&self) -> ApiResult<ExchangeRates> {
+        let body = fetch_cached(self.client, self.cache, base_exchange_api_url()).await?;
+        let response = serde_json::from_str::<ExchangeRatesApiResponse>(&body)?;
+        ExchangeRates::new(
+            &response.base,
+            response.rates.unwrap_or_default(),
+            response.timestamp,
+        )
+        .ok_or_else(|| unsupported_rates(self.name()))
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct CoinbaseResponse {
+    data: CoinbaseRates,
+}
+
+#[derive(Deserialize, Debug)]
+struct CoinbaseRates {
+    currency: String,
+    rates: HashMap<String, String>,
+}
+
+/// https://api.coinbase.com/v2/exchange-rates, `COINBASE_EXCHANGE_API_URL` doesn't need a key
+pub struct CoinbaseProvider<'p, C: Cache> {
+    client: &'p reqwest::Client,
+    cache: &'p C,
+}
+
+#[rocket::async_trait]
+impl<C: Cache + Clone + 'static> ExchangeRateProvider for CoinbaseProvider<'_, C> {
+    fn name(&self) -> &'static str {
+        "coinbase"
+    }
+
+    async fn usd_rates(&self) -> ApiResult<ExchangeRates> {
+        let body = fetch_cached(self.client, self.cache, coinbase_exchange_api_url()).await?;
+        parse_coinbase_rates(&body)?.ok_or_else(|| unsupported_rates(self.name()))
+    }
+}
+
+// Rates are strings, crypto currencies that can't be parsed are left out
+pub(super) fn parse_coinbase_rates(body: &str) -> ApiResult<Option<ExchangeRates>> {
+    let response = serde_json::from_str::<CoinbaseResponse>(body)?;
+    let rates = response
+        .data
+        .rates
+        .into_iter()
+        .filter_map(|(code, rate)| rate.parse::<f64>().ok().map(|rate| (code, rate)))
+        .collect();
+    Ok(ExchangeRates::new(&response.data.currency, rates, None))
+}
+
+/// StaticRates
+///
+/// Content of the `EXCHANGE_RATES_FILE_PATH`, for environments without access to a rates API
+///
+/// <details>
+/// <summary>Sample</summary>
+///
+/// ```json
+/// {
+///   "base": "USD",
+///   "rates": {
+///     "EUR": 0.84,
+///     "CHF": 0.91
+///   },
+///   "timestamp": 1620000000
+/// }
+/// ```
+/// </details>
+#[derive(Deserialize, Debug)]
+struct StaticRates {
+    base: String,
+    rates: HashMap<String, f64>,
+    /// Rates without a timestamp are never considered stale
+    timestamp: Option<i64>,
+}
+
+/// Rates of a JSON file, which is read on every request so that it can be updated in place
+pub struct StaticFileProvider {
+    path: String,
+}
+
+#[rocket::async_trait]
+impl ExchangeRateProvider for StaticFileProvider {
+    fn name(&self) -> &'static str {
+        "static"
+    }
+
+    async fn usd_rates(&self) -> ApiResult<ExchangeRates> {
+        let file = rocket::tokio::fs::read_to_string(&self.path)
+            .await
+            .map_err(|error| {
+                ApiError::new_from_message(format!("Could not read {}: {}", self.path, error))
+            })?;
+        parse_static_rates(&file)?.ok_or_else(|| unsupported_rates(self.name()))
+    }
+}
+
+pub(super) fn parse_static_rates(file: &str) -> ApiResult<Option<ExchangeRates>> {
+    let file = serde_json::from_str::<StaticRates>(file)?;
+    Ok(ExchangeRates::new(&file.base, file.rates, file.timestamp))
+}
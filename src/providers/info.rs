@@ -1,18 +1,23 @@
 use crate::cache::cache_operations::RequestCached;
-use crate::cache::redis::ServiceCache;
-use crate::cache::Cache;
+use crate::cache::compression::{pack, unpack, CompressionSettings};
+use crate::cache::single_flight::coalesce;
+use crate::cache::{namespaced, Cache, ServiceCache};
+use crate::config::chains::ChainConfig;
 use crate::config::{
-    address_info_cache_duration, base_exchange_api_url, base_transaction_service_url,
-    exchange_api_cache_duration, long_error_duration, safe_app_info_request_timeout,
-    safe_app_manifest_cache_duration, safe_info_cache_duration, safe_info_request_timeout,
-    short_error_duration, token_info_cache_duration, token_info_request_timeout,
+    address_info_cache_duration, exchange_rates_max_age, long_error_duration,
+    request_stale_duration, safe_app_info_request_timeout, safe_app_manifest_cache_duration,
+    safe_info_request_timeout, short_error_duration, token_info_request_timeout,
 };
 use crate::models::commons::Page;
+use crate::monitoring::services::report_info_lookup;
+use crate::monitoring::tracing::{in_span, upstream_headers};
 use crate::providers::address_info::{AddressInfo, ContractInfo};
+use crate::providers::exchange::{configured_providers, first_fresh_rates, ExchangeRates};
 use crate::utils::context::Context;
 use crate::utils::errors::ApiResult;
 use crate::utils::json::default_if_null;
 use crate::utils::urls::build_manifest_url;
+use chrono::Utc;
 use lazy_static::lazy_static;
 use mockall::automock;
 use rocket::futures::TryFutureExt;
@@ -30,6 +35,11 @@ lazy_static! {
     pub static ref SAFE_V_1_3_0: Version = Version::new(1, 3, 0);
 }
 
+/// Token lists differ per chain, `TOKENS_KEY` followed by the chain id
+pub fn tokens_cache_key(chain_id: u64) -> String {
+    namespaced(&format!("{}_{}", TOKENS_KEY, chain_id))
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum TokenType {
@@ -69,12 +79,6 @@ struct Manifest {
     pub(super) icon_path: String,
 }
 
-#[derive(Deserialize, Clone, Debug)]
-pub struct Exchange {
-    pub rates: Option<HashMap<String, f64>>,
-    pub base: String,
-}
-
 #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct TokenInfo {
@@ -98,6 +102,7 @@ pub trait InfoProvider {
 }
 
 pub struct DefaultInfoProvider<'p, C: Cache> {
+    chain: &'static ChainConfig,
     client: &'p reqwest::Client,
     cache: &'p C,
     // Mutex is an async Mutex, meaning that the lock is non-blocking
@@ -106,13 +111,70 @@ pub struct DefaultInfoProvider<'p, C: Cache> {
 }
 
 #[rocket::async_trait]
-impl<C: Cache> InfoProvider for DefaultInfoProvider<'_, C> {
+impl<C: Cache + Clone + 'static> InfoProvider for DefaultInfoProvider<'_, C> {
     async fn safe_info(&self, safe: &str) -> ApiResult<SafeInfo> {
         let safe_cache = &mut self.safe_cache.lock().await;
-        Self::cached(safe_cache, || self.load_safe_info(safe.to_string()), safe).await
+        let result = in_span(
+            "InfoProvider::safe_info",
+            Self::cached(safe_cache, || self.load_safe_info(safe.to_string()), safe),
+        )
+        .await;
+        report_info_lookup("safe_info", &result);
+        result
     }
 
     async fn token_info(&self, token: &str) -> ApiResult<TokenInfo> {
+        let result = in_span(
+            "InfoProvider::token_info",
+            self.load_token_info_cached(token),
+        )
+        .await;
+        report_info_lookup("token_info", &result);
+        result
+    }
+
+    async fn safe_app_info(&self, url: &str) -> ApiResult<SafeAppInfo> {
+        let result = in_span("InfoProvider::safe_app_info", self.load_safe_app_info(url)).await;
+        report_info_lookup("safe_app_info", &result);
+        result
+    }
+
+    async fn contract_info(&self, address: &str) -> ApiResult<AddressInfo> {
+        let result = in_span(
+            "InfoProvider::contract_info",
+            self.load_contract_info(address),
+        )
+        .await;
+        report_info_lookup("contract_info", &result);
+        result
+    }
+
+    async fn full_address_info_search(&self, address: &str) -> ApiResult<AddressInfo> {
+        let search = self
+            .token_info(&address)
+            .map_ok(|it| AddressInfo {
+                name: it.name,
+                logo_uri: it.logo_uri,
+            })
+            .or_else(|_| async move { self.contract_info(&address).await });
+        in_span("InfoProvider::full_address_info_search", search).await
+    }
+}
+
+impl<'a> DefaultInfoProvider<'a, ServiceCache> {
+    pub fn new(context: &'a Context) -> Self {
+        DefaultInfoProvider {
+            chain: context.chain(),
+            client: context.client(),
+            cache: context.cache(),
+            safe_cache: Default::default(),
+            token_cache: Default::default(),
+        }
+    }
+}
+
+impl<C: Cache + Clone + 'static> DefaultInfoProvider<'_, C> {
+    async fn load_token_info_cached(&self, token: &str) -> ApiResult<TokenInfo> {
         if token != "0x0000000000000000000000000000000000000000" {
             let token_cache = &mut self.token_cache.lock().await;
             Self::cached(
@@ -126,11 +188,12 @@ impl<C: Cache> InfoProvider for DefaultInfoProvider<'_, C> {
         }
     }
 
-    async fn safe_app_info(&self, url: &str) -> ApiResult<SafeAppInfo> {
+    async fn load_safe_app_info(&self, url: &str) -> ApiResult<SafeAppInfo> {
         let manifest_url = build_manifest_url(url)?;
 
         let manifest_json = RequestCached::new(manifest_url)
             .cache_duration(safe_app_manifest_cache_duration())
+            .stale_duration(request_stale_duration())
             .error_cache_duration(long_error_duration())
             .cache_all_errors()
             .request_timeout(safe_app_info_request_timeout())
@@ -144,14 +207,15 @@ impl<C: Cache> InfoProvider for DefaultInfoProvider<'_, C> {
         })
     }
 
-    async fn contract_info(&self, address: &str) -> ApiResult<AddressInfo> {
+    async fn load_contract_info(&self, address: &str) -> ApiResult<AddressInfo> {
         let url = format!(
             "{}/v1/contracts/{}/",
-            base_transaction_service_url(),
+            self.chain.base_transaction_service_url(),
             address
         );
         let contract_info_json = RequestCached::new(url)
             .cache_duration(address_info_cache_duration())
+            .stale_duration(request_stale_duration())
             .error_cache_duration(long_error_duration())
             .execute(self.client, self.cache)
             .await?;
@@ -166,29 +230,6 @@ impl<C: Cache> InfoProvider for DefaultInfoProvider<'_, C> {
         }
     }
 
-    async fn full_address_info_search(&self, address: &str) -> ApiResult<AddressInfo> {
-        self.token_info(&address)
-            .map_ok(|it| AddressInfo {
-                name: it.name,
-                logo_uri: it.logo_uri,
-            })
-            .or_else(|_| async move { self.contract_info(&address).await })
-            .await
-    }
-}
-
-impl<'a> DefaultInfoProvider<'a, ServiceCache<'a>> {
-    pub fn new(context: &'a Context) -> Self {
-        DefaultInfoProvider {
-            client: context.client(),
-            cache: context.cache(),
-            safe_cache: Default::default(),
-            token_cache: Default::default(),
-        }
-    }
-}
-
-impl<C: Cache> DefaultInfoProvider<'_, C> {
     async fn cached<'a, T, Fut>(
         local_cache: &'a mut HashMap<String, Option<T>>,
         generator: impl FnOnce() -> Fut,
@@ -212,9 +253,13 @@ impl<C: Cache> DefaultInfoProvider<'_, C> {
     }
 
     async fn load_safe_info(&self, safe: String) -> ApiResult<Option<SafeInfo>> {
-        let url = format!("{}/v1/safes/{}/", base_transaction_service_url(), safe);
+        let url = format!(
+            "{}/v1/safes/{}/",
+            self.chain.base_transaction_service_url(),
+            safe
+        );
         let data = RequestCached::new(url)
-            .cache_duration(safe_info_cache_duration())
+            .cache_duration(self.chain.safe_info_cache_duration())
             .error_cache_duration(short_error_duration())
             .request_timeout(safe_info_request_timeout())
             .execute(self.client, self.cache)
@@ -223,80 +268,111 @@ impl<C: Cache> DefaultInfoProvider<'_, C> {
     }
 
     async fn populate_token_cache(&self) -> ApiResult<()> {
-        let url = format!("{}/v1/tokens/?limit=10000", base_transaction_service_url());
+        let url = format!(
+            "{}/v1/tokens/?limit=10000",
+            self.chain.base_transaction_service_url()
+        );
         let response = self
             .client
             .get(&url)
+            .headers(upstream_headers(&url))
             .timeout(Duration::from_millis(token_info_request_timeout()))
             .send()
             .await?;
         let data: Page<TokenInfo> = response.json().await?;
+        let cache_key = tokens_cache_key(self.chain.chain_id);
+        let compression = CompressionSettings::configured_for_hash_fields();
         for token in data.results.iter() {
             self.cache
-                .insert_in_hash(TOKENS_KEY, &token.address, &serde_json::to_string(&token)?);
+                .insert_in_hash(
+                    &cache_key,
+                    &token.address,
+                    &pack(&serde_json::to_string(&token)?, compression),
+                )
+                .await;
         }
         Ok(())
     }
 
     async fn check_token_cache(&self) -> ApiResult<()> {
-        if self.cache.has_key(TOKENS_KEY) {
-            return Ok(());
+        coalesce(
+            self.cache,
+            &tokens_cache_key(self.chain.chain_id),
+            || self.token_cache_state(),
+            || self.fill_token_cache(),
+        )
+        .await
+    }
+
+    // Requests that find the cache still populating wait for it instead of populating it again
+    async fn token_cache_state(&self) -> Option<ApiResult<()>> {
+        let cache_key = tokens_cache_key(self.chain.chain_id);
+        match self.cache.get_from_hash(&cache_key, "state").await {
+            Some(state) if state != "populating" => Some(Ok(())),
+            _ => None,
         }
-        self.cache.insert_in_hash(TOKENS_KEY, "state", "populating");
+    }
+
+    async fn fill_token_cache(&self) -> ApiResult<()> {
+        let cache_key = tokens_cache_key(self.chain.chain_id);
+        self.cache
+            .insert_in_hash(&cache_key, "state", "populating")
+            .await;
         let result = self.populate_token_cache().await;
         if result.is_ok() {
             self.cache
-                .expire_entity(TOKENS_KEY, token_info_cache_duration());
-            self.cache.insert_in_hash(TOKENS_KEY, "state", "populated");
+                .expire_entity(&cache_key, self.chain.token_info_cache_duration())
+                .await;
+            self.cache
+                .insert_in_hash(&cache_key, "state", "populated")
+                .await;
         } else {
-            self.cache.expire_entity(TOKENS_KEY, short_error_duration());
-            self.cache.insert_in_hash(TOKENS_KEY, "state", "errored");
+            self.cache
+                .expire_entity(&cache_key, short_error_duration())
+                .await;
+            self.cache
+                .insert_in_hash(&cache_key, "state", "errored")
+                .await;
         }
         result
     }
 
     async fn load_token_info(&self, token: String) -> ApiResult<Option<TokenInfo>> {
         self.check_token_cache().await?;
-        match self.cache.get_from_hash(TOKENS_KEY, &token) {
+        let cached = self
+            .cache
+            .get_from_hash(&tokens_cache_key(self.chain.chain_id), &token)
+            .await;
+        match cached.and_then(unpack) {
             Some(cached) => Ok(Some(serde_json::from_str::<TokenInfo>(&cached)?)),
             None => Ok(None),
         }
     }
 
+    /// Errors if none of the `EXCHANGE_RATE_PROVIDERS` has fresh rates for the currency
     pub async fn exchange_usd_to(&self, currency_code: &str) -> ApiResult<f64> {
         if &currency_code.to_lowercase() == "usd" {
             return Ok(1.0);
         }
 
-        let currency_code = currency_code.to_uppercase();
-        let exchange = self.fetch_exchange().await?;
-        match exchange.rates {
-            Some(rates) => {
-                let base_to_usd = rates.get("USD").unwrap_or(&0.0);
-                rates
-                    .get(&currency_code)
-                    .cloned()
-                    .map(|base_to_requested_code| base_to_requested_code / base_to_usd)
-                    .ok_or(client_error!(422, "Currency not found"))
-            }
-            None => Err(client_error!(422, "Currency not found")),
-        }
+        self.fetch_exchange(Some(currency_code))
+            .await?
+            .usd_to(currency_code)
+            .ok_or(client_error!(422, "Currency not found"))
     }
 
     pub async fn available_currency_codes(&self) -> ApiResult<Vec<String>> {
-        let exchange = self.fetch_exchange().await?;
-        Ok(exchange
-            .rates
-            .map_or(vec![], |s| s.keys().cloned().collect::<Vec<_>>()))
+        let exchange = self.fetch_exchange(None).await?;
+        Ok(exchange.rates.keys().cloned().collect::<Vec<_>>())
     }
 
-    async fn fetch_exchange(&self) -> ApiResult<Exchange> {
-        let url = base_exchange_api_url();
-        let body = RequestCached::new(url)
-            .cache_duration(exchange_api_cache_duration())
-            .error_cache_duration(short_error_duration())
-            .execute(self.client, self.cache)
-            .await?;
-        Ok(serde_json::from_str::<Exchange>(&body)?)
+    async fn fetch_exchange(&self, currency_code: Option<&str>) -> ApiResult<ExchangeRates> {
+        first_fresh_rates(
+            &configured_providers(self.client, self.cache),
+            currency_code,
+            exchange_rates_max_age(),
+            Utc::now().timestamp(),
+        )
+        .await
     }
 }
@@ -1,3 +1,7 @@
 pub mod address_info;
+pub mod exchange;
 pub mod ext;
 pub mod info;
+
+#[cfg(test)]
+mod tests;
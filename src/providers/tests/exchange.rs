@@ -0,0 +1,145 @@
+use crate::providers::exchange::{
+    first_fresh_rates, ExchangeRateProvider, ExchangeRates, MockExchangeRateProvider,
+};
+use crate::providers::exchange::{parse_coinbase_rates, parse_static_rates};
+use crate::utils::errors::ApiError;
+use std::collections::HashMap;
+
+const NOW: i64 = 1620000000;
+const MAX_AGE: u64 = 60 * 60;
+
+fn rates(values: &[(&str, f64)]) -> HashMap<String, f64> {
+    values
+        .iter()
+        .map(|(code, rate)| (code.to_string(), *rate))
+        .collect()
+}
+
+fn provider(
+    name: &'static str,
+    rates: Result<ExchangeRates, ApiError>,
+) -> Box<dyn ExchangeRateProvider> {
+    let mut provider = MockExchangeRateProvider::new();
+    provider.expect_name().return_const(name);
+    provider
+        .expect_usd_rates()
+        .times(1)
+        .return_once(move || rates);
+    Box::new(provider)
+}
+
+#[test]
+fn exchange_rates_are_converted_to_usd() {
+    let actual = ExchangeRates::new("eur", rates(&[("USD", 1.25), ("chf", 1.1)]), None).unwrap();
+
+    assert_eq!(actual.usd_to("USD"), Some(1.0));
+    assert_eq!(actual.usd_to("eur"), Some(0.8));
+    assert_eq!(actual.usd_to("CHF"), Some(1.1 / 1.25));
+    assert_eq!(actual.usd_to("GBP"), None);
+}
+
+#[test]
+fn exchange_rates_without_usd() {
+    let actual = ExchangeRates::new("EUR", rates(&[("CHF", 1.1)]), None);
+
+    assert_eq!(actual, None);
+}
+
+#[test]
+fn exchange_rates_staleness() {
+    let rates = ExchangeRates::new("USD", rates(&[]), Some(NOW - MAX_AGE as i64)).unwrap();
+    let without_timestamp = ExchangeRates {
+        timestamp: None,
+        ..rates.clone()
+    };
+
+    assert!(!rates.is_older_than(MAX_AGE, NOW));
+    assert!(rates.is_older_than(MAX_AGE, NOW + 1));
+    assert!(!without_timestamp.is_older_than(MAX_AGE, NOW + 1));
+}
+
+#[test]
+fn coinbase_rates() {
+    let body = r#"{
+        "data": {
+            "currency": "USD",
+            "rates": { "EUR": "0.8", "BTC": "0.00002", "XYZ": "n/a" }
+        }
+    }"#;
+    let expected =
+        ExchangeRates::new("USD", rates(&[("EUR", 0.8), ("BTC", 0.00002)]), None).unwrap();
+
+    let actual = parse_coinbase_rates(body).unwrap();
+
+    assert_eq!(actual, Some(expected));
+}
+
+#[test]
+fn static_rates() {
+    let file = r#"{
+        "base": "EUR",
+        "rates": { "USD": 1.25 },
+        "timestamp": 1620000000
+    }"#;
+    let expected = ExchangeRates::new("EUR", rates(&[("USD", 1.25)]), Some(NOW)).unwrap();
+
+    let actual = parse_static_rates(file).unwrap();
+
+    assert_eq!(actual, Some(expected));
+}
+
+#[rocket::async_test]
+async fn first_fresh_rates_falls_back_to_next_provider() {
+    let fresh = ExchangeRates::new("USD", rates(&[("EUR", 0.8)]), Some(NOW)).unwrap();
+    let stale = ExchangeRates::new(
+        "USD",
+        rates(&[("EUR", 0.9)]),
+        Some(NOW - 2 * MAX_AGE as i64),
+    )
+    .unwrap();
+    let providers = vec![
+        provider("exchangeratesapi", Err(client_error!(500, "Unavailable"))),
+        provider("coinbase", Ok(stale)),
+        provider("static", Ok(fresh.clone())),
+    ];
+
+    let actual = first_fresh_rates(&providers, None, MAX_AGE, NOW).await;
+
+    assert_eq!(actual.unwrap(), fresh);
+}
+
+#[rocket::async_test]
+async fn first_fresh_rates_without_rates() {
+    let providers = vec![provider(
+        "exchangeratesapi",
+        Err(client_error!(500, "Unavailable")),
+    )];
+
+    let actual = first_fresh_rates(&providers, None, MAX_AGE, NOW).await;
+
+    assert_eq!(actual.unwrap_err().status, 503);
+}
+
+#[rocket::async_test]
+async fn first_fresh_rates_falls_back_for_missing_currency() {
+    let without_chf = ExchangeRates::new("USD", rates(&[("EUR", 0.8)]), Some(NOW)).unwrap();
+    let with_chf = ExchangeRates::new("USD", rates(&[("CHF", 0.9)]), Some(NOW)).unwrap();
+    let providers = vec![
+        provider("exchangeratesapi", Ok(without_chf)),
+        provider("static", Ok(with_chf.clone())),
+    ];
+
+    let actual = first_fresh_rates(&providers, Some("chf"), MAX_AGE, NOW).await;
+
+    assert_eq!(actual.unwrap(), with_chf);
+}
+
+#[rocket::async_test]
+async fn first_fresh_rates_without_currency() {
+    let without_chf = ExchangeRates::new("USD", rates(&[("EUR", 0.8)]), Some(NOW)).unwrap();
+    let providers = vec![provider("exchangeratesapi", Ok(without_chf))];
+
+    let actual = first_fresh_rates(&providers, Some("CHF"), MAX_AGE, NOW).await;
+
+    assert_eq!(actual.unwrap_err().status, 422);
+}
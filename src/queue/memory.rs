@@ -0,0 +1,92 @@
+use crate::config::{webhook_dead_letter_max_length, webhook_queue_block_timeout};
+use crate::models::service::admin::DeadLetter;
+use crate::queue::{Message, Queue};
+use rocket::tokio::sync::Notify;
+use rocket::tokio::time::timeout;
+use std::collections::VecDeque;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+#[derive(Default)]
+struct State {
+    next_id: u64,
+    messages: VecDeque<Message>,
+    dead_letters: VecDeque<DeadLetter>,
+}
+
+/// Process-local queue for the `memory` cache backend.
+///
+/// Messages are lost when the process stops, they are removed as soon as they are popped so
+/// acknowledging them is a no-op.
+pub struct InMemoryQueue {
+    state: Mutex<State>,
+    pushed: Notify,
+}
+
+impl InMemoryQueue {
+    pub fn new() -> Self {
+        InMemoryQueue {
+            state: Mutex::new(State::default()),
+            pushed: Notify::new(),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<State> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn take(&self, count: usize) -> Vec<Message> {
+        let mut state = self.state();
+        let count = count.min(state.messages.len());
+        state.messages.drain(..count).collect()
+    }
+}
+
+#[rocket::async_trait]
+impl Queue for InMemoryQueue {
+    async fn push(&self, body: &str, attempts: u32) -> bool {
+        {
+            let mut state = self.state();
+            state.next_id += 1;
+            let id = state.next_id.to_string();
+            state.messages.push_back(Message {
+                id,
+                body: body.to_string(),
+                attempts,
+            });
+        }
+        self.pushed.notify_one();
+        true
+    }
+
+    async fn pop(&self, count: usize) -> Vec<Message> {
+        let messages = self.take(count);
+        if !messages.is_empty() {
+            return messages;
+        }
+        let block_timeout = Duration::from_millis(webhook_queue_block_timeout());
+        let _ = timeout(block_timeout, self.pushed.notified()).await;
+        self.take(count)
+    }
+
+    async fn ack(&self, _id: &str) {}
+
+    async fn dead_letter(&self, dead_letter: &DeadLetter) {
+        let mut state = self.state();
+        state.dead_letters.push_front(dead_letter.clone());
+        state
+            .dead_letters
+            .truncate(webhook_dead_letter_max_length());
+    }
+
+    async fn dead_letters(&self, count: usize) -> Vec<DeadLetter> {
+        self.state()
+            .dead_letters
+            .iter()
+            .take(count)
+            .cloned()
+            .collect()
+    }
+}
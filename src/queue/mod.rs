@@ -0,0 +1,90 @@
+pub mod memory;
+pub mod redis;
+
+#[cfg(test)]
+mod tests;
+
+use crate::config::cache_backend;
+use crate::models::service::admin::DeadLetter;
+use crate::queue::memory::InMemoryQueue;
+use crate::queue::redis::RedisQueue;
+use mockall::automock;
+use rocket::request::{self, FromRequest, Request};
+use rocket::State;
+use std::sync::Arc;
+
+const QUEUE_STREAM_KEY: &'static str = "q_webhooks";
+const QUEUE_DEAD_LETTER_KEY: &'static str = "q_webhooks_dead";
+
+/// Message as read from the queue, `attempts` is the number of times processing it failed
+#[derive(Clone, Debug, PartialEq)]
+pub struct Message {
+    pub id: String,
+    pub body: String,
+    pub attempts: u32,
+}
+
+/// Queue of received webhooks, messages stay in the queue until they are acknowledged
+#[automock]
+#[rocket::async_trait]
+pub trait Queue: Send + Sync {
+    /// `false` if the message could not be stored
+    async fn push(&self, body: &str, attempts: u32) -> bool;
+    /// Waits up to `WEBHOOK_QUEUE_BLOCK_TIMEOUT` milliseconds for messages
+    async fn pop(&self, count: usize) -> Vec<Message>;
+    async fn ack(&self, id: &str);
+    async fn dead_letter(&self, dead_letter: &DeadLetter);
+    /// Most recent first
+    async fn dead_letters(&self, count: usize) -> Vec<DeadLetter>;
+}
+
+pub fn create_queue() -> Arc<dyn Queue> {
+    match cache_backend().as_str() {
+        "redis" => Arc::new(RedisQueue::new()),
+        "memory" => Arc::new(InMemoryQueue::new()),
+        backend => panic!("Unsupported cache backend: {}", backend),
+    }
+}
+
+/// Request guard for the queue matching the `CACHE_BACKEND`
+#[derive(Clone)]
+pub struct ServiceQueue(Arc<dyn Queue>);
+
+impl ServiceQueue {
+    pub fn new(queue: Arc<dyn Queue>) -> Self {
+        ServiceQueue(queue)
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ServiceQueue {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        let queue = try_outcome!(request.guard::<State<Arc<dyn Queue>>>().await);
+        request::Outcome::Success(ServiceQueue(queue.inner().clone()))
+    }
+}
+
+#[rocket::async_trait]
+impl Queue for ServiceQueue {
+    async fn push(&self, body: &str, attempts: u32) -> bool {
+        self.0.push(body, attempts).await
+    }
+
+    async fn pop(&self, count: usize) -> Vec<Message> {
+        self.0.pop(count).await
+    }
+
+    async fn ack(&self, id: &str) {
+        self.0.ack(id).await
+    }
+
+    async fn dead_letter(&self, dead_letter: &DeadLetter) {
+        self.0.dead_letter(dead_letter).await
+    }
+
+    async fn dead_letters(&self, count: usize) -> Vec<DeadLetter> {
+        self.0.dead_letters(count).await
+    }
+}
@@ -0,0 +1,239 @@
+use crate::cache::namespaced;
+use crate::cache::redis::create_pool;
+use crate::config::{
+    webhook_dead_letter_max_length, webhook_queue_block_timeout, webhook_queue_claim_idle_time,
+    webhook_queue_consumer, webhook_queue_retry_delay,
+};
+use crate::models::service::admin::DeadLetter;
+use crate::queue::{Message, Queue, QUEUE_DEAD_LETTER_KEY, QUEUE_STREAM_KEY};
+use bb8::{Pool, PooledConnection};
+use bb8_redis::RedisConnectionManager;
+use redis::streams::{
+    StreamClaimReply, StreamId, StreamPendingCountReply, StreamReadOptions, StreamReadReply,
+};
+use redis::{pipe, AsyncCommands, RedisResult};
+use rocket::tokio::time::sleep;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+const CONSUMER_GROUP: &'static str = "gateway";
+
+/// Queue backed by a Redis stream, shared by all gateway instances through a consumer group.
+///
+/// Messages are deleted from the stream once they are acknowledged. Messages read but not
+/// acknowledged by a consumer are handed out to it again after a restart, or claimed by any
+/// consumer once they were idle for `WEBHOOK_QUEUE_CLAIM_IDLE_TIME` milliseconds.
+pub struct RedisQueue {
+    // Reads block a connection, so they don't share the pool of the cache
+    pool: Pool<RedisConnectionManager>,
+    stream: String,
+    dead_letters: String,
+    group_created: AtomicBool,
+    recovered: AtomicBool,
+}
+
+impl RedisQueue {
+    pub fn new() -> Self {
+        RedisQueue {
+            pool: create_pool(),
+            stream: namespaced(QUEUE_STREAM_KEY),
+            dead_letters: namespaced(QUEUE_DEAD_LETTER_KEY),
+            group_created: AtomicBool::new(false),
+            recovered: AtomicBool::new(false),
+        }
+    }
+
+    async fn conn(&self) -> Option<PooledConnection<'_, RedisConnectionManager>> {
+        self.pool
+            .get()
+            .await
+            .map_err(|error| log::error!("Could not get a redis connection: {:?}", error))
+            .ok()
+    }
+
+    async fn read(
+        &self,
+        con: &mut redis::aio::Connection,
+        count: usize,
+    ) -> RedisResult<Vec<Message>> {
+        if !self.group_created.load(Ordering::Relaxed) {
+            let result: RedisResult<()> = con
+                .xgroup_create_mkstream(&self.stream, CONSUMER_GROUP, "0")
+                .await;
+            match result {
+                Err(error) if error.code() != Some("BUSYGROUP") => return Err(error),
+                _ => self.group_created.store(true, Ordering::Relaxed),
+            }
+        }
+
+        let recovering = !self.recovered.load(Ordering::Relaxed);
+        if !recovering {
+            let claimed = self.claim_idle(con, count).await?;
+            if !claimed.is_empty() {
+                return Ok(claimed);
+            }
+        }
+        let mut options = StreamReadOptions::default()
+            .group(CONSUMER_GROUP, webhook_queue_consumer())
+            .count(count);
+        if !recovering {
+            options = options.block(webhook_queue_block_timeout() as usize);
+        }
+        // "0" reads the messages of this consumer that were never acknowledged
+        let id = if recovering { "0" } else { ">" };
+        let reply: Option<StreamReadReply> =
+            con.xread_options(&[&self.stream], &[id], &options).await?;
+
+        let messages: Vec<Message> = reply
+            .map(|reply| reply.keys)
+            .unwrap_or_default()
+            .into_iter()
+            .flat_map(|key| key.ids)
+            .map(to_message)
+            .collect();
+        if recovering && messages.is_empty() {
+            self.recovered.store(true, Ordering::Relaxed);
+        }
+        Ok(messages)
+    }
+
+    // Consumer names come from the HOSTNAME, so the pending messages of an instance that was
+    // replaced would never be read again otherwise
+    async fn claim_idle(
+        &self,
+        con: &mut redis::aio::Connection,
+        count: usize,
+    ) -> RedisResult<Vec<Message>> {
+        let min_idle_time = webhook_queue_claim_idle_time() as usize;
+        let pending: StreamPendingCountReply = con
+            .xpending_count(&self.stream, CONSUMER_GROUP, "-", "+", count)
+            .await?;
+        let ids: Vec<String> = pending
+            .ids
+            .into_iter()
+            .filter(|pending| pending.last_delivered_ms >= min_idle_time)
+            .map(|pending| pending.id)
+            .collect();
+        if ids.is_empty() {
+            return Ok(vec![]);
+        }
+        // Only messages that are still idle are claimed, another consumer may have been faster
+        let claimed: StreamClaimReply = con
+            .xclaim(
+                &self.stream,
+                CONSUMER_GROUP,
+                webhook_queue_consumer(),
+                min_idle_time,
+                &ids[..],
+            )
+            .await?;
+        for message in &claimed.ids {
+            log::info!("Claimed idle webhook {}", message.id);
+        }
+        Ok(claimed.ids.into_iter().map(to_message).collect())
+    }
+}
+
+#[rocket::async_trait]
+impl Queue for RedisQueue {
+    async fn push(&self, body: &str, attempts: u32) -> bool {
+        let mut con = match self.conn().await {
+            Some(con) => con,
+            None => return false,
+        };
+        let result: RedisResult<String> = con
+            .xadd(
+                &self.stream,
+                "*",
+                &[
+                    ("body", body.to_string()),
+                    ("attempts", attempts.to_string()),
+                ],
+            )
+            .await;
+        result
+            .map_err(|error| log::error!("Could not queue webhook: {:?}", error))
+            .is_ok()
+    }
+
+    async fn pop(&self, count: usize) -> Vec<Message> {
+        let result = match self.conn().await {
+            Some(mut con) => self.read(&mut con, count).await.map_err(|error| {
+                log::error!("Could not read webhooks: {:?}", error);
+            }),
+            None => Err(()),
+        };
+        match result {
+            Ok(messages) => messages,
+            Err(_) => {
+                // Don't spin while Redis is unavailable
+                sleep(Duration::from_millis(webhook_queue_retry_delay())).await;
+                vec![]
+            }
+        }
+    }
+
+    async fn ack(&self, id: &str) {
+        if let Some(mut con) = self.conn().await {
+            let result: RedisResult<()> = pipe()
+                .xack(&self.stream, CONSUMER_GROUP, &[id])
+                .xdel(&self.stream, &[id])
+                .query_async(&mut *con)
+                .await;
+            if let Err(error) = result {
+                log::error!("Could not acknowledge webhook {}: {:?}", id, error);
+            }
+        }
+    }
+
+    async fn dead_letter(&self, dead_letter: &DeadLetter) {
+        let value = match serde_json::to_string(dead_letter) {
+            Ok(value) => value,
+            Err(_) => return,
+        };
+        if let Some(mut con) = self.conn().await {
+            let result: RedisResult<()> = pipe()
+                .lpush(&self.dead_letters, value)
+                .ltrim(
+                    &self.dead_letters,
+                    0,
+                    webhook_dead_letter_max_length() as isize - 1,
+                )
+                .query_async(&mut *con)
+                .await;
+            if let Err(error) = result {
+                log::error!(
+                    "Could not store dead letter {}: {:?}",
+                    dead_letter.id,
+                    error
+                );
+            }
+        }
+    }
+
+    async fn dead_letters(&self, count: usize) -> Vec<DeadLetter> {
+        if count == 0 {
+            return vec![];
+        }
+        let mut con = match self.conn().await {
+            Some(con) => con,
+            None => return vec![],
+        };
+        let values: Vec<String> = con
+            .lrange(&self.dead_letters, 0, count as isize - 1)
+            .await
+            .unwrap_or_default();
+        values
+            .iter()
+            .filter_map(|value| serde_json::from_str(value).ok())
+            .collect()
+    }
+}
+
+fn to_message(entry: StreamId) -> Message {
+    Message {
+        body: entry.get("body").unwrap_or_default(),
+        attempts: entry.get("attempts").unwrap_or(0),
+        id: entry.id,
+    }
+}
@@ -0,0 +1,73 @@
+use crate::models::service::admin::DeadLetter;
+use crate::queue::memory::InMemoryQueue;
+use crate::queue::{Message, Queue};
+use rocket::tokio::time::sleep;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[rocket::async_test]
+async fn pop_in_insertion_order() {
+    let queue = InMemoryQueue::new();
+    queue.push("first", 0).await;
+    queue.push("second", 2).await;
+    queue.push("third", 0).await;
+
+    let actual = queue.pop(2).await;
+
+    assert_eq!(
+        actual,
+        vec![
+            Message {
+                id: String::from("1"),
+                body: String::from("first"),
+                attempts: 0,
+            },
+            Message {
+                id: String::from("2"),
+                body: String::from("second"),
+                attempts: 2,
+            },
+        ]
+    );
+    assert_eq!(queue.pop(2).await.len(), 1);
+}
+
+#[rocket::async_test]
+async fn pop_waits_for_push() {
+    let queue = Arc::new(InMemoryQueue::new());
+    let pusher = queue.clone();
+    rocket::tokio::spawn(async move {
+        sleep(Duration::from_millis(50)).await;
+        pusher.push("late", 0).await;
+    });
+
+    let actual = queue.pop(10).await;
+
+    assert_eq!(actual.len(), 1);
+    assert_eq!(actual[0].body, "late");
+}
+
+#[rocket::async_test]
+async fn dead_letters_most_recent_first() {
+    let queue = InMemoryQueue::new();
+    for id in 1..=3 {
+        queue
+            .dead_letter(&DeadLetter {
+                id: id.to_string(),
+                body: String::from("{}"),
+                attempts: 5,
+                reason: String::from("Cache unavailable"),
+                failed_at: 1620000000,
+            })
+            .await;
+    }
+
+    let actual: Vec<String> = queue
+        .dead_letters(2)
+        .await
+        .into_iter()
+        .map(|dead_letter| dead_letter.id)
+        .collect();
+
+    assert_eq!(actual, vec!["3", "2"]);
+}
@@ -1,10 +1,11 @@
 use crate::cache::cache_operations::CacheResponse;
 use crate::cache::Cache;
-use crate::config::base_transaction_service_url;
-use crate::config::{about_cache_duration, webhook_token};
+use crate::config::about_cache_duration;
+use crate::monitoring::tracing::upstream_headers;
 use crate::services::about;
 use crate::utils::context::Context;
 use crate::utils::errors::ApiResult;
+use crate::utils::signatures::{check_legacy_token, Signature};
 use rocket::response::content;
 
 /**
@@ -24,7 +25,7 @@ use rocket::response::content;
  * There are no query parameters for this endpoint
  */
 #[get("/about")]
-pub async fn info(context: Context<'_>) -> ApiResult<content::Json<String>> {
+pub async fn info(context: Context) -> ApiResult<content::Json<String>> {
     CacheResponse::new(context.uri())
         .duration(about_cache_duration())
         .resp_generator(about::get_about)
@@ -34,18 +35,33 @@ pub async fn info(context: Context<'_>) -> ApiResult<content::Json<String>> {
 
 #[doc(hidden)]
 #[get("/about/backbone")]
-pub async fn backbone(context: Context<'_>) -> ApiResult<content::Json<String>> {
-    let url = format!("{}/v1/about/", base_transaction_service_url());
+pub async fn backbone(context: Context) -> ApiResult<content::Json<String>> {
+    let url = format!(
+        "{}/v1/about/",
+        context.chain().base_transaction_service_url()
+    );
     Ok(content::Json(
-        context.client().get(&url).send().await?.text().await?,
+        context
+            .client()
+            .get(&url)
+            .headers(upstream_headers(&url))
+            .send()
+            .await?
+            .text()
+            .await?,
     ))
 }
 
+#[doc(hidden)]
+#[get("/about/redis")]
+pub async fn redis(context: Context, signature: Signature) -> ApiResult<String> {
+    signature.verify(context.cache(), "").await?;
+    Ok(context.cache().info().await.unwrap_or(String::new()))
+}
+
 #[doc(hidden)]
 #[get("/about/redis/<token>")]
-pub fn redis(context: Context<'_>, token: String) -> ApiResult<String> {
-    if token != webhook_token() {
-        bail!("Invalid token");
-    }
-    Ok(context.cache().info().unwrap_or(String::new()))
+pub async fn redis_with_token(context: Context, token: String) -> ApiResult<String> {
+    check_legacy_token(&token)?;
+    Ok(context.cache().info().await.unwrap_or(String::new()))
 }
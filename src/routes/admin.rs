@@ -0,0 +1,151 @@
+use crate::cache::admin;
+use crate::config::Config;
+use crate::queue::{Queue, ServiceQueue};
+use crate::services::settings;
+use crate::utils::context::Context;
+use crate::utils::errors::{parse_request_body, ApiResult};
+use crate::utils::signatures::Signature;
+use rocket::response::content;
+use rocket::State;
+use std::sync::Arc;
+
+/**
+ * `/v1/admin/cache/keys?<limit>` <br />
+ * Returns the [CacheKey](crate::models::service::admin::CacheKey)s matching the
+ * [InvalidationPattern](crate::cache::cache_operations::InvalidationPattern) in the body
+ */
+#[post(
+    "/v1/admin/cache/keys?<limit>",
+    format = "json",
+    data = "<invalidation_pattern>"
+)]
+pub async fn keys(
+    context: Context,
+    signature: Signature,
+    limit: Option<usize>,
+    invalidation_pattern: String,
+) -> ApiResult<content::Json<String>> {
+    signature
+        .verify(context.cache(), &invalidation_pattern)
+        .await?;
+    let keys = admin::list_keys(
+        context.cache(),
+        &parse_request_body(&invalidation_pattern)?,
+        context.chain().chain_id,
+        limit.unwrap_or(100),
+    )
+    .await;
+    Ok(content::Json(serde_json::to_string(&keys)?))
+}
+
+/**
+ * `/v1/admin/cache/entries?<key>&<field>` <br />
+ * Returns the decoded [CachedEntry](crate::models::service::admin::CachedEntry) stored under
+ * `key` (without namespace), `field` selects the entry of a hash such as `dip_ti_<chain_id>`
+ */
+#[get("/v1/admin/cache/entries?<key>&<field>")]
+pub async fn entry(
+    context: Context,
+    signature: Signature,
+    key: String,
+    field: Option<String>,
+) -> ApiResult<content::Json<String>> {
+    signature.verify(context.cache(), "").await?;
+    let entry = admin::get_entry(context.cache(), &key, field.as_deref()).await?;
+    Ok(content::Json(serde_json::to_string(&entry)?))
+}
+
+/**
+ * `/v1/admin/cache/stats` <br />
+ * Returns the [CacheFamilyStats](crate::models::service::admin::CacheFamilyStats) of every key family,
+ * the memory of large families is an estimate
+ */
+#[get("/v1/admin/cache/stats")]
+pub async fn stats(context: Context, signature: Signature) -> ApiResult<content::Json<String>> {
+    signature.verify(context.cache(), "").await?;
+    let stats = admin::family_stats(context.cache()).await;
+    Ok(content::Json(serde_json::to_string(&stats)?))
+}
+
+/**
+ * `/v1/admin/cache/flush?<dry_run>` <br />
+ * Returns a [FlushReport](crate::models::service::admin::FlushReport) of the keys matching the
+ * [InvalidationPattern](crate::cache::cache_operations::InvalidationPattern) in the body, which
+ * are only deleted if `dry_run` is `false`
+ */
+#[post(
+    "/v1/admin/cache/flush?<dry_run>",
+    format = "json",
+    data = "<invalidation_pattern>"
+)]
+pub async fn flush(
+    context: Context,
+    signature: Signature,
+    dry_run: Option<bool>,
+    invalidation_pattern: String,
+) -> ApiResult<content::Json<String>> {
+    signature
+        .verify(context.cache(), &invalidation_pattern)
+        .await?;
+    let report = admin::flush(
+        context.cache(),
+        parse_request_body(&invalidation_pattern)?,
+        context.chain().chain_id,
+        dry_run.unwrap_or(true),
+    )
+    .await?;
+    Ok(content::Json(serde_json::to_string(&report)?))
+}
+
+/**
+ * `/v1/admin/webhooks/dead-letters?<limit>` <br />
+ * Returns the most recent [DeadLetter](crate::models::service::admin::DeadLetter)s, webhooks
+ * that could not be processed
+ */
+#[get("/v1/admin/webhooks/dead-letters?<limit>")]
+pub async fn dead_letters(
+    context: Context,
+    queue: ServiceQueue,
+    signature: Signature,
+    limit: Option<usize>,
+) -> ApiResult<content::Json<String>> {
+    signature.verify(context.cache(), "").await?;
+    let dead_letters = queue.dead_letters(limit.unwrap_or(100)).await;
+    Ok(content::Json(serde_json::to_string(&dead_letters)?))
+}
+
+/**
+ * `/v1/admin/settings` <br />
+ * Returns the [RuntimeSettings](crate::models::service::admin::RuntimeSettings), the feature
+ * flags and cache durations that can be changed without a restart
+ */
+#[get("/v1/admin/settings")]
+pub async fn get_settings(
+    context: Context,
+    base: State<'_, Arc<Config>>,
+    signature: Signature,
+) -> ApiResult<content::Json<String>> {
+    signature.verify(context.cache(), "").await?;
+    let settings = settings::get_settings(context.cache(), base.inner()).await?;
+    Ok(content::Json(serde_json::to_string(&settings)?))
+}
+
+/**
+ * `/v1/admin/settings` <br />
+ * Changes the runtime settings in the body, e.g. `{"feature_flag_nested_decoding": false}`, and
+ * returns the updated [RuntimeSettings](crate::models::service::admin::RuntimeSettings). Settings
+ * set to `null` go back to the configuration of the launch.
+ */
+#[post("/v1/admin/settings", format = "json", data = "<changes>")]
+pub async fn update_settings(
+    context: Context,
+    base: State<'_, Arc<Config>>,
+    signature: Signature,
+    changes: String,
+) -> ApiResult<content::Json<String>> {
+    signature.verify(context.cache(), &changes).await?;
+    let settings =
+        settings::update_settings(context.cache(), base.inner(), parse_request_body(&changes)?)
+            .await?;
+    Ok(content::Json(serde_json::to_string(&settings)?))
+}
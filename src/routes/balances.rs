@@ -1,5 +1,4 @@
 use crate::cache::cache_operations::CacheResponse;
-use crate::config::balances_cache_duration;
 use crate::services::balances::*;
 use crate::utils::context::Context;
 use crate::utils::errors::ApiResult;
@@ -28,14 +27,14 @@ use rocket::response::content;
  */
 #[get("/v1/safes/<safe_address>/balances/<fiat>?<trusted>&<exclude_spam>")]
 pub async fn get_balances(
-    context: Context<'_>,
+    context: Context,
     safe_address: String,
     fiat: String,
     trusted: Option<bool>,
     exclude_spam: Option<bool>,
 ) -> ApiResult<content::Json<String>> {
     CacheResponse::new(context.uri())
-        .duration(balances_cache_duration())
+        .duration(context.chain().balances_cache_duration())
         .resp_generator(|| {
             balances(
                 &context,
@@ -58,7 +57,7 @@ pub async fn get_balances(
  * The entries are sorted alphabetically, with the exception of `USD` and `EUR` being placed in the top of the list in that order.
 */
 #[get("/v1/balances/supported-fiat-codes")]
-pub async fn get_supported_fiat(context: Context<'_>) -> ApiResult<content::Json<String>> {
+pub async fn get_supported_fiat(context: Context) -> ApiResult<content::Json<String>> {
     CacheResponse::new(context.uri())
         .resp_generator(|| fiat_codes(&context))
         .execute(context.cache())
@@ -0,0 +1,56 @@
+use crate::cache::cache_operations::CacheResponse;
+use crate::config::chain_info_cache_duration;
+use crate::services::chains;
+use crate::utils::context::Context;
+use crate::utils::errors::ApiResult;
+use rocket::response::content;
+
+/**
+ * `/v1/chains` <br />
+ * Returns a [Page](crate::models::commons::Page) of [ChainInfo](crate::models::service::chains::ChainInfo)
+ *
+ * # Chains
+ *
+ * Every chain served by this instance of the gateway, in the order of the chain configuration. The endpoints of a chain are available as `/v1/chains/<chain_id>/...`.
+ *
+ * ## Path
+ *
+ * `/v1/chains`
+ *
+ * ## Query parameters
+ *
+ * There are no query parameters for this endpoint
+ */
+#[get("/v1/chains")]
+pub async fn get_chains(context: Context) -> ApiResult<content::Json<String>> {
+    CacheResponse::new(context.uri())
+        .duration(chain_info_cache_duration())
+        .resp_generator(chains::get_chains)
+        .execute(context.cache())
+        .await
+}
+
+/**
+ * `/v1/chains/<chain_id>` <br />
+ * Returns [ChainInfo](crate::models::service::chains::ChainInfo)
+ *
+ * # Chain
+ *
+ * Native currency, block explorer, RPC and features of a chain served by this instance of the gateway.
+ *
+ * ## Path
+ *
+ * `/v1/chains/<chain_id>` returns `404` for chains that are not served
+ *
+ * ## Query parameters
+ *
+ * There are no query parameters for this endpoint
+ */
+#[get("/v1/chains/<chain_id>")]
+pub async fn get_chain(context: Context, chain_id: u64) -> ApiResult<content::Json<String>> {
+    CacheResponse::new(context.uri())
+        .duration(chain_info_cache_duration())
+        .resp_generator(|| chains::get_chain_info(chain_id))
+        .execute(context.cache())
+        .await
+}
@@ -1,5 +1,4 @@
-use crate::cache::cache_operations::RequestCached;
-use crate::config::{base_transaction_service_url, collectibles_request_timeout};
+use crate::services::collectibles;
 use crate::utils::context::Context;
 use crate::utils::errors::ApiResult;
 use rocket::response::content;
@@ -152,23 +151,18 @@ use rocket::response::content;
  */
 #[get("/v1/safes/<safe_address>/collectibles?<trusted>&<exclude_spam>")]
 pub async fn list(
-    context: Context<'_>,
+    context: Context,
     safe_address: String,
     trusted: Option<bool>,
     exclude_spam: Option<bool>,
 ) -> ApiResult<content::Json<String>> {
-    let url = format!(
-        "{}/v1/safes/{}/collectibles/?trusted={}&exclude_spam={}",
-        base_transaction_service_url(),
-        safe_address,
-        trusted.unwrap_or(false),
-        exclude_spam.unwrap_or(true)
-    );
-
     Ok(content::Json(
-        RequestCached::new(url)
-            .request_timeout(collectibles_request_timeout())
-            .execute(context.client(), context.cache())
-            .await?,
+        collectibles::collectibles(
+            &context,
+            &safe_address,
+            trusted.unwrap_or(false),
+            exclude_spam.unwrap_or(true),
+        )
+        .await?,
     ))
 }
@@ -1,12 +1,21 @@
 use crate::cache::cache_operations::CacheResponse;
+use crate::cache::Cache;
+use crate::models::service::health::{CacheStatus, Health};
 use crate::utils::context::Context;
 use crate::utils::errors::ApiResult;
 use rocket::response::content;
 
 #[get("/health")]
-pub async fn health(context: Context<'_>) -> ApiResult<content::Json<String>> {
+pub async fn health(context: Context) -> ApiResult<content::Json<String>> {
+    // Goes through the cache, so that an unreachable backend is detected by this request
     CacheResponse::new(String::from("/health"))
         .resp_generator(|| async { Ok(String::new()) })
         .execute(context.cache())
-        .await
+        .await?;
+    let cache = if context.cache().is_available().await {
+        CacheStatus::Available
+    } else {
+        CacheStatus::Bypassed
+    };
+    Ok(content::Json(serde_json::to_string(&Health { cache })?))
 }
@@ -1,28 +1,57 @@
 use crate::cache::cache_operations::{Invalidate, InvalidationPattern};
-use crate::config::webhook_token;
-use crate::models::backend::webhooks::Payload;
-use crate::services::hooks::invalidate_caches;
+use crate::queue::ServiceQueue;
+use crate::services::hooks::enqueue_webhook;
 use crate::utils::context::Context;
-use crate::utils::errors::ApiResult;
+use crate::utils::errors::{parse_request_body, ApiResult};
+use crate::utils::signatures::{check_legacy_token, Signature};
 use rocket_contrib::json::Json;
 
+#[post("/v1/hook/update", format = "json", data = "<update>")]
+pub async fn update(
+    context: Context,
+    queue: ServiceQueue,
+    signature: Signature,
+    update: String,
+) -> ApiResult<()> {
+    signature.verify(context.cache(), &update).await?;
+    enqueue_webhook(context.cache(), &queue, &update).await
+}
+
 #[post("/v1/hook/update/<token>", format = "json", data = "<update>")]
-pub fn update(context: Context<'_>, token: String, update: Json<Payload>) -> ApiResult<()> {
-    if token != webhook_token() {
-        bail!("Invalid token");
-    }
-    invalidate_caches(context.cache(), &update)
+pub async fn update_with_token(
+    context: Context,
+    queue: ServiceQueue,
+    token: String,
+    update: String,
+) -> ApiResult<()> {
+    check_legacy_token(&token)?;
+    enqueue_webhook(context.cache(), &queue, &update).await
+}
+
+#[post("/v1/flush", format = "json", data = "<invalidation_pattern>")]
+pub async fn flush(
+    context: Context,
+    signature: Signature,
+    invalidation_pattern: String,
+) -> ApiResult<()> {
+    signature
+        .verify(context.cache(), &invalidation_pattern)
+        .await?;
+    Invalidate::new(parse_request_body(&invalidation_pattern)?)
+        .chain_id(context.chain().chain_id)
+        .execute(context.cache())
+        .await
 }
 
 #[post("/v1/flush/<token>", format = "json", data = "<invalidation_pattern>")]
-pub fn flush(
+pub async fn flush_with_token(
     context: Context,
     token: String,
     invalidation_pattern: Json<InvalidationPattern>,
 ) -> ApiResult<()> {
-    if token != webhook_token() {
-        bail!("Invalid token");
-    }
-    Invalidate::new(invalidation_pattern.0).execute(context.cache());
-    Ok(())
+    check_legacy_token(&token)?;
+    Invalidate::new(invalidation_pattern.0)
+        .chain_id(context.chain().chain_id)
+        .execute(context.cache())
+        .await
 }
@@ -0,0 +1,11 @@
+use crate::monitoring::metrics;
+use rocket::response::content;
+
+/**
+ * `/metrics` <br />
+ * Returns all metrics collected by this instance in the Prometheus text format
+ */
+#[get("/metrics")]
+pub fn metrics() -> content::Plain<String> {
+    content::Plain(metrics::render())
+}
@@ -7,14 +7,20 @@ use rocket_contrib::json::JsonValue;
 
 /// # About endpoint
 pub mod about;
+#[doc(hidden)]
+pub mod admin;
 /// # Balance endpoints
 pub mod balances;
+/// # Chain endpoints
+pub mod chains;
 /// # Collectibles endpoint
 pub mod collectibles;
 #[doc(hidden)]
 pub mod health;
 #[doc(hidden)]
 pub mod hooks;
+#[doc(hidden)]
+pub mod metrics;
 /// # Safe endpoints
 pub mod safes;
 
@@ -32,8 +38,11 @@ pub fn active_routes() -> Vec<Route> {
         about::backbone,
         about::info,
         about::redis,
+        about::redis_with_token,
         balances::get_balances,
         balances::get_supported_fiat,
+        chains::get_chains,
+        chains::get_chain,
         collectibles::list,
         safes::safe_info,
         transactions::details,
@@ -42,8 +51,18 @@ pub fn active_routes() -> Vec<Route> {
         transactions::submit_confirmation,
         transactions::propose_transaction,
         hooks::update,
+        hooks::update_with_token,
         hooks::flush,
-        health::health
+        hooks::flush_with_token,
+        admin::keys,
+        admin::entry,
+        admin::stats,
+        admin::flush,
+        admin::dead_letters,
+        admin::get_settings,
+        admin::update_settings,
+        health::health,
+        metrics::metrics
     ]
 }
 
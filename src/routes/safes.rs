@@ -1,4 +1,5 @@
 use crate::cache::cache_operations::CacheResponse;
+use crate::config::response_stale_duration;
 use crate::services::safes::get_safe_info_ex;
 use crate::utils::context::Context;
 use crate::utils::errors::ApiResult;
@@ -9,12 +10,15 @@ use rocket::response::content;
  * Returns [SafeState](crate::models::service::safes::SafeState)
  */
 #[get("/v1/safes/<safe_address>")]
-pub async fn safe_info(
-    context: Context<'_>,
-    safe_address: String,
-) -> ApiResult<content::Json<String>> {
+pub async fn safe_info(context: Context, safe_address: String) -> ApiResult<content::Json<String>> {
+    let cache = context.cache().clone();
     CacheResponse::new(context.uri())
-        .resp_generator(|| get_safe_info_ex(&context, &safe_address))
-        .execute(context.cache())
+        .resp_generator(move || {
+            let context = context.clone();
+            let safe_address = safe_address.clone();
+            async move { get_safe_info_ex(&context, &safe_address).await }
+        })
+        .stale_while_revalidate(response_stale_duration())
+        .execute(&cache)
         .await
 }
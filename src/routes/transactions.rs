@@ -1,4 +1,5 @@
 use crate::cache::cache_operations::CacheResponse;
+use crate::config::response_stale_duration;
 use crate::models::service::transactions::requests::{
     ConfirmationRequest, MultisigTransactionRequest,
 };
@@ -30,7 +31,7 @@ use rocket_contrib::json::JsonError;
  * There aren't any query parameters that can be passed to this endpoint.
  */
 #[get("/v1/transactions/<details_id>")]
-pub async fn details(context: Context<'_>, details_id: String) -> ApiResult<content::Json<String>> {
+pub async fn details(context: Context, details_id: String) -> ApiResult<content::Json<String>> {
     CacheResponse::new(context.uri())
         .resp_generator(|| transactions_details::get_transactions_details(&context, &details_id))
         .execute(context.cache())
@@ -63,7 +64,7 @@ pub async fn details(context: Context<'_>, details_id: String) -> ApiResult<cont
     data = "<tx_confirmation_request>"
 )]
 pub async fn submit_confirmation<'e>(
-    context: Context<'_>,
+    context: Context,
     safe_tx_hash: String,
     tx_confirmation_request: Result<Json<ConfirmationRequest>, JsonError<'e>>,
 ) -> ApiResult<content::Json<String>> {
@@ -115,21 +116,30 @@ pub async fn submit_confirmation<'e>(
  */
 #[get("/v1/safes/<safe_address>/transactions/history?<page_url>&<timezone_offset>")]
 pub async fn history_transactions(
-    context: Context<'_>,
+    context: Context,
     safe_address: String,
     page_url: Option<String>,
     timezone_offset: Option<String>,
 ) -> ApiResult<content::Json<String>> {
+    let cache = context.cache().clone();
     CacheResponse::new(context.uri())
-        .resp_generator(|| {
-            transactions_history::get_history_transactions(
-                &context,
-                &safe_address,
-                &page_url,
-                &timezone_offset,
-            )
+        .resp_generator(move || {
+            let context = context.clone();
+            let safe_address = safe_address.clone();
+            let page_url = page_url.clone();
+            let timezone_offset = timezone_offset.clone();
+            async move {
+                transactions_history::get_history_transactions(
+                    &context,
+                    &safe_address,
+                    &page_url,
+                    &timezone_offset,
+                )
+                .await
+            }
         })
-        .execute(context.cache())
+        .stale_while_revalidate(response_stale_duration())
+        .execute(&cache)
         .await
 }
 
@@ -162,23 +172,32 @@ pub async fn history_transactions(
  */
 #[get("/v1/safes/<safe_address>/transactions/queued?<page_url>&<timezone_offset>&<trusted>")]
 pub async fn queued_transactions(
-    context: Context<'_>,
+    context: Context,
     safe_address: String,
     page_url: Option<String>,
     timezone_offset: Option<String>,
     trusted: Option<bool>,
 ) -> ApiResult<content::Json<String>> {
+    let cache = context.cache().clone();
     CacheResponse::new(context.uri())
-        .resp_generator(|| {
-            transactions_queued::get_queued_transactions(
-                &context,
-                &safe_address,
-                &page_url,
-                &timezone_offset,
-                &trusted,
-            )
+        .resp_generator(move || {
+            let context = context.clone();
+            let safe_address = safe_address.clone();
+            let page_url = page_url.clone();
+            let timezone_offset = timezone_offset.clone();
+            async move {
+                transactions_queued::get_queued_transactions(
+                    &context,
+                    &safe_address,
+                    &page_url,
+                    &timezone_offset,
+                    &trusted,
+                )
+                .await
+            }
         })
-        .execute(context.cache())
+        .stale_while_revalidate(response_stale_duration())
+        .execute(&cache)
         .await
 }
 
@@ -207,7 +226,7 @@ pub async fn queued_transactions(
     data = "<multisig_transaction_request>"
 )]
 pub async fn propose_transaction<'e>(
-    context: Context<'_>,
+    context: Context,
     safe_address: String,
     multisig_transaction_request: Result<Json<MultisigTransactionRequest>, JsonError<'e>>,
 ) -> ApiResult<()> {
@@ -1,7 +1,5 @@
 use crate::cache::cache_operations::RequestCached;
-use crate::config::{
-    balances_cache_duration, balances_request_timeout, base_transaction_service_url,
-};
+use crate::config::balances_request_timeout;
 use crate::models::backend::balances::Balance as BalanceDto;
 use crate::models::service::balances::{Balance, Balances};
 use crate::providers::info::DefaultInfoProvider;
@@ -10,7 +8,7 @@ use crate::utils::errors::ApiResult;
 use std::cmp::Ordering;
 
 pub async fn balances(
-    context: &Context<'_>,
+    context: &Context,
     safe_address: &str,
     fiat: &str,
     trusted: bool,
@@ -18,59 +16,68 @@ pub async fn balances(
 ) -> ApiResult<Balances> {
     let url = format!(
         "{}/v1/safes/{}/balances/usd/?trusted={}&exclude_spam={}",
-        base_transaction_service_url(),
+        context.chain().base_transaction_service_url(),
         safe_address,
         trusted,
         exclude_spam
     );
 
     let body = RequestCached::new(url)
-        .cache_duration(balances_cache_duration())
+        .cache_duration(context.chain().balances_cache_duration())
         .request_timeout(balances_request_timeout())
         .execute(context.client(), context.cache())
         .await?;
     let backend_balances: Vec<BalanceDto> = serde_json::from_str(&body)?;
 
     let info_provider = DefaultInfoProvider::new(&context);
-    let usd_to_fiat = info_provider.exchange_usd_to(fiat).await.unwrap_or(0.0);
+    // Balances are still useful without their fiat values, which are flagged as unavailable
+    let usd_to_fiat = info_provider.exchange_usd_to(fiat).await.ok();
 
     let mut total_fiat = 0.0;
 
     let mut service_balances: Vec<Balance> = backend_balances
         .into_iter()
         .map(|it| {
-            let balance = it.to_balance(usd_to_fiat);
-            total_fiat += balance.fiat_balance.parse::<f64>().unwrap_or(0.0);
+            let balance = it.to_balance(usd_to_fiat, &context.chain().native_currency);
+            total_fiat += fiat_value(&balance);
             balance
         })
         .collect();
 
     service_balances.sort_by(|a, b| {
-        b.fiat_balance
-            .parse::<f64>()
-            .unwrap_or(0.0)
-            .partial_cmp(&a.fiat_balance.parse::<f64>().unwrap_or(0.0))
+        fiat_value(b)
+            .partial_cmp(&fiat_value(a))
             .unwrap_or(Ordering::Equal)
     });
     Ok(Balances {
-        fiat_total: total_fiat.to_string(),
+        fiat_total: usd_to_fiat.map(|_| total_fiat.to_string()),
+        fiat_available: usd_to_fiat.is_some(),
         items: service_balances,
     })
 }
 
-pub async fn fiat_codes(context: &Context<'_>) -> ApiResult<Vec<String>> {
+fn fiat_value(balance: &Balance) -> f64 {
+    balance
+        .fiat_balance
+        .as_ref()
+        .and_then(|fiat_balance| fiat_balance.parse::<f64>().ok())
+        .unwrap_or(0.0)
+}
+
+pub async fn fiat_codes(context: &Context) -> ApiResult<Vec<String>> {
     let info_provider = DefaultInfoProvider::new(&context);
     let mut fiat_codes = info_provider.available_currency_codes().await?;
 
-    let usd_index = fiat_codes.iter().position(|it| it.eq("USD")).unwrap();
-    let eur_index = fiat_codes.iter().position(|it| it.eq("EUR")).unwrap();
-
-    let usd_code = fiat_codes.swap_remove(usd_index);
-    let eur_code = fiat_codes.swap_remove(eur_index);
+    // Rates of the static provider don't have to include EUR
+    let mut output: Vec<String> = ["USD", "EUR"]
+        .iter()
+        .filter_map(|code| {
+            let index = fiat_codes.iter().position(|it| it.eq(code))?;
+            Some(fiat_codes.swap_remove(index))
+        })
+        .collect();
 
     fiat_codes.sort_by(|a, b| a.to_lowercase().cmp(&b.to_lowercase()));
-
-    let mut output = vec![usd_code, eur_code];
     output.append(&mut fiat_codes);
 
     Ok(output)
@@ -0,0 +1,298 @@
+use crate::cache::ServiceCache;
+use crate::config::chains::{default_chain, ChainConfig};
+use crate::config::{
+    cache_warmer_concurrency, cache_warmer_debounce, cache_warmer_enabled, cache_warmer_host,
+};
+use crate::routes::{balances, collectibles, safes, transactions};
+use crate::utils::chains::chain_scoped_path;
+use crate::utils::context::Context;
+use crate::utils::errors::ApiResult;
+use lazy_static::lazy_static;
+use rocket::response::content;
+use rocket::tokio::sync::Semaphore;
+use rocket::tokio::task::JoinHandle;
+use rocket::tokio::time::sleep;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+lazy_static! {
+    // Only set once `start_cache_warmer` was called, so that tests never warm anything
+    static ref CACHE_WARMER: RwLock<Option<Arc<CacheWarmer>>> = RwLock::new(None);
+}
+
+/// Time it took to regenerate the response of `endpoint`, or to find out that it was still cached
+#[derive(Debug, PartialEq)]
+pub struct WarmUp {
+    pub endpoint: &'static str,
+    pub duration: Duration,
+    pub success: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Endpoint {
+    SafeInfo,
+    QueuedTransactions,
+    TransactionHistory,
+    Balances,
+    Collectibles,
+}
+
+impl Endpoint {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Endpoint::SafeInfo => "safe info",
+            Endpoint::QueuedTransactions => "queued transactions",
+            Endpoint::TransactionHistory => "transaction history",
+            Endpoint::Balances => "balances",
+            Endpoint::Collectibles => "collectibles",
+        }
+    }
+}
+
+type Query = &'static [(&'static str, &'static str)];
+
+// Query strings of the first pages as requested by the web and mobile clients. Responses are
+// cached per uri, so only these variants are worth warming.
+const ENDPOINTS: &[(Endpoint, &str, &[Query])] = &[
+    (Endpoint::SafeInfo, "/v1/safes/{}", &[&[]]),
+    (
+        Endpoint::QueuedTransactions,
+        "/v1/safes/{}/transactions/queued",
+        &[&[], &[("timezone_offset", "0")]],
+    ),
+    (
+        Endpoint::TransactionHistory,
+        "/v1/safes/{}/transactions/history",
+        &[&[], &[("timezone_offset", "0")]],
+    ),
+    (
+        Endpoint::Balances,
+        "/v1/safes/{}/balances/USD",
+        &[&[], &[("exclude_spam", "true"), ("trusted", "false")]],
+    ),
+];
+
+/// Request of a client opening a Safe, `uri` as the client sends it
+#[derive(Debug, PartialEq)]
+pub struct WarmUpRequest {
+    pub endpoint: Endpoint,
+    pub uri: String,
+    query: Query,
+}
+
+impl WarmUpRequest {
+    fn param(&self, name: &str) -> Option<String> {
+        self.query
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value.to_string())
+    }
+
+    fn flag(&self, name: &str) -> Option<bool> {
+        self.param(name).and_then(|value| value.parse().ok())
+    }
+}
+
+/// Safes of the default chain are requested with and without the chain in the path
+pub fn warm_up_requests(chain: &'static ChainConfig, safe_address: &str) -> Vec<WarmUpRequest> {
+    let mut requests = vec![];
+    for (endpoint, path, queries) in ENDPOINTS {
+        let path = path.replace("{}", safe_address);
+        let mut paths = vec![chain_scoped_path(chain.chain_id, &path)];
+        if chain.chain_id == default_chain().chain_id {
+            paths.insert(0, path);
+        }
+        for path in &paths {
+            for query in queries.iter() {
+                let uri = if query.is_empty() {
+                    path.to_string()
+                } else {
+                    let params: Vec<String> = query
+                        .iter()
+                        .map(|(key, value)| format!("{}={}", key, value))
+                        .collect();
+                    format!("{}?{}", path, params.join("&"))
+                };
+                requests.push(WarmUpRequest {
+                    endpoint: *endpoint,
+                    uri,
+                    query,
+                });
+            }
+        }
+    }
+    requests
+}
+
+/// Regenerates the responses clients load first when opening a Safe, so that the first request
+/// after a webhook doesn't have to wait for them
+pub struct CacheWarmer {
+    cache: ServiceCache,
+    client: reqwest::Client,
+    host: String,
+    permits: Semaphore,
+    scheduled: Mutex<HashSet<String>>,
+}
+
+impl CacheWarmer {
+    pub fn new(
+        cache: ServiceCache,
+        client: reqwest::Client,
+        host: String,
+        concurrency: usize,
+    ) -> Self {
+        CacheWarmer {
+            cache,
+            client,
+            host,
+            permits: Semaphore::new(concurrency),
+            scheduled: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// `false` if a warm up of the Safe is already scheduled
+    pub fn schedule(&self, chain_id: u64, safe_address: &str) -> bool {
+        self.scheduled
+            .lock()
+            .unwrap()
+            .insert(scheduled_key(chain_id, safe_address))
+    }
+
+    fn unschedule(&self, chain_id: u64, safe_address: &str) {
+        self.scheduled
+            .lock()
+            .unwrap()
+            .remove(&scheduled_key(chain_id, safe_address));
+    }
+
+    /// Warms the Safe once `debounce` has elapsed, `None` if a warm up of it is already scheduled
+    pub fn schedule_warm_up(
+        self: Arc<Self>,
+        chain: &'static ChainConfig,
+        safe_address: &str,
+        debounce: Duration,
+    ) -> Option<JoinHandle<Vec<WarmUp>>> {
+        if !self.schedule(chain.chain_id, safe_address) {
+            return None;
+        }
+        let safe_address = safe_address.to_string();
+        Some(rocket::tokio::spawn(async move {
+            sleep(debounce).await;
+            self.unschedule(chain.chain_id, &safe_address);
+            let _permit = self.permits.acquire().await;
+            self.warm_up(chain, &safe_address).await
+        }))
+    }
+
+    // The route handlers are called directly so that the same keys, durations and generators
+    // are used as for client requests. Responses that are still cached are left as they are.
+    pub async fn warm_up(&self, chain: &'static ChainConfig, safe_address: &str) -> Vec<WarmUp> {
+        let mut warm_ups = vec![];
+        for request in warm_up_requests(chain, safe_address) {
+            let started = Instant::now();
+            let result = self.request(chain, safe_address, &request).await;
+            if let Err(error) = &result {
+                log::warn!(
+                    "Could not warm {} on chain {}: {:?}",
+                    request.uri,
+                    chain.chain_id,
+                    error
+                );
+            }
+            warm_ups.push(WarmUp {
+                endpoint: request.endpoint.name(),
+                duration: started.elapsed(),
+                success: result.is_ok(),
+            });
+        }
+        warm_ups
+    }
+
+    async fn request(
+        &self,
+        chain: &'static ChainConfig,
+        safe_address: &str,
+        request: &WarmUpRequest,
+    ) -> ApiResult<content::Json<String>> {
+        let context = Context::new(
+            chain,
+            request.uri.to_string(),
+            Some(self.host.to_string()),
+            self.cache.clone(),
+            self.client.clone(),
+        );
+        let address = safe_address.to_string();
+        match request.endpoint {
+            Endpoint::SafeInfo => safes::safe_info(context, address).await,
+            Endpoint::QueuedTransactions => {
+                transactions::queued_transactions(
+                    context,
+                    address,
+                    None,
+                    request.param("timezone_offset"),
+                    request.flag("trusted"),
+                )
+                .await
+            }
+            Endpoint::TransactionHistory => {
+                transactions::history_transactions(
+                    context,
+                    address,
+                    None,
+                    request.param("timezone_offset"),
+                )
+                .await
+            }
+            Endpoint::Balances => {
+                balances::get_balances(
+                    context,
+                    address,
+                    String::from("USD"),
+                    request.flag("trusted"),
+                    request.flag("exclude_spam"),
+                )
+                .await
+            }
+            Endpoint::Collectibles => {
+                collectibles::list(
+                    context,
+                    address,
+                    request.flag("trusted"),
+                    request.flag("exclude_spam"),
+                )
+                .await
+            }
+        }
+    }
+}
+
+pub fn start_cache_warmer(cache: ServiceCache, client: reqwest::Client) {
+    if cache_warmer_enabled() {
+        let warmer = CacheWarmer::new(
+            cache,
+            client,
+            cache_warmer_host(),
+            cache_warmer_concurrency(),
+        );
+        *CACHE_WARMER.write().unwrap() = Some(Arc::new(warmer));
+    }
+}
+
+/// The Safe is warmed `CACHE_WARMER_DEBOUNCE` milliseconds after the first call, later calls
+/// for the same Safe of `chain` until then are ignored
+pub fn schedule_warm_up(chain: &'static ChainConfig, safe_address: &str) {
+    let warmer = match CACHE_WARMER.read().unwrap().as_ref() {
+        Some(warmer) => warmer.clone(),
+        None => return,
+    };
+    warmer.schedule_warm_up(
+        chain,
+        safe_address,
+        Duration::from_millis(cache_warmer_debounce()),
+    );
+}
+
+fn scheduled_key(chain_id: u64, safe_address: &str) -> String {
+    format!("{}:{}", chain_id, safe_address.to_lowercase())
+}
@@ -0,0 +1,18 @@
+use crate::config::chains::{chain, chains};
+use crate::models::commons::Page;
+use crate::models::service::chains::ChainInfo;
+use crate::utils::errors::ApiResult;
+
+pub async fn get_chains() -> ApiResult<Page<ChainInfo>> {
+    Ok(Page {
+        next: None,
+        previous: None,
+        results: chains().iter().map(|chain| chain.to_chain_info()).collect(),
+    })
+}
+
+pub async fn get_chain_info(chain_id: u64) -> ApiResult<ChainInfo> {
+    chain(chain_id)
+        .map(|chain| chain.to_chain_info())
+        .ok_or(client_error!(404, "Chain not found"))
+}
@@ -0,0 +1,24 @@
+use crate::cache::cache_operations::RequestCached;
+use crate::config::collectibles_request_timeout;
+use crate::utils::context::Context;
+use crate::utils::errors::ApiResult;
+
+/// Response of the transaction service as is
+pub async fn collectibles(
+    context: &Context,
+    safe_address: &str,
+    trusted: bool,
+    exclude_spam: bool,
+) -> ApiResult<String> {
+    let url = format!(
+        "{}/v1/safes/{}/collectibles/?trusted={}&exclude_spam={}",
+        context.chain().base_transaction_service_url(),
+        safe_address,
+        trusted,
+        exclude_spam
+    );
+    RequestCached::new(url)
+        .request_timeout(collectibles_request_timeout())
+        .execute(context.client(), context.cache())
+        .await
+}
@@ -1,37 +1,216 @@
 use crate::cache::cache_operations::{Invalidate, InvalidationPattern, InvalidationScope};
-use crate::cache::Cache;
+use crate::cache::{namespaced, Cache, ServiceCache};
+use crate::config::chains::{chain, default_chain};
+use crate::config::{
+    webhook_dedupe_duration, webhook_queue_batch_size, webhook_queue_enabled,
+    webhook_queue_max_attempts, webhook_queue_retry_delay,
+};
 use crate::models::backend::webhooks::{Payload, PayloadDetails};
-use crate::utils::errors::ApiResult;
+use crate::models::service::admin::DeadLetter;
+use crate::monitoring::services::{report_webhook, report_webhook_queue};
+use crate::queue::{Message, Queue, ServiceQueue};
+use crate::services::cache_warmer::schedule_warm_up;
+use crate::utils::errors::{parse_request_body, ApiResult};
+use chrono::Utc;
+use rocket::tokio::time::sleep;
+use sha2::{Digest, Sha256};
+use std::time::Duration;
 
-pub fn invalidate_caches(cache: &impl Cache, payload: &Payload) -> ApiResult<()> {
-    Invalidate::new(InvalidationPattern::Any(
-        InvalidationScope::Both,
-        payload.address.to_owned(),
+const WEBHOOK_DEDUPE_PREFIX: &'static str = "c_hook";
+
+/// Queues the webhook so that the sender doesn't wait for the invalidation, identical payloads
+/// are only queued once until the first one was processed
+pub async fn enqueue_webhook(cache: &impl Cache, queue: &impl Queue, body: &str) -> ApiResult<()> {
+    let payload: Payload = parse_request_body(body)?;
+    if !webhook_queue_enabled() {
+        return invalidate_caches(cache, &payload).await;
+    }
+    let dedupe_key = dedupe_key(&payload);
+    if !cache
+        .acquire_lock(&dedupe_key, "queued", webhook_dedupe_duration() * 1000)
+        .await
+    {
+        report_webhook_queue("duplicate");
+        return Ok(());
+    }
+    if queue.push(body, 0).await {
+        report_webhook_queue("queued");
+        Ok(())
+    } else {
+        // Senders don't retry, so the webhook is better handled late than not at all
+        cache.invalidate(&dedupe_key).await;
+        invalidate_caches(cache, &payload).await
+    }
+}
+
+// Of the event the payload describes, so that formatting, field order and fields that don't
+// change the invalidation don't matter. Instances running different builds during a deployment
+// have to agree on the key.
+pub(super) fn dedupe_key(payload: &Payload) -> String {
+    let chain_id = payload
+        .chain_id
+        .clone()
+        .unwrap_or_else(|| default_chain().chain_id.to_string());
+    let details = payload.details.as_ref();
+    let event = format!(
+        "{}:{}:{}:{}",
+        chain_id,
+        payload.address.to_lowercase(),
+        details.map_or("NONE", |details| details.event_type()),
+        details
+            .and_then(|details| details.transaction_hash())
+            .unwrap_or_default()
+            .to_lowercase()
+    );
+    namespaced(&format!(
+        "{}_{}",
+        WEBHOOK_DEDUPE_PREFIX,
+        hex::encode(Sha256::digest(event.as_bytes()))
     ))
-    .execute(cache);
-    payload.details.as_ref().map(|d| match d {
-        PayloadDetails::NewConfirmation(data) => {
-            Invalidate::new(InvalidationPattern::Any(
-                InvalidationScope::Both,
-                String::from(&data.safe_tx_hash),
-            ))
-            .execute(cache);
+}
+
+pub fn spawn_webhook_worker(cache: ServiceCache, queue: ServiceQueue) {
+    rocket::tokio::spawn(async move {
+        loop {
+            for message in queue.pop(webhook_queue_batch_size()).await {
+                if !process_webhook(&cache, &queue, &message).await {
+                    sleep(Duration::from_millis(webhook_queue_retry_delay())).await;
+                }
+            }
         }
-        PayloadDetails::ExecutedMultisigTransaction(data) => {
-            Invalidate::new(InvalidationPattern::Any(
-                InvalidationScope::Both,
-                String::from(&data.safe_tx_hash),
-            ))
-            .execute(cache);
+    });
+}
+
+/// `false` if the webhook has to be retried. Webhooks that can't be parsed, or failed
+/// `WEBHOOK_QUEUE_MAX_ATTEMPTS` times, are moved to the dead-letter list. Retries while the cache
+/// is unavailable don't count as attempts, so that an outage doesn't dead-letter every webhook.
+pub async fn process_webhook(cache: &impl Cache, queue: &impl Queue, message: &Message) -> bool {
+    let payload: Payload = match serde_json::from_str(&message.body) {
+        Ok(payload) => payload,
+        Err(error) => {
+            move_to_dead_letters(queue, message, &error.to_string()).await;
+            return true;
         }
-        PayloadDetails::PendingMultisigTransaction(data) => {
-            Invalidate::new(InvalidationPattern::Any(
-                InvalidationScope::Both,
-                String::from(&data.safe_tx_hash),
-            ))
-            .execute(cache);
+    };
+    if !cache.is_available().await {
+        log::warn!("Retrying webhook {}: Cache unavailable", message.id);
+        if queue.push(&message.body, message.attempts).await {
+            report_webhook_queue("retried");
+            queue.ack(&message.id).await;
         }
-        _ => {}
-    });
+        return false;
+    }
+    let result = invalidate_caches(cache, &payload)
+        .await
+        .map_err(|error| error.details.message.unwrap_or_default());
+    let processed = match result {
+        Ok(()) => {
+            report_webhook_queue("processed");
+            true
+        }
+        Err(reason) if message.attempts + 1 >= webhook_queue_max_attempts() => {
+            move_to_dead_letters(queue, message, &reason).await;
+            true
+        }
+        Err(reason) => {
+            log::warn!("Retrying webhook {}: {}", message.id, reason);
+            // Left unacknowledged if it can't be queued again, to be retried after a restart
+            if !queue.push(&message.body, message.attempts + 1).await {
+                return false;
+            }
+            report_webhook_queue("retried");
+            false
+        }
+    };
+    if processed {
+        cache.invalidate(&dedupe_key(&payload)).await;
+    }
+    queue.ack(&message.id).await;
+    processed
+}
+
+async fn move_to_dead_letters(queue: &impl Queue, message: &Message, reason: &str) {
+    log::error!("Could not process webhook {}: {}", message.id, reason);
+    queue
+        .dead_letter(&DeadLetter {
+            id: message.id.to_string(),
+            body: message.body.to_string(),
+            attempts: message.attempts + 1,
+            reason: reason.to_string(),
+            failed_at: Utc::now().timestamp(),
+        })
+        .await;
+    report_webhook_queue("dead_lettered");
+}
+
+pub async fn invalidate_caches(cache: &impl Cache, payload: &Payload) -> ApiResult<()> {
+    report_webhook(
+        payload
+            .details
+            .as_ref()
+            .map_or("NONE", |details| details.event_type()),
+    );
+    let chain = match &payload.chain_id {
+        Some(chain_id) => match chain_id.parse().ok().and_then(chain) {
+            Some(chain) => chain,
+            // Nothing is cached for chains that this gateway doesn't serve
+            None => return Ok(()),
+        },
+        None => default_chain(),
+    };
+    for pattern in invalidation_patterns(&payload.address, payload.details.as_ref()) {
+        Invalidate::new(pattern)
+            .chain_id(chain.chain_id)
+            .execute(cache)
+            .await?;
+    }
+    schedule_warm_up(chain, &payload.address);
     Ok(())
 }
+
+/// Caches affected by an event of the Safe at `address`, on the chain of the event. Events we
+/// don't know about invalidate everything related to the Safe.
+fn invalidation_patterns(
+    address: &str,
+    details: Option<&PayloadDetails>,
+) -> Vec<InvalidationPattern> {
+    let address = address.to_owned();
+    match details {
+        // Only the queue changes until a transaction is executed
+        Some(PayloadDetails::NewConfirmation(data)) => vec![
+            InvalidationPattern::SafeInfo(InvalidationScope::Requests, address.to_owned()),
+            InvalidationPattern::SafeInfo(InvalidationScope::Responses, address.to_owned()),
+            InvalidationPattern::QueuedTransactions(InvalidationScope::Both, address),
+            InvalidationPattern::Any(InvalidationScope::Both, data.safe_tx_hash.to_owned()),
+        ],
+        Some(PayloadDetails::PendingMultisigTransaction(data)) => vec![
+            InvalidationPattern::SafeInfo(InvalidationScope::Requests, address.to_owned()),
+            InvalidationPattern::SafeInfo(InvalidationScope::Responses, address.to_owned()),
+            InvalidationPattern::QueuedTransactions(InvalidationScope::Both, address),
+            InvalidationPattern::Any(InvalidationScope::Both, data.safe_tx_hash.to_owned()),
+        ],
+        // The executed transaction could have changed the configuration and any of the assets
+        Some(PayloadDetails::ExecutedMultisigTransaction(data)) => vec![
+            InvalidationPattern::Any(InvalidationScope::Both, address),
+            InvalidationPattern::Any(InvalidationScope::Both, data.safe_tx_hash.to_owned()),
+        ],
+        Some(PayloadDetails::IncomingEther(_)) => vec![
+            InvalidationPattern::SafeInfo(InvalidationScope::Requests, address.to_owned()),
+            InvalidationPattern::SafeInfo(InvalidationScope::Responses, address.to_owned()),
+            InvalidationPattern::Balances(InvalidationScope::Both, address.to_owned()),
+            InvalidationPattern::Transfers(InvalidationScope::Both, address.to_owned()),
+            InvalidationPattern::Transactions(InvalidationScope::Both, address),
+        ],
+        Some(PayloadDetails::IncomingToken(_)) => vec![
+            InvalidationPattern::SafeInfo(InvalidationScope::Requests, address.to_owned()),
+            InvalidationPattern::SafeInfo(InvalidationScope::Responses, address.to_owned()),
+            InvalidationPattern::Balances(InvalidationScope::Both, address.to_owned()),
+            InvalidationPattern::Collectibles(InvalidationScope::Both, address.to_owned()),
+            InvalidationPattern::Transfers(InvalidationScope::Both, address.to_owned()),
+            InvalidationPattern::Transactions(InvalidationScope::Both, address),
+        ],
+        Some(PayloadDetails::Unknown) | None => {
+            vec![InvalidationPattern::Any(InvalidationScope::Both, address)]
+        }
+    }
+}
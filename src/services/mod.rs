@@ -3,8 +3,12 @@ use std::cmp::max;
 
 pub mod about;
 pub mod balances;
+pub mod cache_warmer;
+pub mod chains;
+pub mod collectibles;
 pub mod hooks;
 pub mod safes;
+pub mod settings;
 pub mod transactions_details;
 pub mod transactions_history;
 pub mod transactions_proposal;
@@ -1,5 +1,5 @@
 use crate::cache::cache_operations::RequestCached;
-use crate::config::{base_transaction_service_url, transaction_request_timeout};
+use crate::config::transaction_request_timeout;
 use crate::models::backend::transactions::{MultisigTransaction, Transaction};
 use crate::models::backend::transfers::Transfer;
 use crate::models::commons::Page;
@@ -11,10 +11,7 @@ use chrono::Utc;
 
 // We use Utc::now().timestamp() as the fallback value so that we don't block clients from reloading
 // as returning always 0, and the clients invalidating on value changes, would prevent reloading
-pub async fn get_safe_info_ex(
-    context: &Context<'_>,
-    safe_address: &String,
-) -> ApiResult<SafeState> {
+pub async fn get_safe_info_ex(context: &Context, safe_address: &String) -> ApiResult<SafeState> {
     let info_provider = DefaultInfoProvider::new(context);
     let safe_info = info_provider.safe_info(safe_address).await?;
     let safe_info_ex = safe_info.to_safe_info_ex(&info_provider).await;
@@ -40,12 +37,12 @@ pub async fn get_safe_info_ex(
     Ok(safe_state)
 }
 
-async fn get_last_collectible(context: &Context<'_>, safe_address: &String) -> ApiResult<i64> {
+async fn get_last_collectible(context: &Context, safe_address: &String) -> ApiResult<i64> {
     let url = format!(
         "{}/v1/safes/{}/transfers/?\
         &erc721=true\
         &limit=1",
-        base_transaction_service_url(),
+        context.chain().base_transaction_service_url(),
         safe_address,
     );
 
@@ -68,14 +65,14 @@ async fn get_last_collectible(context: &Context<'_>, safe_address: &String) -> A
         .ok_or(api_error!("Couldn't get tx timestamps"))
 }
 
-async fn get_last_queued_tx(context: &Context<'_>, safe_address: &String) -> ApiResult<i64> {
+async fn get_last_queued_tx(context: &Context, safe_address: &String) -> ApiResult<i64> {
     let url = format!(
         "{}/v1/safes/{}/multisig-transactions/?\
         &ordering=-modified\
         &executed=false\
         &trusted=true\
         &limit=1",
-        base_transaction_service_url(),
+        context.chain().base_transaction_service_url(),
         safe_address,
     );
 
@@ -94,13 +91,13 @@ async fn get_last_queued_tx(context: &Context<'_>, safe_address: &String) -> Api
         .ok_or(api_error!("Couldn't get tx timestamps"))
 }
 
-async fn get_last_history_tx(context: &Context<'_>, safe_address: &String) -> ApiResult<i64> {
+async fn get_last_history_tx(context: &Context, safe_address: &String) -> ApiResult<i64> {
     let url = format!(
         "{}/v1/safes/{}/all-transactions/?\
         &ordering=executionDate
         &queued=false\
         &executed=true",
-        base_transaction_service_url(),
+        context.chain().base_transaction_service_url(),
         safe_address
     );
 
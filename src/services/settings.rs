@@ -0,0 +1,212 @@
+use crate::cache::{namespaced, Cache, ServiceCache};
+use crate::config::chains::{chains, ChainConfig};
+use crate::config::{config, init_config, Config, RUNTIME_SETTINGS};
+use crate::models::service::admin::RuntimeSettings;
+use crate::utils::errors::{ApiError, ApiResult};
+use rocket::tokio::time::sleep;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+const SETTINGS_KEY: &'static str = "settings";
+const OVERRIDES_FIELD: &'static str = "overrides";
+
+/// Values of [RUNTIME_SETTINGS] by lower case name, as they would be set in the environment
+pub type Overrides = BTreeMap<String, String>;
+
+/// Re-reads the runtime settings every `RUNTIME_SETTINGS_REFRESH_INTERVAL` seconds. `base` is the
+/// configuration of the launch, settings without an override go back to its values.
+pub fn spawn_settings_refresher(cache: ServiceCache, base: Arc<Config>) {
+    rocket::tokio::spawn(async move {
+        loop {
+            refresh_settings(&cache, &base).await;
+            sleep(Duration::from_secs(base.runtime_settings_refresh_interval)).await;
+        }
+    });
+}
+
+/// Settings that can't be read, e.g. while the cache is unavailable, keep their current values
+pub async fn refresh_settings(cache: &impl Cache, base: &Config) {
+    let overrides = match load_overrides(cache, &base.runtime_settings_path).await {
+        Ok(overrides) => overrides,
+        Err(error) => {
+            log::warn!("Could not read the runtime settings: {}", error);
+            return;
+        }
+    };
+    match base.with_settings(&overrides) {
+        Ok(updated) => apply(updated, &overrides),
+        Err(errors) => log::warn!("Ignoring the runtime settings: {}", errors),
+    }
+}
+
+pub async fn get_settings(cache: &impl Cache, base: &Config) -> ApiResult<RuntimeSettings> {
+    let path = &base.runtime_settings_path;
+    let overrides = load_overrides(cache, path)
+        .await
+        .map_err(|error| ApiError::new_from_message_with_code(503, error))?;
+    runtime_settings(path, overrides, &config())
+}
+
+/// `changes` set to `null` go back to the configuration of the launch. The settings are applied
+/// right away, other instances pick them up with their next refresh. Cache durations that every
+/// chain sets in its `cacheDurations` are rejected, as they wouldn't have any effect.
+pub async fn update_settings(
+    cache: &impl Cache,
+    base: &Config,
+    changes: BTreeMap<String, serde_json::Value>,
+) -> ApiResult<RuntimeSettings> {
+    let path = &base.runtime_settings_path;
+    if !path.is_empty() {
+        return Err(client_error!(
+            409,
+            "Runtime settings are read from RUNTIME_SETTINGS_PATH"
+        ));
+    }
+    check_not_shadowed(&changes, chains())?;
+    let current = load_overrides(cache, path)
+        .await
+        .map_err(|error| ApiError::new_from_message_with_code(503, error))?;
+    let overrides = merge_overrides(current, changes);
+    let updated = base
+        .with_settings(&overrides)
+        .map_err(|errors| ApiError::new_from_message_with_code(422, errors.to_string()))?;
+    // Applying settings that other instances never see would leave them diverging silently
+    let stored = cache
+        .insert_in_hash(
+            &namespaced(SETTINGS_KEY),
+            OVERRIDES_FIELD,
+            &serde_json::to_string(&overrides)?,
+        )
+        .await;
+    if !stored {
+        return Err(client_error!(503, "Could not store the runtime settings"));
+    }
+    let settings = runtime_settings(path, overrides.clone(), &updated)?;
+    apply(updated, &overrides);
+    Ok(settings)
+}
+
+/// From the file at `path` (`RUNTIME_SETTINGS_PATH`) if set, otherwise from the cache backend
+pub async fn load_overrides(cache: &impl Cache, path: &str) -> Result<Overrides, String> {
+    if !path.is_empty() {
+        let file = std::fs::read_to_string(&path)
+            .map_err(|error| format!("Could not read {}: {}", path, error))?;
+        return parse_settings_file(&file);
+    }
+    // A missing entry only means that no setting was changed if the cache could be asked
+    if !cache.is_available().await {
+        return Err(String::from("Cache unavailable"));
+    }
+    match cache
+        .get_from_hash(&namespaced(SETTINGS_KEY), OVERRIDES_FIELD)
+        .await
+    {
+        Some(overrides) => serde_json::from_str(&overrides).map_err(|error| error.to_string()),
+        None => Ok(Overrides::new()),
+    }
+}
+
+/// Flat TOML table, like the `CONFIG_FILE`
+pub fn parse_settings_file(file: &str) -> Result<Overrides, String> {
+    let table: toml::value::Table = toml::from_str(file).map_err(|error| error.to_string())?;
+    Ok(table
+        .into_iter()
+        .map(|(key, value)| match value {
+            toml::Value::String(value) => (key.to_lowercase(), value),
+            value => (key.to_lowercase(), value.to_string()),
+        })
+        .collect())
+}
+
+pub fn merge_overrides(
+    mut overrides: Overrides,
+    changes: BTreeMap<String, serde_json::Value>,
+) -> Overrides {
+    for (key, value) in changes {
+        let key = key.to_lowercase();
+        match value {
+            serde_json::Value::Null => overrides.remove(&key),
+            serde_json::Value::String(value) => overrides.insert(key, value),
+            value => overrides.insert(key, value.to_string()),
+        };
+    }
+    overrides
+}
+
+/// Cache durations that chains set themselves, by setting and chain id
+pub fn chain_values(chains: &[ChainConfig]) -> BTreeMap<String, BTreeMap<String, usize>> {
+    let mut values: BTreeMap<String, BTreeMap<String, usize>> = BTreeMap::new();
+    for chain in chains {
+        for (setting, duration) in chain.cache_durations.settings().iter() {
+            if let Some(duration) = duration {
+                values
+                    .entry(setting.to_string())
+                    .or_default()
+                    .insert(chain.chain_id.to_string(), *duration);
+            }
+        }
+    }
+    values
+}
+
+// A setting that every chain sets itself wouldn't change anything
+pub(super) fn check_not_shadowed(
+    changes: &BTreeMap<String, serde_json::Value>,
+    chains: &[ChainConfig],
+) -> ApiResult<()> {
+    let chain_values = chain_values(chains);
+    let shadowed: Vec<String> = changes
+        .iter()
+        .filter(|(_, value)| !value.is_null())
+        .map(|(setting, _)| setting.to_lowercase())
+        .filter(|setting| {
+            chain_values
+                .get(setting)
+                .map_or(false, |values| values.len() == chains.len())
+        })
+        .collect();
+    if shadowed.is_empty() {
+        Ok(())
+    } else {
+        Err(ApiError::new_from_message_with_code(
+            422,
+            format!(
+                "Set by the cacheDurations of every chain: {}",
+                shadowed.join(", ")
+            ),
+        ))
+    }
+}
+
+fn apply(updated: Config, overrides: &Overrides) {
+    if *config() != updated {
+        log::info!("Runtime settings changed, overrides: {:?}", overrides);
+        init_config(Arc::new(updated));
+    }
+}
+
+/// `values` are read from `current`, the configuration with the overrides applied
+fn runtime_settings(
+    path: &str,
+    overrides: Overrides,
+    current: &Config,
+) -> ApiResult<RuntimeSettings> {
+    let values = match serde_json::to_value(current)? {
+        serde_json::Value::Object(values) => values
+            .into_iter()
+            .filter(|(key, _)| RUNTIME_SETTINGS.contains(&key.as_str()))
+            .collect(),
+        _ => BTreeMap::new(),
+    };
+    Ok(RuntimeSettings {
+        source: if path.is_empty() {
+            String::from("cache")
+        } else {
+            path.to_string()
+        },
+        overrides,
+        values,
+        chain_values: chain_values(chains()),
+    })
+}
@@ -0,0 +1,56 @@
+use crate::cache::memory::InMemoryCache;
+use crate::cache::{namespaced, Cache, ServiceCache};
+use crate::config::chains::default_chain;
+use crate::config::{base_exchange_api_url, coinbase_exchange_api_url};
+use crate::json::BALANCE_ETHER;
+use crate::services::balances::balances;
+use crate::utils::context::Context;
+use std::sync::Arc;
+
+const SAFE_ADDRESS: &'static str = "0x1230B3d59858296A31053C1b8562Ecf89A2f888b";
+
+// Upstream responses as cached by `RequestCached`, so that no request is sent
+async fn cache_response(cache: &impl Cache, url: &str, status: u16, body: &str) {
+    cache
+        .create(
+            &namespaced(&format!("c_reqs_{}", url)),
+            &format!("{};{}", status, body),
+            60,
+        )
+        .await;
+}
+
+#[rocket::async_test]
+async fn balances_without_exchange_rates() {
+    let cache = ServiceCache::new(Arc::new(InMemoryCache::new(10)));
+    let context = Context::new(
+        default_chain(),
+        format!("/v1/safes/{}/balances/EUR", SAFE_ADDRESS),
+        None,
+        cache.clone(),
+        reqwest::Client::new(),
+    );
+    let balances_url = format!(
+        "{}/v1/safes/{}/balances/usd/?trusted=false&exclude_spam=true",
+        default_chain().base_transaction_service_url(),
+        SAFE_ADDRESS
+    );
+    cache_response(&cache, &balances_url, 200, &format!("[{}]", BALANCE_ETHER)).await;
+    for provider_url in &[base_exchange_api_url(), coinbase_exchange_api_url()] {
+        cache_response(&cache, provider_url, 500, "Unavailable").await;
+    }
+
+    let actual = balances(&context, SAFE_ADDRESS, "EUR", false, true)
+        .await
+        .unwrap();
+
+    assert!(!actual.fiat_available);
+    assert_eq!(actual.fiat_total, None);
+    assert_eq!(actual.items.len(), 1);
+    assert_eq!(actual.items[0].balance, "7457594371050000001");
+    assert_eq!(actual.items[0].fiat_balance, None);
+    assert_eq!(actual.items[0].fiat_conversion, None);
+    let json = serde_json::to_value(&actual).unwrap();
+    assert_eq!(json["fiatTotal"], serde_json::Value::Null);
+    assert_eq!(json["items"][0]["fiatBalance"], serde_json::Value::Null);
+}
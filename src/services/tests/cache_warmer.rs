@@ -0,0 +1,153 @@
+use crate::cache::memory::InMemoryCache;
+use crate::cache::{namespaced, response_key, Cache, ServiceCache};
+use crate::config::chains::default_chain;
+use crate::config::{base_exchange_api_url, coinbase_exchange_api_url};
+use crate::json::BALANCE_ETHER;
+use crate::services::cache_warmer::{warm_up_requests, CacheWarmer, Endpoint};
+use std::sync::Arc;
+use std::time::Duration;
+
+const SAFE_ADDRESS: &'static str = "0x1230B3d59858296A31053C1b8562Ecf89A2f888b";
+
+fn setup_warmer(cache: &ServiceCache) -> CacheWarmer {
+    CacheWarmer::new(
+        cache.clone(),
+        reqwest::Client::new(),
+        String::from("localhost:8000"),
+        5,
+    )
+}
+
+fn setup_cache() -> ServiceCache {
+    ServiceCache::new(Arc::new(InMemoryCache::new(100)))
+}
+
+// Responses that are still cached are not regenerated, so that no request is sent
+async fn cache_responses(cache: &impl Cache, endpoints: &[Endpoint]) {
+    for request in warm_up_requests(default_chain(), SAFE_ADDRESS) {
+        if endpoints.contains(&request.endpoint) {
+            cache.create(&response_key(&request.uri), "{}", 600).await;
+        }
+    }
+}
+
+// Upstream responses as cached by `RequestCached`
+async fn cache_request(cache: &impl Cache, url: &str, status: u16, body: &str) {
+    cache
+        .create(
+            &namespaced(&format!("c_reqs_{}", url)),
+            &format!("{};{}", status, body),
+            60,
+        )
+        .await;
+}
+
+#[test]
+fn schedule_once_per_safe() {
+    let warmer = setup_warmer(&setup_cache());
+
+    assert!(warmer.schedule(1, "0x1230B3d59858296A31053C1b8562Ecf89A2f888b"));
+    assert!(!warmer.schedule(1, "0x1230B3d59858296A31053C1b8562Ecf89A2f888b"));
+    assert!(!warmer.schedule(1, "0x1230b3d59858296a31053c1b8562ecf89a2f888b"));
+    assert!(warmer.schedule(1, "0x65F8236309e5A99Ff0d129d04E486EBCE20DC7B0"));
+    assert!(warmer.schedule(4, "0x1230B3d59858296A31053C1b8562Ecf89A2f888b"));
+}
+
+#[test]
+fn warm_up_requests_as_sent_by_clients() {
+    let chain_id = default_chain().chain_id;
+    let balances: Vec<String> = warm_up_requests(default_chain(), SAFE_ADDRESS)
+        .into_iter()
+        .filter(|request| request.endpoint == Endpoint::Balances)
+        .map(|request| request.uri)
+        .collect();
+
+    assert_eq!(
+        balances,
+        vec![
+            format!("/v1/safes/{}/balances/USD", SAFE_ADDRESS),
+            format!(
+                "/v1/safes/{}/balances/USD?exclude_spam=true&trusted=false",
+                SAFE_ADDRESS
+            ),
+            format!(
+                "/v1/chains/{}/safes/{}/balances/USD",
+                chain_id, SAFE_ADDRESS
+            ),
+            format!(
+                "/v1/chains/{}/safes/{}/balances/USD?exclude_spam=true&trusted=false",
+                chain_id, SAFE_ADDRESS
+            ),
+        ]
+    );
+}
+
+#[rocket::async_test]
+async fn warm_up_fills_cache() {
+    let cache = setup_cache();
+    cache_responses(
+        &cache,
+        &[
+            Endpoint::SafeInfo,
+            Endpoint::QueuedTransactions,
+            Endpoint::TransactionHistory,
+        ],
+    )
+    .await;
+    let balances_url = format!(
+        "{}/v1/safes/{}/balances/usd/?trusted=false&exclude_spam=true",
+        default_chain().base_transaction_service_url(),
+        SAFE_ADDRESS
+    );
+    cache_request(&cache, &balances_url, 200, &format!("[{}]", BALANCE_ETHER)).await;
+    for provider_url in &[base_exchange_api_url(), coinbase_exchange_api_url()] {
+        cache_request(&cache, provider_url, 500, "Unavailable").await;
+    }
+
+    let warm_ups = setup_warmer(&cache)
+        .warm_up(default_chain(), SAFE_ADDRESS)
+        .await;
+
+    assert_eq!(warm_ups.len(), 14);
+    assert!(warm_ups.iter().all(|warm_up| warm_up.success));
+    for request in warm_up_requests(default_chain(), SAFE_ADDRESS) {
+        assert!(
+            cache.has_key(&response_key(&request.uri)).await,
+            "{}",
+            request.uri
+        );
+    }
+}
+
+#[rocket::async_test]
+async fn schedule_warm_up_drops_repeated_events() {
+    let cache = setup_cache();
+    cache_responses(
+        &cache,
+        &[
+            Endpoint::SafeInfo,
+            Endpoint::QueuedTransactions,
+            Endpoint::TransactionHistory,
+            Endpoint::Balances,
+        ],
+    )
+    .await;
+    let warmer = Arc::new(setup_warmer(&cache));
+    let debounce = Duration::from_millis(10);
+
+    let first = warmer
+        .clone()
+        .schedule_warm_up(default_chain(), SAFE_ADDRESS, debounce);
+    let repeated =
+        warmer
+            .clone()
+            .schedule_warm_up(default_chain(), &SAFE_ADDRESS.to_lowercase(), debounce);
+
+    assert!(repeated.is_none());
+    let warm_ups = first.unwrap().await.unwrap();
+    assert_eq!(warm_ups.len(), 14);
+    // Events after the warm up started schedule the next one
+    assert!(warmer
+        .schedule_warm_up(default_chain(), SAFE_ADDRESS, debounce)
+        .is_some());
+}
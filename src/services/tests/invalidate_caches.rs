@@ -1,138 +1,227 @@
+use crate::cache::memory::InMemoryCache;
 use crate::cache::*;
+use crate::config::chains::default_chain;
 use crate::models::backend::webhooks::{
-    ExecutedMultisigTransaction, NewConfirmation, Payload, PayloadDetails,
-    PendingMultisigTransaction,
+    ExecutedMultisigTransaction, IncomingEther, IncomingToken, NewConfirmation, Payload,
+    PayloadDetails, PendingMultisigTransaction,
 };
 use crate::services::hooks::invalidate_caches;
-use mockall::predicate::*;
-use mockall::Sequence;
 
-#[test]
-fn invalidate_with_empty_payload() {
-    let payload = Payload {
-        address: "0x1230B3d59858296A31053C1b8562Ecf89A2f888b".to_string(),
-        details: None,
-    };
-
-    let mut mock_cache = MockCache::new();
-
-    mock_cache.expect_fetch().times(0);
-    mock_cache.expect_create().times(0);
-    mock_cache.expect_invalidate().times(0);
-
-    mock_cache
-        .expect_invalidate_pattern()
-        .times(1)
-        .return_const(())
-        .with(eq("c_re*0x1230B3d59858296A31053C1b8562Ecf89A2f888b*"));
-
-    invalidate_caches(&mock_cache, &payload).unwrap();
+const SAFE_ADDRESS: &'static str = "0x1230B3d59858296A31053C1b8562Ecf89A2f888b";
+const SAFE_TX_HASH: &'static str =
+    "0x65df8a1e5a40703d9c67d5df6f9b552d3830faf0507c3d7350ba3764d3a68621";
+const TX_HASH: &'static str = "0x0ebb2c317f55c96469e0ed2014f5833dc02a70b42f0ac52f4630938900caa698";
+const TX_SERVICE: &'static str = "https://safe-transaction.rinkeby.gnosis.io/api";
+
+// Keys as stored by `CacheResponse` (route uri) and `RequestCached` (upstream url)
+fn cached_keys() -> Vec<(&'static str, String)> {
+    let response = |uri: String| namespaced(&format!("c_resp_{}", uri));
+    let request = |path: String| namespaced(&format!("c_reqs_{}{}", TX_SERVICE, path));
+    let safe = SAFE_ADDRESS;
+    vec![
+        ("safe info", response(format!("/v1/safes/{}", safe))),
+        ("safe info request", request(format!("/v1/safes/{}/", safe))),
+        ("creation request", request(format!("/v1/safes/{}/creation/", safe))),
+        (
+            "queued",
+            response(format!("/v1/safes/{}/transactions/queued", safe)),
+        ),
+        (
+            "queued request",
+            request(format!(
+                "/v1/safes/{}/multisig-transactions/?&nonce__gte=5&ordering=nonce,submissionDate&trusted=false",
+                safe
+            )),
+        ),
+        (
+            "history",
+            response(format!("/v1/safes/{}/transactions/history", safe)),
+        ),
+        (
+            "history request",
+            request(format!(
+                "/v1/safes/{}/all-transactions/?&queued=false&executed=true",
+                safe
+            )),
+        ),
+        (
+            "balances",
+            response(format!("/v1/safes/{}/balances/USD", safe)),
+        ),
+        (
+            "balances request",
+            request(format!(
+                "/v1/safes/{}/balances/usd/?trusted=false&exclude_spam=true",
+                safe
+            )),
+        ),
+        (
+            "collectibles",
+            response(format!("/v1/safes/{}/collectibles", safe)),
+        ),
+        (
+            "transfers request",
+            request(format!(
+                "/v1/safes/{}/transfers/?transaction_hash={}&limit=1000",
+                safe, TX_HASH
+            )),
+        ),
+        (
+            "transaction details",
+            response(format!(
+                "/v1/transactions/multisig_{}_{}",
+                safe, SAFE_TX_HASH
+            )),
+        ),
+        (
+            "transaction details request",
+            request(format!("/v1/multisig-transactions/{}/", SAFE_TX_HASH)),
+        ),
+    ]
 }
 
-#[test]
-fn invalidate_new_confirmation_payload() {
-    let payload = Payload {
-        address: "0x1230B3d59858296A31053C1b8562Ecf89A2f888b".to_string(),
-        details: Some(PayloadDetails::NewConfirmation(NewConfirmation {
-            owner: "0x65F8236309e5A99Ff0d129d04E486EBCE20DC7B0".to_string(),
-            safe_tx_hash: "0x65df8a1e5a40703d9c67d5df6f9b552d3830faf0507c3d7350ba3764d3a68621"
-                .to_string(),
-        })),
-    };
-
-    let mut mock_cache = MockCache::new();
-    let mut sequence = Sequence::new();
-
-    mock_cache.expect_fetch().times(0);
-    mock_cache.expect_create().times(0);
-    mock_cache.expect_invalidate().times(0);
-    mock_cache
-        .expect_invalidate_pattern()
-        .times(1)
-        .return_const(())
-        .with(eq("c_re*0x1230B3d59858296A31053C1b8562Ecf89A2f888b*"))
-        .in_sequence(&mut sequence);
-    mock_cache
-        .expect_invalidate_pattern()
-        .times(1)
-        .return_const(())
-        .with(eq(
-            "c_re*0x65df8a1e5a40703d9c67d5df6f9b552d3830faf0507c3d7350ba3764d3a68621*",
-        ))
-        .in_sequence(&mut sequence);
+// Tagged the same way as entries stored through the cache operations
+async fn cache_with_keys() -> InMemoryCache {
+    let cache = InMemoryCache::new(100);
+    for (_, key) in cached_keys() {
+        cache.create(&key, "{}", 60).await;
+        let tags: Vec<String> = [SAFE_ADDRESS, SAFE_TX_HASH, TX_HASH]
+            .iter()
+            .filter(|identifier| key.contains(*identifier))
+            .map(|identifier| {
+                namespaced(&format!(
+                    "c_tag_{}_{}",
+                    default_chain().chain_id,
+                    identifier.to_lowercase()
+                ))
+            })
+            .collect();
+        cache.tag(&key, &tags, 60).await;
+    }
+    cache
+}
 
-    invalidate_caches(&mock_cache, &payload).unwrap();
+fn payload(details: Option<PayloadDetails>) -> Payload {
+    Payload {
+        address: SAFE_ADDRESS.to_string(),
+        chain_id: None,
+        details,
+    }
 }
 
-#[test]
-fn invalidate_executed_multisig_transaction_payload() {
-    let payload = Payload {
-        address: "0x1230B3d59858296A31053C1b8562Ecf89A2f888b".to_string(),
-        details: Some(PayloadDetails::ExecutedMultisigTransaction(
-            ExecutedMultisigTransaction {
-                safe_tx_hash: "0x65df8a1e5a40703d9c67d5df6f9b552d3830faf0507c3d7350ba3764d3a68621"
-                    .to_string(),
-                tx_hash: "0x0ebb2c317f55c96469e0ed2014f5833dc02a70b42f0ac52f4630938900caa698"
-                    .to_string(),
-            },
-        )),
-    };
+#[rocket::async_test]
+async fn invalidate_caches_per_event() {
+    let new_confirmation = PayloadDetails::NewConfirmation(NewConfirmation {
+        owner: "0x65F8236309e5A99Ff0d129d04E486EBCE20DC7B0".to_string(),
+        safe_tx_hash: SAFE_TX_HASH.to_string(),
+    });
+    let pending_transaction =
+        PayloadDetails::PendingMultisigTransaction(PendingMultisigTransaction {
+            safe_tx_hash: SAFE_TX_HASH.to_string(),
+        });
+    let executed_transaction =
+        PayloadDetails::ExecutedMultisigTransaction(ExecutedMultisigTransaction {
+            safe_tx_hash: SAFE_TX_HASH.to_string(),
+            tx_hash: TX_HASH.to_string(),
+        });
+    let incoming_ether = PayloadDetails::IncomingEther(IncomingEther {
+        tx_hash: TX_HASH.to_string(),
+        value: "1000000000000000000".to_string(),
+    });
+    let incoming_token = PayloadDetails::IncomingToken(IncomingToken {
+        tx_hash: TX_HASH.to_string(),
+        token_address: "0x81D0FF4fE216fB6aC98ED609086A92d94dbfE666".to_string(),
+        token_id: Some("1".to_string()),
+        value: None,
+    });
+    // Keys that are still cached after the event
+    let cases: Vec<(Option<PayloadDetails>, Vec<&str>)> = vec![
+        (
+            Some(new_confirmation),
+            vec![
+                "creation request",
+                "history",
+                "balances",
+                "balances request",
+                "collectibles",
+                "transfers request",
+            ],
+        ),
+        (
+            Some(pending_transaction),
+            vec![
+                "creation request",
+                "history",
+                "balances",
+                "balances request",
+                "collectibles",
+                "transfers request",
+            ],
+        ),
+        (Some(executed_transaction), vec![]),
+        (
+            Some(incoming_ether),
+            vec![
+                "creation request",
+                "collectibles",
+                "transaction details",
+                "transaction details request",
+            ],
+        ),
+        (
+            Some(incoming_token),
+            vec![
+                "creation request",
+                "transaction details",
+                "transaction details request",
+            ],
+        ),
+        (
+            Some(PayloadDetails::Unknown),
+            vec!["transaction details request"],
+        ),
+        (None, vec!["transaction details request"]),
+    ];
+
+    for (details, expected) in cases {
+        let event = format!("{:?}", details);
+        let cache = cache_with_keys().await;
+
+        invalidate_caches(&cache, &payload(details)).await.unwrap();
+
+        let mut actual = vec![];
+        for (name, key) in cached_keys() {
+            if cache.has_key(&key).await {
+                actual.push(name);
+            }
+        }
+        assert_eq!(actual, expected, "{}", event);
+    }
+}
 
-    let mut mock_cache = MockCache::new();
-    let mut sequence = Sequence::new();
+#[rocket::async_test]
+async fn invalidate_caches_of_event_chain_only() {
+    let cache = cache_with_keys().await;
+    let other_chain = namespaced(&format!("c_resp_/v1/chains/1337/safes/{}", SAFE_ADDRESS));
+    cache.create(&other_chain, "{}", 60).await;
+    let other_chain_tag = namespaced(&format!("c_tag_1337_{}", SAFE_ADDRESS.to_lowercase()));
+    cache.tag(&other_chain, &[other_chain_tag], 60).await;
 
-    mock_cache.expect_fetch().times(0);
-    mock_cache.expect_create().times(0);
-    mock_cache.expect_invalidate().times(0);
-    mock_cache
-        .expect_invalidate_pattern()
-        .times(1)
-        .return_const(())
-        .with(eq("c_re*0x1230B3d59858296A31053C1b8562Ecf89A2f888b*"))
-        .in_sequence(&mut sequence);
-    mock_cache
-        .expect_invalidate_pattern()
-        .times(1)
-        .return_const(())
-        .with(eq(
-            "c_re*0x65df8a1e5a40703d9c67d5df6f9b552d3830faf0507c3d7350ba3764d3a68621*",
-        ))
-        .in_sequence(&mut sequence);
+    invalidate_caches(&cache, &payload(None)).await.unwrap();
 
-    invalidate_caches(&mock_cache, &payload).unwrap();
+    assert!(cache.has_key(&other_chain).await);
+    assert!(!cache.has_key(&cached_keys()[0].1).await);
 }
 
-#[test]
-fn invalidate_pending_multisig_transaction_payload() {
-    let payload = Payload {
-        address: "0x1230B3d59858296A31053C1b8562Ecf89A2f888b".to_string(),
-        details: Some(PayloadDetails::PendingMultisigTransaction(
-            PendingMultisigTransaction {
-                safe_tx_hash: "0x65df8a1e5a40703d9c67d5df6f9b552d3830faf0507c3d7350ba3764d3a68621"
-                    .to_string(),
-            },
-        )),
-    };
+#[rocket::async_test]
+async fn invalidate_caches_ignores_unknown_chain() {
+    let cache = cache_with_keys().await;
+    let mut payload = payload(None);
+    payload.chain_id = Some(String::from("1337"));
 
-    let mut mock_cache = MockCache::new();
-    let mut sequence = Sequence::new();
-    mock_cache.expect_fetch().times(0);
-    mock_cache.expect_create().times(0);
-    mock_cache.expect_invalidate().times(0);
-    mock_cache
-        .expect_invalidate_pattern()
-        .times(1)
-        .return_const(())
-        .with(eq("c_re*0x1230B3d59858296A31053C1b8562Ecf89A2f888b*"))
-        .in_sequence(&mut sequence);
-    mock_cache
-        .expect_invalidate_pattern()
-        .times(1)
-        .return_const(())
-        .with(eq(
-            "c_re*0x65df8a1e5a40703d9c67d5df6f9b552d3830faf0507c3d7350ba3764d3a68621*",
-        ))
-        .in_sequence(&mut sequence);
+    invalidate_caches(&cache, &payload).await.unwrap();
 
-    invalidate_caches(&mut mock_cache, &payload).unwrap();
+    for (name, key) in cached_keys() {
+        assert!(cache.has_key(&key).await, "{}", name);
+    }
 }
@@ -1,4 +1,8 @@
+mod balances;
+mod cache_warmer;
 mod invalidate_caches;
 mod parse_id;
+mod process_webhooks;
+mod settings;
 mod transactions_history;
 mod transactions_queued;
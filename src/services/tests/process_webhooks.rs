@@ -0,0 +1,162 @@
+use crate::cache::memory::InMemoryCache;
+use crate::cache::MockCache;
+use crate::models::backend::webhooks::Payload;
+use crate::queue::memory::InMemoryQueue;
+use crate::queue::{Message, Queue};
+use crate::services::hooks::{dedupe_key, enqueue_webhook, process_webhook};
+
+const NEW_CONFIRMATION: &'static str = r#"{
+    "address": "0x1230B3d59858296A31053C1b8562Ecf89A2f888b",
+    "type": "NEW_CONFIRMATION",
+    "owner": "0x65F8236309e5A99Ff0d129d04E486EBCE20DC7B0",
+    "safeTxHash": "0x65df8a1e5a40703d9c67d5df6f9b552d3830faf0507c3d7350ba3764d3a68621"
+}"#;
+
+#[rocket::async_test]
+async fn enqueue_identical_webhooks_once() {
+    let cache = InMemoryCache::new(10);
+    let queue = InMemoryQueue::new();
+
+    enqueue_webhook(&cache, &queue, NEW_CONFIRMATION)
+        .await
+        .unwrap();
+    enqueue_webhook(&cache, &queue, NEW_CONFIRMATION)
+        .await
+        .unwrap();
+
+    assert_eq!(queue.pop(10).await.len(), 1);
+}
+
+#[test]
+fn dedupe_key_of_event() {
+    let reordered = r#"{
+        "safeTxHash": "0x65DF8A1E5A40703D9C67D5DF6F9B552D3830FAF0507C3D7350BA3764D3A68621",
+        "type": "NEW_CONFIRMATION",
+        "owner": "0x65F8236309e5A99Ff0d129d04E486EBCE20DC7B0",
+        "address": "0x1230b3d59858296a31053c1b8562ecf89a2f888b",
+        "unknownField": 1
+    }"#;
+    let other_chain = NEW_CONFIRMATION.replace("{", r#"{"chainId": "1337","#);
+    let parse = |body: &str| serde_json::from_str::<Payload>(body).unwrap();
+
+    let expected = dedupe_key(&parse(NEW_CONFIRMATION));
+
+    assert_eq!(dedupe_key(&parse(reordered)), expected);
+    assert_ne!(dedupe_key(&parse(&other_chain)), expected);
+}
+
+#[rocket::async_test]
+async fn enqueue_invalid_webhook() {
+    let cache = InMemoryCache::new(10);
+    let queue = InMemoryQueue::new();
+
+    let actual = enqueue_webhook(&cache, &queue, "{}").await;
+
+    assert_eq!(actual.expect_err("Payload without address").status, 422);
+}
+
+#[rocket::async_test]
+async fn process_webhook_releases_dedupe_key() {
+    let cache = InMemoryCache::new(10);
+    let queue = InMemoryQueue::new();
+    enqueue_webhook(&cache, &queue, NEW_CONFIRMATION)
+        .await
+        .unwrap();
+    let message = queue.pop(10).await.remove(0);
+
+    assert!(process_webhook(&cache, &queue, &message).await);
+
+    enqueue_webhook(&cache, &queue, NEW_CONFIRMATION)
+        .await
+        .unwrap();
+    assert_eq!(queue.pop(10).await.len(), 1);
+    assert!(queue.dead_letters(10).await.is_empty());
+}
+
+#[rocket::async_test]
+async fn process_invalid_webhook_moves_to_dead_letters() {
+    let cache = InMemoryCache::new(10);
+    let queue = InMemoryQueue::new();
+    let message = Message {
+        id: String::from("1"),
+        body: String::from("not a webhook"),
+        attempts: 0,
+    };
+
+    assert!(process_webhook(&cache, &queue, &message).await);
+
+    let dead_letters = queue.dead_letters(10).await;
+    assert_eq!(dead_letters.len(), 1);
+    assert_eq!(dead_letters[0].body, "not a webhook");
+    assert_eq!(dead_letters[0].attempts, 1);
+}
+
+#[rocket::async_test]
+async fn process_webhook_retries_while_cache_unavailable() {
+    let mut mock_cache = MockCache::new();
+    mock_cache.expect_is_available().return_const(false);
+    mock_cache.expect_invalidate().times(0);
+    mock_cache.expect_invalidate_tag().times(0);
+    let queue = InMemoryQueue::new();
+    let message = Message {
+        id: String::from("1"),
+        body: String::from(NEW_CONFIRMATION),
+        attempts: 0,
+    };
+
+    assert!(!process_webhook(&mock_cache, &queue, &message).await);
+
+    // Not counted as an attempt, so that outages don't dead-letter webhooks
+    let retried = queue.pop(10).await;
+    assert_eq!(retried.len(), 1);
+    assert_eq!(retried[0].body, NEW_CONFIRMATION);
+    assert_eq!(retried[0].attempts, 0);
+    assert!(queue.dead_letters(10).await.is_empty());
+}
+
+#[rocket::async_test]
+async fn process_webhook_retries_failed_invalidation() {
+    let mut mock_cache = MockCache::new();
+    mock_cache.expect_is_available().return_const(true);
+    mock_cache
+        .expect_invalidate_tag()
+        .times(1)
+        .return_const(false);
+    mock_cache.expect_invalidate().times(0);
+    let queue = InMemoryQueue::new();
+    let message = Message {
+        id: String::from("1"),
+        body: String::from(NEW_CONFIRMATION),
+        attempts: 0,
+    };
+
+    assert!(!process_webhook(&mock_cache, &queue, &message).await);
+
+    let retried = queue.pop(10).await;
+    assert_eq!(retried.len(), 1);
+    assert_eq!(retried[0].attempts, 1);
+    assert!(queue.dead_letters(10).await.is_empty());
+}
+
+#[rocket::async_test]
+async fn process_webhook_gives_up_after_max_attempts() {
+    let mut mock_cache = MockCache::new();
+    mock_cache.expect_is_available().return_const(true);
+    mock_cache
+        .expect_invalidate_tag()
+        .times(1)
+        .return_const(false);
+    mock_cache.expect_invalidate().times(1).return_const(());
+    let queue = InMemoryQueue::new();
+    let message = Message {
+        id: String::from("1"),
+        body: String::from(NEW_CONFIRMATION),
+        attempts: 4,
+    };
+
+    assert!(process_webhook(&mock_cache, &queue, &message).await);
+
+    let dead_letters = queue.dead_letters(10).await;
+    assert_eq!(dead_letters.len(), 1);
+    assert_eq!(dead_letters[0].attempts, 5);
+}
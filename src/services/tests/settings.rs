@@ -0,0 +1,192 @@
+use crate::cache::memory::InMemoryCache;
+use crate::cache::{namespaced, Cache, MockCache};
+use crate::config::chains::{parse_chains, ChainConfig};
+use crate::config::Config;
+use crate::services::settings::{
+    chain_values, check_not_shadowed, load_overrides, merge_overrides, parse_settings_file,
+    update_settings, Overrides,
+};
+use serde_json::json;
+use std::collections::BTreeMap;
+
+// Configuration of the launch, independent of the environment of the test run
+fn base_config() -> Config {
+    let env = |key: &str| match key {
+        "REDIS_URL" => Some(String::from("redis://:password@localhost:6379")),
+        "EXCHANGE_API_BASE_URL" => Some(String::from("http://api.exchangeratesapi.io/latest")),
+        "EXCHANGE_API_KEY" => Some(String::from("exchange_key")),
+        "WEBHOOK_TOKEN" => Some(String::from("webhook_token")),
+        "TRANSACTION_SERVICE_URL" => Some(String::from("https://safe-transaction.gnosis.io")),
+        _ => None,
+    };
+    Config::load(&env, None).unwrap()
+}
+
+fn chains_with_cache_durations() -> Vec<ChainConfig> {
+    parse_chains(
+        r#"[
+            {
+                "chainId": 1,
+                "transactionServiceUrl": "https://safe-transaction.gnosis.io",
+                "nativeCurrency": { "name": "Ether", "symbol": "ETH", "decimals": 18 },
+                "cacheDurations": { "balances": 60 }
+            },
+            {
+                "chainId": 100,
+                "transactionServiceUrl": "https://safe-transaction.xdai.gnosis.io",
+                "nativeCurrency": { "name": "xDai", "symbol": "XDAI", "decimals": 18 },
+                "cacheDurations": { "safeInfo": 30, "balances": 120 }
+            }
+        ]"#,
+    )
+    .unwrap()
+}
+
+fn overrides(values: &[(&str, &str)]) -> Overrides {
+    values
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn merge_overrides_sets_and_removes_settings() {
+    let current = overrides(&[
+        ("feature_flag_nested_decoding", "false"),
+        ("about_cache_duration", "60"),
+    ]);
+    let changes: BTreeMap<String, serde_json::Value> = serde_json::from_value(json!({
+        "FEATURE_FLAG_NESTED_DECODING": null,
+        "balances_request_cache_duration": 5,
+        "log_all_error_responses": true,
+        "safe_info_cache_duration": "120"
+    }))
+    .unwrap();
+    let expected = overrides(&[
+        ("about_cache_duration", "60"),
+        ("balances_request_cache_duration", "5"),
+        ("log_all_error_responses", "true"),
+        ("safe_info_cache_duration", "120"),
+    ]);
+
+    let actual = merge_overrides(current, changes);
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn parse_settings_file_reads_values_as_strings() {
+    let file = r#"
+        FEATURE_FLAG_NESTED_DECODING = false
+        balances_request_cache_duration = 5
+        safe_info_cache_duration = "120"
+    "#;
+    let expected = overrides(&[
+        ("balances_request_cache_duration", "5"),
+        ("feature_flag_nested_decoding", "false"),
+        ("safe_info_cache_duration", "120"),
+    ]);
+
+    let actual = parse_settings_file(file).unwrap();
+
+    assert_eq!(actual, expected);
+}
+
+#[rocket::async_test]
+async fn load_overrides_from_cache() {
+    let cache = InMemoryCache::new(10);
+    assert_eq!(load_overrides(&cache, "").await, Ok(Overrides::new()));
+
+    cache
+        .insert_in_hash(
+            &namespaced("settings"),
+            "overrides",
+            r#"{"feature_flag_nested_decoding":"false"}"#,
+        )
+        .await;
+
+    assert_eq!(
+        load_overrides(&cache, "").await,
+        Ok(overrides(&[("feature_flag_nested_decoding", "false")]))
+    );
+}
+
+#[rocket::async_test]
+async fn update_settings_rejects_invalid_settings() {
+    let cache = InMemoryCache::new(10);
+    let changes: BTreeMap<String, serde_json::Value> =
+        serde_json::from_value(json!({ "redis_url": "redis://other" })).unwrap();
+
+    let error = update_settings(&cache, &base_config(), changes)
+        .await
+        .unwrap_err();
+
+    assert_eq!(error.status, 422);
+    assert_eq!(load_overrides(&cache, "").await, Ok(Overrides::new()));
+}
+
+#[rocket::async_test]
+async fn update_settings_from_file_is_rejected() {
+    let cache = InMemoryCache::new(10);
+    let base = Config {
+        runtime_settings_path: String::from("/etc/safe-client-gateway/settings.toml"),
+        ..base_config()
+    };
+    let changes: BTreeMap<String, serde_json::Value> =
+        serde_json::from_value(json!({ "feature_flag_nested_decoding": false })).unwrap();
+
+    let error = update_settings(&cache, &base, changes).await.unwrap_err();
+
+    assert_eq!(error.status, 409);
+}
+
+#[rocket::async_test]
+async fn update_settings_fails_if_not_stored() {
+    let mut cache = MockCache::new();
+    cache.expect_is_available().return_const(true);
+    cache.expect_get_from_hash().return_const(None::<String>);
+    cache.expect_insert_in_hash().times(1).return_const(false);
+    let changes: BTreeMap<String, serde_json::Value> =
+        serde_json::from_value(json!({ "about_cache_duration": 60 })).unwrap();
+
+    let error = update_settings(&cache, &base_config(), changes)
+        .await
+        .unwrap_err();
+
+    assert_eq!(error.status, 503);
+}
+
+#[test]
+fn chain_values_of_chain_cache_durations() {
+    let actual = chain_values(&chains_with_cache_durations());
+
+    let expected: BTreeMap<String, BTreeMap<String, usize>> = serde_json::from_value(json!({
+        "balances_request_cache_duration": { "1": 60, "100": 120 },
+        "safe_info_cache_duration": { "100": 30 }
+    }))
+    .unwrap();
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn check_not_shadowed_rejects_settings_of_every_chain() {
+    let chains = chains_with_cache_durations();
+    let changes = |changes: serde_json::Value| -> BTreeMap<String, serde_json::Value> {
+        serde_json::from_value(changes).unwrap()
+    };
+
+    let shadowed = check_not_shadowed(
+        &changes(json!({ "balances_request_cache_duration": 10 })),
+        &chains,
+    );
+    let partially_shadowed =
+        check_not_shadowed(&changes(json!({ "safe_info_cache_duration": 10 })), &chains);
+    let restored = check_not_shadowed(
+        &changes(json!({ "balances_request_cache_duration": null })),
+        &chains,
+    );
+
+    assert_eq!(shadowed.unwrap_err().status, 422);
+    assert!(partially_shadowed.is_ok());
+    assert!(restored.is_ok());
+}
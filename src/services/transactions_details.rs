@@ -1,7 +1,7 @@
 extern crate reqwest;
 
 use crate::cache::cache_operations::RequestCached;
-use crate::config::{base_transaction_service_url, transaction_request_timeout};
+use crate::config::transaction_request_timeout;
 use crate::models::backend::transactions::{ModuleTransaction, MultisigTransaction};
 use crate::models::backend::transfers::Transfer;
 use crate::models::commons::Page;
@@ -18,13 +18,13 @@ use crate::utils::transactions::fetch_rejections;
 use log::debug;
 
 pub(super) async fn get_multisig_transaction_details(
-    context: &Context<'_>,
+    context: &Context,
     safe_tx_hash: &str,
 ) -> ApiResult<TransactionDetails> {
     let mut info_provider = DefaultInfoProvider::new(context);
     let url = format!(
         "{}/v1/multisig-transactions/{}/",
-        base_transaction_service_url(),
+        context.chain().base_transaction_service_url(),
         safe_tx_hash
     );
     let body = RequestCached::new(url)
@@ -48,7 +48,7 @@ pub(super) async fn get_multisig_transaction_details(
 }
 
 async fn get_ethereum_transaction_details(
-    context: &Context<'_>,
+    context: &Context,
     safe: &str,
     tx_hash: &str,
     detail_hash: &str,
@@ -56,7 +56,7 @@ async fn get_ethereum_transaction_details(
     let mut info_provider = DefaultInfoProvider::new(context);
     let url = format!(
         "{}/v1/safes/{}/transfers/?transaction_hash={}&limit=1000",
-        base_transaction_service_url(),
+        context.chain().base_transaction_service_url(),
         safe,
         tx_hash
     );
@@ -83,7 +83,7 @@ async fn get_ethereum_transaction_details(
 }
 
 async fn get_module_transaction_details(
-    context: &Context<'_>,
+    context: &Context,
     safe: &str,
     tx_hash: &str,
     detail_hash: &str,
@@ -92,7 +92,7 @@ async fn get_module_transaction_details(
 
     let url = format!(
         "{}/v1/safes/{}/module-transactions/?transaction_hash={}&limit=1000",
-        base_transaction_service_url(),
+        context.chain().base_transaction_service_url(),
         safe,
         tx_hash
     );
@@ -115,7 +115,7 @@ async fn get_module_transaction_details(
 }
 
 pub async fn get_transactions_details(
-    context: &Context<'_>,
+    context: &Context,
     details_id: &String,
 ) -> ApiResult<TransactionDetails> {
     let id_parts = parse_id(details_id)?;
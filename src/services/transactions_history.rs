@@ -1,7 +1,7 @@
 extern crate reqwest;
 
 use crate::cache::cache_operations::RequestCached;
-use crate::config::{base_transaction_service_url, transaction_request_timeout};
+use crate::config::transaction_request_timeout;
 use crate::models::backend::transactions::{CreationTransaction, Transaction};
 use crate::models::commons::{Page, PageMetadata};
 use crate::models::service::transactions::summary::{
@@ -15,7 +15,7 @@ use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, NaiveDateTime, Utc};
 use itertools::Itertools;
 
 pub async fn get_history_transactions(
-    context: &Context<'_>,
+    context: &Context,
     safe_address: &String,
     page_url: &Option<String>,
     timezone_offset: &Option<String>,
@@ -82,7 +82,7 @@ pub async fn get_history_transactions(
 }
 
 fn build_page_url(
-    context: &Context<'_>,
+    context: &Context,
     safe_address: &str,
     page_meta: &PageMetadata,
     timezone_offset: &Option<String>,
@@ -116,14 +116,14 @@ pub(super) fn adjust_page_meta(meta: &PageMetadata) -> PageMetadata {
 }
 
 async fn fetch_backend_paged_txs(
-    context: &Context<'_>,
+    context: &Context,
     safe_address: &str,
     page_url: &Option<String>,
 ) -> ApiResult<Page<Transaction>> {
     let page_metadata = PageMetadata::from_url_string(page_url.as_ref().unwrap_or(&"".to_string()));
     let url = format!(
         "{}/v1/safes/{}/all-transactions/?{}&queued=false&executed=true",
-        base_transaction_service_url(),
+        context.chain().base_transaction_service_url(),
         safe_address,
         page_metadata.to_url_string()
     );
@@ -215,12 +215,12 @@ pub(super) fn get_day_timestamp_millis(timestamp_in_millis: i64, timezone_offset
 }
 
 pub(super) async fn get_creation_transaction_summary(
-    context: &Context<'_>,
+    context: &Context,
     safe: &String,
 ) -> ApiResult<TransactionSummary> {
     let url = format!(
         "{}/v1/safes/{}/creation/",
-        base_transaction_service_url(),
+        context.chain().base_transaction_service_url(),
         safe
     );
     debug!("{}", &url);
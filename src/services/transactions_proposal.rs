@@ -1,29 +1,33 @@
-use crate::cache::Cache;
-use crate::config::base_transaction_service_url;
+use crate::cache::cache_operations::{Invalidate, InvalidationPattern, InvalidationScope};
 use crate::models::service::transactions::requests::MultisigTransactionRequest;
+use crate::monitoring::tracing::upstream_headers;
 use crate::utils::context::Context;
 use crate::utils::errors::{ApiError, ApiResult};
 use std::collections::HashMap;
 
 pub async fn submit_confirmation(
-    context: &Context<'_>,
+    context: &Context,
     safe_tx_hash: &str,
     signature: &str,
 ) -> ApiResult<()> {
     let url = format!(
         "{}/v1/multisig-transactions/{}/confirmations/",
-        base_transaction_service_url(),
+        context.chain().base_transaction_service_url(),
         &safe_tx_hash
     );
     let mut json = HashMap::new();
     json.insert("signature", signature);
 
-    let response = context.client().post(&url).json(&json).send().await?;
+    let response = context
+        .client()
+        .post(&url)
+        .headers(upstream_headers(&url))
+        .json(&json)
+        .send()
+        .await?;
 
     if response.status().is_success() {
-        context
-            .cache()
-            .invalidate_pattern(&format!("*{}*", &safe_tx_hash));
+        invalidate(context, safe_tx_hash).await;
         Ok(())
     } else {
         Err(ApiError::from_http_response(
@@ -35,26 +39,25 @@ pub async fn submit_confirmation(
 }
 
 pub async fn propose_transaction(
-    context: &Context<'_>,
+    context: &Context,
     safe_address: &str,
     transaction_request: &MultisigTransactionRequest,
 ) -> ApiResult<()> {
     let url = format!(
         "{}/v1/safes/{}/multisig-transactions/",
-        base_transaction_service_url(),
+        context.chain().base_transaction_service_url(),
         &safe_address
     );
     let response = context
         .client()
         .post(&url)
+        .headers(upstream_headers(&url))
         .json(&transaction_request)
         .send()
         .await?;
 
     if response.status().is_success() {
-        context
-            .cache()
-            .invalidate_pattern(&format!("*{}*", &safe_address));
+        invalidate(context, safe_address).await;
         Ok(())
     } else {
         Err(ApiError::from_http_response(
@@ -64,3 +67,16 @@ pub async fn propose_transaction(
         .await)
     }
 }
+
+// The transaction service accepted the request, so a failed invalidation only delays the update
+// until its webhook arrives
+async fn invalidate(context: &Context, value: &str) {
+    let pattern = InvalidationPattern::Any(InvalidationScope::Both, value.to_string());
+    if let Err(error) = Invalidate::new(pattern)
+        .chain_id(context.chain().chain_id)
+        .execute(context.cache())
+        .await
+    {
+        log::warn!("Could not invalidate {}: {}", value, error);
+    }
+}
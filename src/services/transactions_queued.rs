@@ -1,5 +1,5 @@
 use crate::cache::cache_operations::RequestCached;
-use crate::config::{base_transaction_service_url, transaction_request_timeout};
+use crate::config::transaction_request_timeout;
 use crate::models::backend::transactions::MultisigTransaction;
 use crate::models::commons::{Page, PageMetadata};
 use crate::models::service::transactions::summary::{ConflictType, Label, TransactionListItem};
@@ -12,7 +12,7 @@ use std::collections::HashMap;
 
 // use https://doc.rust-lang.org/std/iter/trait.Iterator.html#method.peekable
 pub async fn get_queued_transactions(
-    context: &Context<'_>,
+    context: &Context,
     safe_address: &String,
     page_url: &Option<String>,
     timezone_offset: &Option<String>,
@@ -32,7 +32,7 @@ pub async fn get_queued_transactions(
     let safe_nonce = info_provider.safe_info(safe_address).await?.nonce as i64;
     let url = format!(
         "{}/v1/safes/{}/multisig-transactions/?{}&nonce__gte={}&ordering=nonce,submissionDate&trusted={}",
-        base_transaction_service_url(),
+        context.chain().base_transaction_service_url(),
         safe_address,
         adjusted_page_meta.to_url_string(),
         safe_nonce,
@@ -0,0 +1,76 @@
+use crate::config::chains::{chain, ChainConfig};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::uri::Origin;
+use rocket::{Data, Request};
+
+const CHAINS_PATH_PREFIX: &'static str = "/v1/chains/";
+
+/// Set by [ChainScope] for requests made to a `/v1/chains/<chain_id>/...` route
+#[derive(Clone, Default)]
+pub struct RequestChain {
+    pub chain: Option<&'static ChainConfig>,
+    /// The uri as requested, before the chain was removed from it
+    pub uri: Option<String>,
+}
+
+/// Serves `/v1/chains/<chain_id>/<path>` with the route of `/v1/<path>` for every chain of the
+/// registry. Routes without the prefix keep serving the default chain.
+pub struct ChainScope();
+
+#[rocket::async_trait]
+impl Fairing for ChainScope {
+    fn info(&self) -> Info {
+        Info {
+            name: "Resolve the chain of chain scoped routes",
+            kind: Kind::Request,
+        }
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _data: &mut Data) {
+        let uri = request.uri().to_string();
+        let (chain, path) = match split_chain_scope(&uri)
+            .and_then(|(chain_id, path)| chain(chain_id).map(|chain| (chain, path)))
+        {
+            Some(scope) => scope,
+            // Unknown chains are left to the router, which doesn't have a route for them
+            None => return,
+        };
+        if let Ok(origin) = Origin::parse_owned(path) {
+            request.set_uri(origin);
+            request.local_cache(|| RequestChain {
+                chain: Some(chain),
+                uri: Some(uri),
+            });
+        }
+    }
+}
+
+pub fn request_chain(request: &Request<'_>) -> RequestChain {
+    request.local_cache(RequestChain::default).clone()
+}
+
+/// The uri the client requested, including the chain
+pub fn original_uri(request: &Request<'_>) -> String {
+    request_chain(request)
+        .uri
+        .unwrap_or_else(|| request.uri().to_string())
+}
+
+/// `/v1/chains/4/safes/<address>` becomes `(4, "/v1/safes/<address>")`, `None` for paths that are
+/// not scoped to a chain
+pub fn split_chain_scope(uri: &str) -> Option<(u64, String)> {
+    let scoped = uri.strip_prefix(CHAINS_PATH_PREFIX)?;
+    let (chain_id, path) = scoped.split_at(scoped.find('/')?);
+    if path.len() <= 1 {
+        return None;
+    }
+    Some((chain_id.parse().ok()?, format!("/v1{}", path)))
+}
+
+/// Inverse of [split_chain_scope] for paths starting with `/v1/`
+pub fn chain_scoped_path(chain_id: u64, path: &str) -> String {
+    match path.strip_prefix("/v1/") {
+        Some(path) => format!("{}{}/{}", CHAINS_PATH_PREFIX, chain_id, path),
+        None => path.to_string(),
+    }
+}
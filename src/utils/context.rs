@@ -2,31 +2,63 @@ use rocket::http::uri::Origin;
 use rocket::request::{self, FromRequest, Request};
 use rocket::State;
 
-use crate::cache::redis::ServiceCache;
+use crate::cache::ServiceCache;
+use crate::config::chains::{default_chain, ChainConfig};
 use crate::config::scheme;
+use crate::utils::chains::{chain_scoped_path, request_chain, split_chain_scope};
 
-pub struct Context<'r> {
+#[derive(Clone)]
+pub struct Context {
+    chain: &'static ChainConfig,
     uri: String,
     host: Option<String>,
-    cache: ServiceCache<'r>,
-    client: State<'r, reqwest::Client>,
+    cache: ServiceCache,
+    client: reqwest::Client,
 }
 
-impl<'r> Context<'r> {
-    pub fn client(&self) -> &'r reqwest::Client {
-        self.client.inner()
+impl Context {
+    /// For work done outside of a request, e.g. regenerating cached responses
+    pub fn new(
+        chain: &'static ChainConfig,
+        uri: String,
+        host: Option<String>,
+        cache: ServiceCache,
+        client: reqwest::Client,
+    ) -> Self {
+        Context {
+            chain,
+            uri,
+            host,
+            cache,
+            client,
+        }
     }
 
-    pub fn cache(&self) -> &ServiceCache<'r> {
+    pub fn client(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    pub fn cache(&self) -> &ServiceCache {
         &self.cache
     }
 
+    pub fn chain(&self) -> &'static ChainConfig {
+        self.chain
+    }
+
+    /// As requested, so that responses of different chains are cached separately
     pub fn uri(&self) -> String {
         self.uri.clone()
     }
 
+    /// `origin` is scoped to the chain if the request was
     pub fn build_absolute_url(&self, origin: Origin) -> String {
-        format!("{}{}", self.host().unwrap(), origin)
+        let path = if split_chain_scope(&self.uri).is_some() {
+            chain_scoped_path(self.chain.chain_id, &origin.to_string())
+        } else {
+            origin.to_string()
+        };
+        format!("{}{}", self.host().unwrap(), path)
     }
 
     fn host(&self) -> Option<String> {
@@ -37,23 +69,27 @@ impl<'r> Context<'r> {
 }
 
 #[rocket::async_trait]
-impl<'r> FromRequest<'r> for Context<'r> {
+impl<'r> FromRequest<'r> for Context {
     type Error = ();
 
     async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
-        let cache = request.guard().await.unwrap();
+        let cache = try_outcome!(request.guard::<ServiceCache>().await);
         let client = try_outcome!(request.guard::<State<reqwest::Client>>().await);
         // TODO: I couldn't get the request to be part of the context ... not sure if we want that for the future
         let host = request
             .headers()
             .get_one("Host")
             .map(|host| host.to_string());
-        let uri = request.uri().to_string();
+        let request_chain = request_chain(request);
+        let uri = request_chain
+            .uri
+            .unwrap_or_else(|| request.uri().to_string());
         return request::Outcome::Success(Context {
+            chain: request_chain.chain.unwrap_or_else(default_chain),
             host,
             uri,
             cache,
-            client,
+            client: client.inner().clone(),
         });
     }
 }
@@ -1,11 +1,14 @@
 use crate::config::log_all_error_responses;
+use crate::monitoring::logging::{log_fields, request_scope};
+use crate::utils::chains::original_uri;
 use reqwest::Response as ReqwestResponse;
 use rocket::http::{ContentType, Status};
 use rocket::request::Request;
 use rocket::response::{self, Responder, Response};
 use rocket_contrib::json::JsonError;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use serde_json;
+use serde_json::{self, Value};
 use std::fmt;
 use std::io::Cursor;
 use std::result::Result;
@@ -13,13 +16,13 @@ use thiserror::Error;
 
 pub type ApiResult<T, E = ApiError> = Result<T, E>;
 
-#[derive(Error, Debug, PartialEq)]
+#[derive(Error, Debug, PartialEq, Clone)]
 pub struct ApiError {
     pub status: u16,
     pub details: ErrorDetails,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 pub struct ErrorDetails {
     pub code: u64,
     pub message: Option<String>,
@@ -96,12 +99,12 @@ impl fmt::Display for ApiError {
 impl<'r> Responder<'r, 'static> for ApiError {
     fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
         if log_all_error_responses() || (self.status >= 500 && self.status < 600) {
-            log::error!(
-                "ERR::{}::{}::{}",
-                self.status,
-                request.uri().to_string(),
-                self.details
-            );
+            let _scope = request_scope(request);
+            let _fields = log_fields(&[
+                ("status", Value::from(self.status)),
+                ("uri", Value::from(original_uri(request))),
+            ]);
+            log::error!("Error response: {}", self.details);
         }
         let resp = serde_json::to_string(&self.details).unwrap_or(String::from(
             &self
@@ -132,6 +135,13 @@ impl From<serde_json::error::Error> for ApiError {
     }
 }
 
+/// For bodies that are read as `String`, e.g. to verify their signature. Unlike the responses of
+/// the backends, a body that can't be parsed is an error of the client.
+pub fn parse_request_body<T: DeserializeOwned>(body: &str) -> ApiResult<T> {
+    serde_json::from_str(body)
+        .map_err(|error| ApiError::new_from_message_with_code(422, error.to_string()))
+}
+
 impl From<rocket_contrib::json::JsonError<'_>> for ApiError {
     fn from(err: JsonError<'_>) -> Self {
         let message = match err {
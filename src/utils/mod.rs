@@ -3,10 +3,12 @@ use crate::models::commons::{DataDecoded, ParamValue, ValueDecodedType};
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 
+pub mod chains;
 pub mod context;
 pub mod cors;
 pub mod errors;
 pub mod json;
+pub mod signatures;
 pub mod transactions;
 pub mod urls;
 
@@ -0,0 +1,137 @@
+use crate::cache::{namespaced, Cache};
+use crate::config::{
+    legacy_webhook_token_enabled, webhook_secrets, webhook_signature_tolerance, webhook_token,
+};
+use crate::utils::chains::original_uri;
+use crate::utils::errors::ApiResult;
+use chrono::Utc;
+use hmac::{Hmac, Mac, NewMac};
+use rocket::request::{self, FromRequest, Request};
+use sha2::Sha256;
+
+pub const SIGNATURE_HEADER: &'static str = "X-Signature";
+pub const SIGNATURE_TIMESTAMP_HEADER: &'static str = "X-Signature-Timestamp";
+
+const SIGNATURE_PREFIX: &'static str = "c_sig";
+
+/// Request guard for requests signed with one of the `WEBHOOK_SECRETS`
+///
+/// The `X-Signature` header holds the hex encoded HMAC-SHA256 of `<timestamp>.<path>.<body>`,
+/// where `timestamp` is the value of the `X-Signature-Timestamp` header (seconds since epoch) and
+/// `path` is the path and query of the request. Requests with a timestamp that is off by more than
+/// `WEBHOOK_SIGNATURE_TOLERANCE` seconds are rejected, and every signature is only accepted once
+/// within that time, so that requests can't be replayed. Signed requests fail with 503 while the
+/// cache backend is unavailable.
+///
+/// The body is only available to the route, which has to call [Signature::verify] with it.
+pub struct Signature {
+    path: String,
+    timestamp: Option<String>,
+    signature: Option<String>,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Signature {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        let headers = request.headers();
+        request::Outcome::Success(Signature::new(
+            original_uri(request),
+            headers
+                .get_one(SIGNATURE_TIMESTAMP_HEADER)
+                .map(|timestamp| timestamp.to_string()),
+            headers
+                .get_one(SIGNATURE_HEADER)
+                .map(|signature| signature.to_string()),
+        ))
+    }
+}
+
+impl Signature {
+    pub(super) fn new(path: String, timestamp: Option<String>, signature: Option<String>) -> Self {
+        Signature {
+            path,
+            timestamp,
+            signature,
+        }
+    }
+
+    pub async fn verify(&self, cache: &impl Cache, body: &str) -> ApiResult<()> {
+        self.verify_once(cache, body, &webhook_secrets(), Utc::now().timestamp())
+            .await
+    }
+
+    pub(super) async fn verify_once(
+        &self,
+        cache: &impl Cache,
+        body: &str,
+        secrets: &[String],
+        now: i64,
+    ) -> ApiResult<()> {
+        let signature = self.verify_with(body, secrets, now)?;
+        // Timestamps are accepted that far off in both directions. Requests are rejected while
+        // the cache backend is unavailable, as replays couldn't be detected.
+        let remembered_for = webhook_signature_tolerance() as usize * 2 * 1000;
+        match cache
+            .create_if_absent(&replay_key(&signature), "used", remembered_for)
+            .await
+        {
+            Some(true) => Ok(()),
+            Some(false) => Err(client_error!(401, "Replayed signature")),
+            None => Err(client_error!(503, "Signatures can't be verified right now")),
+        }
+    }
+
+    // Every secret is tried, so that senders can switch to a new secret one at a time. Returns
+    // the verified signature.
+    pub(super) fn verify_with(
+        &self,
+        body: &str,
+        secrets: &[String],
+        now: i64,
+    ) -> ApiResult<Vec<u8>> {
+        let timestamp = self
+            .timestamp
+            .as_ref()
+            .and_then(|timestamp| timestamp.parse::<i64>().ok())
+            .ok_or(client_error!(401, "Missing signature timestamp"))?;
+        if (now - timestamp).abs() > webhook_signature_tolerance() as i64 {
+            return Err(client_error!(401, "Expired signature"));
+        }
+        let signature = self
+            .signature
+            .as_ref()
+            .and_then(|signature| hex::decode(signature.trim_start_matches("sha256=")).ok())
+            .ok_or(client_error!(401, "Missing signature"))?;
+
+        let payload = format!("{}.{}.{}", timestamp, self.path, body);
+        if secrets
+            .iter()
+            .any(|secret| is_signed_with(secret, &payload, &signature))
+        {
+            Ok(signature)
+        } else {
+            Err(client_error!(401, "Invalid signature"))
+        }
+    }
+}
+
+pub(super) fn replay_key(signature: &[u8]) -> String {
+    namespaced(&format!("{}_{}", SIGNATURE_PREFIX, hex::encode(signature)))
+}
+
+fn is_signed_with(secret: &str, payload: &str, signature: &[u8]) -> bool {
+    let mut mac =
+        Hmac::<Sha256>::new_varkey(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(payload.as_bytes());
+    mac.verify(signature).is_ok()
+}
+
+/// `WEBHOOK_TOKEN` as part of the path, only accepted while `LEGACY_WEBHOOK_TOKEN_ENABLED` is set
+pub fn check_legacy_token(token: &str) -> ApiResult<()> {
+    if !legacy_webhook_token_enabled() || token != webhook_token() {
+        bail!("Invalid token");
+    }
+    Ok(())
+}
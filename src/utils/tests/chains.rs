@@ -0,0 +1,36 @@
+use crate::utils::chains::{chain_scoped_path, split_chain_scope};
+
+#[test]
+fn split_chain_scope_of_scoped_uri() {
+    assert_eq!(
+        split_chain_scope("/v1/chains/4/safes/0x1230B3d59858296A31053C1b8562Ecf89A2f888b"),
+        Some((
+            4,
+            String::from("/v1/safes/0x1230B3d59858296A31053C1b8562Ecf89A2f888b")
+        ))
+    );
+    assert_eq!(
+        split_chain_scope("/v1/chains/100/safes/0x1/transactions/queued?timezone_offset=0"),
+        Some((
+            100,
+            String::from("/v1/safes/0x1/transactions/queued?timezone_offset=0")
+        ))
+    );
+}
+
+#[test]
+fn split_chain_scope_of_other_uris() {
+    assert_eq!(split_chain_scope("/v1/safes/0x1"), None);
+    assert_eq!(split_chain_scope("/v1/chains/4"), None);
+    assert_eq!(split_chain_scope("/v1/chains/4/"), None);
+    assert_eq!(split_chain_scope("/v1/chains/rinkeby/safes/0x1"), None);
+}
+
+#[test]
+fn chain_scoped_path_of_v1_path() {
+    assert_eq!(
+        chain_scoped_path(4, "/v1/safes/0x1/transactions/history?page_url=abc"),
+        "/v1/chains/4/safes/0x1/transactions/history?page_url=abc"
+    );
+    assert_eq!(chain_scoped_path(4, "/about"), "/about");
+}
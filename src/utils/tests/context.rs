@@ -0,0 +1,15 @@
+use crate::routes::chains::get_chains;
+use rocket::http::Status;
+use rocket::local::asynchronous::Client;
+
+#[rocket::async_test]
+async fn context_without_cache_fails_request() {
+    let rocket = rocket::build()
+        .mount("/", routes![get_chains])
+        .manage(reqwest::Client::new());
+    let client = Client::debug(rocket).await.expect("valid rocket instance");
+
+    let response = client.get("/v1/chains").dispatch().await;
+
+    assert_eq!(response.status(), Status::InternalServerError);
+}
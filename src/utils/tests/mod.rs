@@ -1,6 +1,9 @@
+mod chains;
+mod context;
 mod data_decoded_utils;
 mod errors;
 mod json;
 mod method_names;
+mod signatures;
 mod transactions;
 mod urls;
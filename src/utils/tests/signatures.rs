@@ -0,0 +1,118 @@
+use crate::cache::memory::InMemoryCache;
+use crate::cache::MockCache;
+use crate::utils::signatures::Signature;
+
+const BODY: &str = "{\"address\":\"0x1\"}";
+const TIMESTAMP: i64 = 1620000000;
+// HMAC-SHA256 of `1620000000./v1/hook/update.{"address":"0x1"}` with "new_secret"
+const SIGNATURE: &str = "ea246616a6c24122f3ab94317d2f48b356bf69f1c2df78294e17aaf84a66c4ad";
+
+fn signature(timestamp: Option<&str>, signature: Option<&str>) -> Signature {
+    Signature::new(
+        String::from("/v1/hook/update"),
+        timestamp.map(String::from),
+        signature.map(String::from),
+    )
+}
+
+fn secrets() -> Vec<String> {
+    vec![String::from("old_secret"), String::from("new_secret")]
+}
+
+#[test]
+fn signature_valid_with_any_secret() {
+    let signature = signature(Some("1620000000"), Some(SIGNATURE));
+
+    assert!(signature.verify_with(BODY, &secrets(), TIMESTAMP).is_ok());
+    assert!(signature
+        .verify_with(BODY, &[String::from("new_secret")], TIMESTAMP + 10)
+        .is_ok());
+}
+
+#[rocket::async_test]
+async fn signature_accepted_once() {
+    let cache = InMemoryCache::new(10);
+    let first = signature(Some("1620000000"), Some(SIGNATURE));
+    let replayed = signature(Some("1620000000"), Some(&format!("sha256={}", SIGNATURE)));
+
+    assert!(first
+        .verify_once(&cache, BODY, &secrets(), TIMESTAMP)
+        .await
+        .is_ok());
+    let actual = replayed
+        .verify_once(&cache, BODY, &secrets(), TIMESTAMP + 10)
+        .await
+        .expect_err("Signature was already used");
+
+    assert_eq!(actual.status, 401);
+}
+
+#[rocket::async_test]
+async fn signature_rejected_without_cache_backend() {
+    let mut mock_cache = MockCache::new();
+    mock_cache
+        .expect_create_if_absent()
+        .times(1)
+        .return_const(None::<bool>);
+    let signature = signature(Some("1620000000"), Some(SIGNATURE));
+
+    let actual = signature
+        .verify_once(&mock_cache, BODY, &secrets(), TIMESTAMP)
+        .await
+        .expect_err("Replays can't be detected");
+
+    assert_eq!(actual.status, 503);
+}
+
+#[test]
+fn signature_with_algorithm_prefix() {
+    let signature = signature(Some("1620000000"), Some(&format!("sha256={}", SIGNATURE)));
+
+    assert!(signature.verify_with(BODY, &secrets(), TIMESTAMP).is_ok());
+}
+
+#[test]
+fn signature_invalid_for_other_body() {
+    let signature = signature(Some("1620000000"), Some(SIGNATURE));
+
+    let actual = signature
+        .verify_with("{\"address\":\"0x2\"}", &secrets(), TIMESTAMP)
+        .expect_err("Body was changed");
+
+    assert_eq!(actual.status, 401);
+}
+
+#[test]
+fn signature_invalid_for_unknown_secret() {
+    let signature = signature(Some("1620000000"), Some(SIGNATURE));
+
+    let actual = signature
+        .verify_with(BODY, &[String::from("old_secret")], TIMESTAMP)
+        .expect_err("Secret was rotated out");
+
+    assert_eq!(actual.status, 401);
+}
+
+#[test]
+fn signature_expired() {
+    let signature = signature(Some("1620000000"), Some(SIGNATURE));
+
+    let actual = signature
+        .verify_with(BODY, &secrets(), TIMESTAMP + 301)
+        .expect_err("Signature is too old");
+
+    assert_eq!(actual.status, 401);
+}
+
+#[test]
+fn signature_missing_headers() {
+    assert!(signature(None, Some(SIGNATURE))
+        .verify_with(BODY, &secrets(), TIMESTAMP)
+        .is_err());
+    assert!(signature(Some("1620000000"), None)
+        .verify_with(BODY, &secrets(), TIMESTAMP)
+        .is_err());
+    assert!(signature(Some("1620000000"), Some("not hex"))
+        .verify_with(BODY, &secrets(), TIMESTAMP)
+        .is_err());
+}
@@ -1,5 +1,5 @@
 use crate::cache::cache_operations::RequestCached;
-use crate::config::{base_transaction_service_url, chain_id, transaction_request_timeout};
+use crate::config::transaction_request_timeout;
 use crate::models::backend::transactions::MultisigTransaction;
 use crate::providers::info::SAFE_V_1_3_0;
 use crate::providers::info::{DefaultInfoProvider, InfoProvider};
@@ -20,7 +20,7 @@ pub const ERC191_BYTE: &'static str = "19";
 pub const ERC191_VERSION: &'static str = "01";
 
 pub async fn fetch_rejections(
-    context: &Context<'_>,
+    context: &Context,
     safe_address: &str,
     nonce: u64,
 ) -> Option<Vec<String>> {
@@ -38,7 +38,7 @@ pub async fn fetch_rejections(
     let domain_hash = if is_legacy {
         domain_hash_v100(&safe_address)
     } else {
-        domain_hash_v130(&safe_address)
+        domain_hash_v130(context.chain().chain_id, &safe_address)
     };
 
     let safe_tx_hash = to_hex_string!(hash(safe_address, nonce, domain_hash).to_vec());
@@ -71,14 +71,14 @@ pub(super) fn hash(safe_address: Address, nonce: u64, domain_hash: [u8; 32]) ->
     keccak256(encoded)
 }
 
-pub(super) fn domain_hash_v130(safe_address: &Address) -> [u8; 32] {
+pub(super) fn domain_hash_v130(chain_id: u64, safe_address: &Address) -> [u8; 32] {
     let domain_separator: H256 =
         serde_json::from_value(serde_json::Value::String(DOMAIN_SEPARATOR_TYPEHASH.into()))
             .unwrap();
 
     let encoded = ethabi::encode(&[
         ethabi::Token::Uint(Uint::from(domain_separator.0)),
-        ethabi::Token::Uint(Uint::from(chain_id())),
+        ethabi::Token::Uint(Uint::from(chain_id)),
         ethabi::Token::Address(Address::from(safe_address.0)),
     ]);
     keccak256(encoded)
@@ -128,12 +128,12 @@ pub(super) fn use_legacy_domain_separator(version: Option<Version>) -> bool {
 
 // We silently fail if the cancellation transaction is not found
 async fn fetch_cancellation_tx(
-    context: &Context<'_>,
+    context: &Context,
     safe_tx_hash: String,
 ) -> Option<MultisigTransaction> {
     let url = format!(
         "{}/v1/multisig-transactions/{}/",
-        base_transaction_service_url(),
+        context.chain().base_transaction_service_url(),
         safe_tx_hash
     );
     let body = RequestCached::new(url)